@@ -16,7 +16,7 @@ public enum AppAlertState: Equatable {
     case invalidFormat(String)
     case addressWrongNetwork(address: Address, network: Network, currentNetwork: Network)
     case noWalletSelected(Address)
-    case foundAddress(Address, Amount?)
+    case foundAddress(Address, Amount?, label: String?, message: String?)
     case noCameraPermission
     case failedToScanQr(error: String)
     case noUnsignedTransactionFound(TxId)
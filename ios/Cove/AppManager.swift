@@ -15,6 +15,9 @@ import SwiftUI
     var alertState: TaggedItem<AppAlertState>? = .none
     var sheetState: TaggedItem<AppSheetState>? = .none
 
+    // partially signed psbt shown in the psbt sheet
+    var importedPsbt: Psbt? = .none
+
     var colorSchemeSelection = Database().globalConfig().colorScheme()
     var selectedNode = Database().globalConfig().selectedNode()
     var selectedFiatCurrency = Database().globalConfig().selectedFiatCurrency()
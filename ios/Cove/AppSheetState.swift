@@ -7,4 +7,5 @@
 
 public enum AppSheetState {
     case qr
+    case psbt
 }
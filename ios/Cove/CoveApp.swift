@@ -80,9 +80,10 @@ struct CoveApp: App {
                 address: address, network: network, currentNetwork: currentNetwork
             ):
                 "The address \(address) is on the wrong network. You are on \(currentNetwork), and the address was for \(network)."
-            case let .noWalletSelected(address),
-                 let .foundAddress(address, _):
+            case let .noWalletSelected(address):
                 String(address)
+            case let .foundAddress(address, _, label: label, message: message):
+                [label, message, String(address)].compactMap { $0 }.joined(separator: "\n\n")
             case .noCameraPermission:
                 "Please allow camera access in Settings to use this feature."
             case let .failedToScanQr(error):
@@ -130,7 +131,7 @@ struct CoveApp: App {
             Button("Cancel") {
                 app.alertState = .none
             }
-        case let .foundAddress(address, amount):
+        case let .foundAddress(address, amount, label: label, message: message):
             Button("Copy Address") {
                 UIPasteboard.general.string = String(address)
             }
@@ -138,7 +139,7 @@ struct CoveApp: App {
             if let id = Database().globalConfig().selectedWallet() {
                 Button("Send To Address") {
                     let route = RouteFactory().sendSetAmount(
-                        id: id, address: address, amount: amount
+                        id: id, address: address, amount: amount, label: label, message: message
                     )
                     app.pushRoute(route)
                     app.alertState = .none
@@ -261,7 +262,12 @@ struct CoveApp: App {
         }
 
         let amount = addressWithNetwork.amount()
-        app.alertState = TaggedItem(.foundAddress(address, amount))
+        app.alertState = TaggedItem(
+            .foundAddress(
+                address, amount,
+                label: addressWithNetwork.label(),
+                message: addressWithNetwork.message()
+            ))
     }
 
     func handleTransaction(_ transaction: BitcoinTransaction) {
@@ -286,6 +292,39 @@ struct CoveApp: App {
         app.pushRoute(route)
     }
 
+    func handlePsbt(_ psbt: Psbt) {
+        Log.debug("Received PSBT: \(psbt.txId().asHashString())")
+
+        // signed by a hardware wallet, merge into the saved unsigned transaction
+        let db = Database().unsignedTransactions()
+        if let txnRecord = db.getTx(txId: psbt.txId()) {
+            do {
+                let transaction = try txnRecord.finalizeSignedPsbt(signed: psbt)
+                let route = RouteFactory().sendConfirm(
+                    id: txnRecord.walletId(), details: txnRecord.confirmDetails(),
+                    signedTransaction: transaction
+                )
+
+                app.pushRoute(route)
+            } catch {
+                Log.error("Unable to finalize signed PSBT: \(error)")
+                app.alertState = .init(.invalidFormat(error.localizedDescription))
+            }
+
+            return
+        }
+
+        // fully signed, continue the same way as a signed transaction
+        if let transaction = try? psbt.extractTransaction() {
+            handleTransaction(transaction)
+            return
+        }
+
+        // still needs signatures, sign it here or combine it with a copy signed somewhere else
+        app.importedPsbt = psbt
+        app.sheetState = .init(.psbt)
+    }
+
     func handleFileOpen(_ url: URL) {
         let fileHandler = FileHandler(filePath: url.absoluteString)
 
@@ -300,6 +339,8 @@ struct CoveApp: App {
                 handleAddress(addressWithNetwork)
             case let .transaction(txn):
                 handleTransaction(txn)
+            case let .psbt(psbt):
+                handlePsbt(psbt)
             }
         } catch {
             switch error {
@@ -336,6 +377,8 @@ struct CoveApp: App {
                 handleAddress(addressWithNetwork)
             case let .transaction(transaction):
                 handleTransaction(transaction)
+            case let .psbt(psbt):
+                handlePsbt(psbt)
             }
         } catch {
             switch error {
@@ -357,6 +400,10 @@ struct CoveApp: App {
         switch state.item {
         case .qr:
             QrCodeScanView(app: app, scannedCode: $scannedCode)
+        case .psbt:
+            if let psbt = app.importedPsbt {
+                PsbtSheetView(app: app, psbt: psbt)
+            }
         }
     }
 
@@ -71,7 +71,8 @@ extension PriceResponse: Equatable {
 public extension SendRoute {
     func id() -> WalletId {
         switch self {
-        case let .setAmount(id, address: _, amount: _): id
+        case let .setAmount(id, address: _, amount: _, label: _, message: _): id
+        case let .setRecipients(id: id, recipients: _): id
         case let .confirm(id: id, details: _, signedTransaction: _): id
         case let .hardwareExport(id: id, details: _): id
         }
@@ -189,13 +189,13 @@ struct QrCodeImportScreen: View {
             guard let multiQr else { return }
 
             // single QR
-            if !multiQr.isBbqr() {
+            if !multiQr.isMultiPart() {
                 scanComplete = true
                 scannedCode = TaggedString(stringValue)
                 return
             }
 
-            // BBQr
+            // BBQr or animated UR
             do {
                 let result = try multiQr.addPart(qr: stringValue)
                 partsLeft = Int(result.partsLeft())
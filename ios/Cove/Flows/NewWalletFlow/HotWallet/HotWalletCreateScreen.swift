@@ -30,6 +30,9 @@ struct WordsView: View {
     var groupedWords: [[GroupedWord]]
     @State private var tabIndex = 0
     @State private var showConfirmationAlert = false
+
+    // optional BIP39 passphrase, never saved
+    @State private var passphrase = ""
     @Environment(\.dismiss) private var dismiss
     @Environment(\.navigate) private var navigate
 
@@ -91,10 +94,15 @@ struct WordsView: View {
             VStack(spacing: 24) {
                 Group {
                     if tabIndex == lastIndex {
+                        SecureField("Passphrase (optional)", text: $passphrase)
+                            .font(.subheadline)
+                            .textInputAutocapitalization(.never)
+                            .autocorrectionDisabled()
+
                         Button(action: {
                             do {
                                 // save the wallet
-                                let walletId = try manager.rust.saveWallet().id
+                                let walletId = try manager.rust.saveWallet(passphrase: passphrase).id
 
                                 navigate(
                                     HotWalletRoute.verifyWords(walletId).intoRoute()
@@ -81,6 +81,12 @@ struct HotWalletImportScreen: View {
     @State private var tabIndex: Int = 0
     @State private var duplicateWallet: DuplicateWalletItem? = .none
 
+    // optional BIP39 passphrase, never saved
+    @State private var passphrase: String = ""
+
+    // BIP44/49/84/86 account, 0 for most wallets
+    @State private var account: UInt32 = 0
+
     @FocusState var focusField: ImportFieldNumber?
 
     @State var manager: ImportWalletManager = .init()
@@ -179,7 +185,9 @@ struct HotWalletImportScreen: View {
 
     func importWallet() {
         do {
-            let walletMetadata = try manager.rust.importWallet(enteredWords: enteredWords)
+            let walletMetadata = try manager.rust.importWallet(
+                enteredWords: enteredWords, passphrase: passphrase, account: account
+            )
             try app.rust.selectWallet(id: walletMetadata.id)
             app.resetRoute(to: .selectedWallet(walletMetadata.id))
         } catch let error as ImportWalletError {
@@ -260,6 +268,16 @@ struct HotWalletImportScreen: View {
 
     @ViewBuilder
     var ImportButton: some View {
+        SecureField("Passphrase (optional)", text: $passphrase)
+            .font(.subheadline)
+            .textInputAutocapitalization(.never)
+            .autocorrectionDisabled()
+            .padding(.vertical, 12)
+
+        Stepper("Account: \(account)", value: $account, in: 0 ... 100)
+            .font(.subheadline)
+            .padding(.bottom, 12)
+
         Button("Import wallet") {
             importWallet()
         }
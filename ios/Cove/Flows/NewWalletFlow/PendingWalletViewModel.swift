@@ -12,6 +12,7 @@ import SwiftUI
     var rust: RustPendingWalletManager
     var numberOfWords: NumberOfBip39Words
     var bip39Words: [String]
+    var addressType: WalletAddressType = .nativeSegwit
 
     public init(numberOfWords: NumberOfBip39Words) {
         let rust = RustPendingWalletManager(numberOfWords: numberOfWords)
@@ -31,6 +32,9 @@ import SwiftUI
                 case let .words(numberOfBip39Words):
                     self.numberOfWords = numberOfBip39Words
                     self.bip39Words = self.rust.bip39Words()
+
+                case let .addressType(addressType):
+                    self.addressType = addressType
                 }
             }
         }
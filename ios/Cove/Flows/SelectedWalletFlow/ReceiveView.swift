@@ -126,7 +126,7 @@ private struct AddressView: View {
             if let addressInfo {
                 GroupBox {
                     VStack {
-                        Image(uiImage: generateQRCode(from: addressInfo.adressString()))
+                        Image(uiImage: generateQRCode(from: addressInfo.paymentUri()))
                             .interpolation(.none)
                             .resizable()
                             .scaledToFit()
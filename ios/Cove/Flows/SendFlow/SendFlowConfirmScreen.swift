@@ -22,6 +22,11 @@ struct SendFlowConfirmScreen: View {
     // private
     @State private var isShowingAlert = false
     @State private var sendState: SendState = .idle
+    @State private var broadcastStatus: BroadcastStatus = .accepted
+
+    // BIP39 passphrase, asked for at send time and never saved
+    @State private var isShowingPassphrasePrompt = false
+    @State private var passphrase = ""
 
     // popover to change btc and sats
     @State private var showingMenu: Bool = false
@@ -145,21 +150,12 @@ struct SendFlowConfirmScreen: View {
             .background(Color.coveBg)
 
             SwipeToSendView(sendState: $sendState) {
-                sendState = .sending
-                Task {
-                    do {
-                        if let txn = signedTransaction {
-                            _ = try await manager.rust.broadcastTransaction(signedTransaction: txn)
-                        } else {
-                            _ = try await manager.rust.signAndBroadcastTransaction(
-                                psbt: details.psbt())
-                        }
-                        sendState = .sent
-                        isShowingAlert = true
-                    } catch {
-                        sendState = .error
-                    }
+                if signedTransaction == nil, metadata.hasPassphrase {
+                    isShowingPassphrasePrompt = true
+                    return
                 }
+
+                send()
             }
             .frame(maxWidth: .infinity)
             .padding(.horizontal)
@@ -170,6 +166,16 @@ struct SendFlowConfirmScreen: View {
                 // accessing seed words for signing, lock so we can re-auth
                 if metadata.walletType == .hot { auth.lock() }
             }
+            .alert("Enter Passphrase", isPresented: $isShowingPassphrasePrompt) {
+                SecureField("Passphrase", text: $passphrase)
+                Button("Sign & Send") { send() }
+                Button("Cancel", role: .cancel) {
+                    passphrase = ""
+                    sendState = .idle
+                }
+            } message: {
+                Text("This wallet was created with a passphrase, enter it to sign the transaction")
+            }
             .alert(
                 "Sent!",
                 isPresented: $isShowingAlert,
@@ -179,11 +185,41 @@ struct SendFlowConfirmScreen: View {
                     }
                 },
                 message: {
-                    Text("Transaction was successfully sent!")
+                    switch broadcastStatus {
+                    case .accepted:
+                        Text("Transaction was successfully sent!")
+                    case .unverified:
+                        Text(
+                            "Transaction was sent to your compact filter peer. Peers don't say if they reject a transaction, so it shows as unconfirmed until it is found in a block"
+                        )
+                    }
                 }
             )
         }
     }
+
+    private func send() {
+        sendState = .sending
+        Task {
+            do {
+                if let txn = signedTransaction {
+                    broadcastStatus = try await manager.rust.broadcastTransaction(
+                        signedTransaction: txn
+                    )
+                } else {
+                    broadcastStatus = try await manager.rust.signAndBroadcastTransaction(
+                        details: details, passphrase: passphrase
+                    )
+                }
+                sendState = .sent
+                isShowingAlert = true
+            } catch {
+                sendState = .error
+            }
+
+            passphrase = ""
+        }
+    }
 }
 
 #if DEBUG
@@ -37,10 +37,15 @@ public struct SendFlowContainer: View {
     @ViewBuilder
     func sendRouteToScreen(sendRoute: SendRoute, manager: WalletManager) -> some View {
         switch sendRoute {
-        case let .setAmount(id: id, address: address, amount: amount):
+        case let .setAmount(
+            id: id, address: address, amount: amount, label: label, message: message
+        ):
             SendFlowSetAmountScreen(
-                id: id, manager: manager, address: address?.string() ?? "", amount: amount
+                id: id, manager: manager, address: address?.string() ?? "", amount: amount,
+                label: label, message: message
             )
+        case let .setRecipients(id: id, recipients: recipients):
+            SendFlowSetRecipientsScreen(id: id, manager: manager, recipients: recipients)
         case let .confirm(id: id, details: details, signedTransaction: signedTransaction):
             SendFlowConfirmScreen(
                 id: id, manager: manager,
@@ -110,6 +115,14 @@ public struct SendFlowContainer: View {
             switch alert.item {
             case let .confirmDetails(error): error
             case let .signAndBroadcast(error): error
+            case let .absurdFee(fee: fee, sendingAmount: sendingAmount):
+                "The fee of \(fee.satsStringWithUnit()) is too high for sending \(sendingAmount.satsStringWithUnit()), transaction was not sent"
+            case let .dustOutput(address: address, amount: amount):
+                "The output of \(amount.satsStringWithUnit()) to \(address.string()) is too small to be relayed, transaction was not sent"
+            case let .dustScriptOutput(script: script, amount: amount):
+                "The output of \(amount.satsStringWithUnit()) to script \(script) is too small to be relayed, transaction was not sent"
+            case let .transactionMismatch(error):
+                "The signed transaction does not match the one you approved, transaction was not sent. \(error)"
             }
 
         return
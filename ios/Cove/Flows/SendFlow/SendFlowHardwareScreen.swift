@@ -247,9 +247,7 @@ struct SendFlowHardwareScreen: View {
 
     func handleScanned(_ txn: StringOrData) {
         do {
-            let bitcoinTransaction = try BitcoinTransaction.tryFromStringOrData(stringOrData: txn)
-            let db = Database().unsignedTransactions()
-            let txnRecord = try db.getTxThrow(txId: bitcoinTransaction.txId())
+            let (txnRecord, bitcoinTransaction) = try txnRecordAndSignedTxn(txn)
 
             let route = RouteFactory()
                 .sendConfirm(
@@ -267,12 +265,35 @@ struct SendFlowHardwareScreen: View {
     func txnRecordAndSignedTxn(_ hex: String) throws -> (
         UnsignedTransactionRecord, BitcoinTransaction
     ) {
-        let bitcoinTransaction = try BitcoinTransaction(txHex: hex)
+        try txnRecordAndSignedTxn(.string(hex))
+    }
+
+    /// Accepts either a signed transaction or a signed psbt returned by the hardware wallet
+    func txnRecordAndSignedTxn(_ txn: StringOrData) throws -> (
+        UnsignedTransactionRecord, BitcoinTransaction
+    ) {
         let db = Database().unsignedTransactions()
+
+        if let psbt = signedPsbt(txn) {
+            let record = try db.getTxThrow(txId: psbt.txId())
+            let bitcoinTransaction = try record.finalizeSignedPsbt(signed: psbt)
+            return (record, bitcoinTransaction)
+        }
+
+        let bitcoinTransaction = try BitcoinTransaction.tryFromStringOrData(stringOrData: txn)
         let record = try db.getTxThrow(txId: bitcoinTransaction.txId())
         return (record, bitcoinTransaction)
     }
 
+    func signedPsbt(_ txn: StringOrData) -> Psbt? {
+        switch txn {
+        case let .string(string):
+            return try? Psbt.tryFromString(psbt: string)
+        case let .data(data):
+            return try? Psbt(data: data)
+        }
+    }
+
     @ViewBuilder
     var AccountSection: some View {
         VStack {
@@ -383,6 +404,16 @@ struct SendFlowHardwareScreen: View {
             }
         }
 
+        Button("QR Code (UR)") {
+            do {
+                let qrs = try details.psbtToUr()
+                sheetState = .init(.exportQr(qrs))
+            } catch {
+                Log.error("Failed to convert PSBT to UR: \(error)")
+                alertState = .init(.bbqrError(error.localizedDescription))
+            }
+        }
+
         Button("NFC") {
             nfcWriter.writeToTag(data: details.psbtBytes())
         }
@@ -475,7 +506,7 @@ struct SendFlowHardwareScreen: View {
         case let .bbqrError(message):
             return AlertBuilder(
                 title: "QR Error",
-                message: "Unable to create BBQr: \(message)",
+                message: "Unable to create QR code: \(message)",
                 actions: singleOkCancel
             )
         case let .fileError(message):
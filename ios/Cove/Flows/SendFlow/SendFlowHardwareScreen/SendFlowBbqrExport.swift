@@ -21,7 +21,7 @@ struct SendFlowBbqrExport: View {
             Text("Scan this QR")
                 .font(.headline)
 
-            Text("Scan this BBQr with your hardware wallet to sign your transaction")
+            Text("Scan this QR with your hardware wallet to sign your transaction")
                 .font(.footnote)
                 .foregroundStyle(.secondary)
                 .multilineTextAlignment(.center)
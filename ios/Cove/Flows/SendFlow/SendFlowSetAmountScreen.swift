@@ -24,6 +24,10 @@ struct SendFlowSetAmountScreen: View {
     @State var address: String = ""
     @State var amount: Amount? = nil
 
+    // from a BIP21 payment request
+    @State var label: String? = nil
+    @State var message: String? = nil
+
     // private
     @State private var isLoading: Bool = true
     @State private var loadingOpacity: CGFloat = 1
@@ -216,6 +220,10 @@ struct SendFlowSetAmountScreen: View {
                     try? manager.rust.saveUnsignedTransaction(details: confirmDetails)
                 }
 
+                if let label, !label.isEmpty {
+                    try? await manager.rust.setAddressLabel(address: address, label: label)
+                }
+
                 let route =
                     switch metadata.walletType {
                     case .hot: RouteFactory().sendConfirm(id: id, details: confirmDetails)
@@ -231,6 +239,25 @@ struct SendFlowSetAmountScreen: View {
         }
     }
 
+    // move to the batch screen with the current recipient as the first one
+    private func addRecipient() {
+        guard validate(displayAlert: true) else { return }
+        guard let sendAmountSats else {
+            return setAlertState(.invalidNumber)
+        }
+
+        guard let address = try? Address.fromString(address: address) else {
+            return setAlertState(.invalidAddress(address))
+        }
+
+        let recipient = AddressAndAmount(
+            address: address, amount: Amount.fromSat(sats: UInt64(sendAmountSats))
+        )
+
+        presenter.focusField = .none
+        app.pushRoute(RouteFactory().sendSetRecipients(id: id, recipients: [recipient]))
+    }
+
     // doing it this way prevents an alert popping up when the user just goes back
     private func setAlertState(_ alertState: AlertState) {
         presenter.setAlertState(alertState)
@@ -263,6 +290,14 @@ struct SendFlowSetAmountScreen: View {
                         VStack {
                             Divider()
                             EnterAddressView(address: $address)
+
+                            if let description = [label, message].compactMap({ $0 }).first {
+                                Text(description)
+                                    .font(.footnote)
+                                    .foregroundStyle(.secondary)
+                                    .frame(maxWidth: .infinity, alignment: .leading)
+                            }
+
                             Divider()
                         }
 
@@ -281,6 +316,9 @@ struct SendFlowSetAmountScreen: View {
 
                             // Next Button
                             NextButtonBottom
+
+                            // Batch send, continues with this recipient in the list
+                            AddRecipientButton
                         }
                     }
 
@@ -627,6 +665,8 @@ struct SendFlowSetAmountScreen: View {
         }
 
         address = addressWithNetwork.address().string()
+        label = addressWithNetwork.label()
+        message = addressWithNetwork.message()
         guard validateAddress(address, displayAlert: true) else { return }
 
         if let amount = addressWithNetwork.amount() {
@@ -1004,6 +1044,15 @@ struct SendFlowSetAmountScreen: View {
         .padding(.vertical, 10)
     }
 
+    var AddRecipientButton: some View {
+        Button(action: addRecipient) {
+            Label("Add Another Recipient", systemImage: "plus")
+                .font(.footnote)
+                .fontWeight(.semibold)
+        }
+        .disabled(!validate())
+    }
+
     @ViewBuilder
     private func SheetContent(_ state: TaggedItem<SheetState>) -> some View {
         switch state.item {
@@ -0,0 +1,268 @@
+//
+//  SendFlowSetRecipientsScreen.swift
+//  Cove
+//
+
+import Foundation
+import SwiftUI
+
+// MARK: SendFlowSetRecipientsScreen
+
+private typealias SheetState = SendFlowPresenter.SheetState
+
+/// Review and edit every recipient of a batch send, all of them are paid by one transaction
+struct SendFlowSetRecipientsScreen: View {
+    @Environment(SendFlowPresenter.self) private var presenter
+    @Environment(AppManager.self) private var app
+    @Environment(\.colorScheme) private var colorScheme
+
+    let id: WalletId
+    @State var manager: WalletManager
+    @State var recipients: [AddressAndAmount]
+
+    // new recipient inputs
+    @State private var newAddress: String = ""
+    @State private var newAmount: String = ""
+
+    // fees
+    @State private var selectedPresentationDetent: PresentationDetent = .height(440)
+    @State private var selectedFeeRate: FeeRateOptionWithTotalFee? = .none
+    @State private var feeRateOptions: FeeRateOptionsWithTotalFee? = .none
+    @State private var feeRateOptionsBase: FeeRateOptions? = .none
+
+    private var metadata: WalletMetadata {
+        manager.walletMetadata
+    }
+
+    private var totalSending: Amount {
+        let sats = recipients.reduce(UInt64(0)) { $0 + $1.amount.asSats() }
+        return Amount.fromSat(sats: sats)
+    }
+
+    private var totalSpent: Amount? {
+        guard let totalFee = selectedFeeRate?.totalFee() else { return .none }
+        return Amount.fromSat(sats: totalSending.asSats() + totalFee.asSats())
+    }
+
+    private var newAmountSats: UInt64? {
+        let newAmount = newAmount.replacingOccurrences(of: ",", with: "")
+        guard let amount = Double(newAmount), amount > 0 else { return .none }
+
+        switch metadata.selectedUnit {
+        case .btc:
+            return UInt64(amount * 100_000_000)
+        case .sat:
+            return UInt64(amount)
+        }
+    }
+
+    private var newRecipient: AddressAndAmount? {
+        let address = newAddress.trimmingCharacters(in: .whitespacesAndNewlines)
+        guard let address = try? Address.fromString(address: address) else { return .none }
+        guard let newAmountSats else { return .none }
+
+        return AddressAndAmount(address: address, amount: Amount.fromSat(sats: newAmountSats))
+    }
+
+    // MARK: Actions
+
+    private func addRecipient() {
+        guard let newRecipient else { return }
+
+        recipients.append(newRecipient)
+        newAddress = ""
+        newAmount = ""
+
+        Task { await getFeeRateOptions() }
+    }
+
+    private func removeRecipient(at offsets: IndexSet) {
+        recipients.remove(atOffsets: offsets)
+
+        if recipients.isEmpty {
+            feeRateOptions = .none
+            selectedFeeRate = .none
+            return
+        }
+
+        Task { await getFeeRateOptions() }
+    }
+
+    private func getFeeRateOptions() async {
+        guard !recipients.isEmpty else { return }
+
+        if feeRateOptionsBase == nil {
+            feeRateOptionsBase = try? await manager.rust.getFeeOptions()
+        }
+
+        do {
+            let feeRateOptions = try await manager.rust.feeRateOptionsWithTotalFeeForBatch(
+                feeRateOptions: feeRateOptionsBase,
+                recipients: recipients
+            )
+
+            await MainActor.run {
+                self.feeRateOptions = feeRateOptions
+
+                selectedFeeRate =
+                    switch selectedFeeRate?.feeSpeed() {
+                    case .fast: feeRateOptions.fast()
+                    case .slow: feeRateOptions.slow()
+                    case .custom: feeRateOptions.custom() ?? feeRateOptions.medium()
+                    case .medium, nil: feeRateOptions.medium()
+                    }
+            }
+        } catch {
+            Log.error("Unable to get batch feeRateOptions: \(error)")
+        }
+    }
+
+    // create the batch psbt and send to next screen
+    private func next() {
+        guard !recipients.isEmpty else { return }
+        guard let feeRate = selectedFeeRate else {
+            return presenter.setAlertState(.unableToGetFeeRate)
+        }
+
+        Task {
+            do {
+                let confirmDetails = try await manager.rust.getConfirmDetailsForBatch(
+                    recipients: recipients,
+                    feeRate: feeRate.feeRate()
+                )
+
+                if case .cold = metadata.walletType {
+                    try? manager.rust.saveUnsignedTransaction(details: confirmDetails)
+                }
+
+                let route =
+                    switch metadata.walletType {
+                    case .hot: RouteFactory().sendConfirm(id: id, details: confirmDetails)
+                    case .cold: RouteFactory().sendHardwareExport(id: id, details: confirmDetails)
+                    }
+
+                app.pushRoute(route)
+            } catch {
+                // error alert is displayed at the top level container, but we can log it here
+                Log.error("unable to get batch confirm details: \(error)")
+            }
+        }
+    }
+
+    var body: some View {
+        VStack(spacing: 0) {
+            SendFlowHeaderView(manager: manager, amount: manager.balance.spendable())
+
+            List {
+                Section(header: Text("Recipients")) {
+                    ForEach(Array(recipients.enumerated()), id: \.offset) { _, recipient in
+                        RecipientRow(recipient)
+                    }
+                    .onDelete(perform: removeRecipient)
+                }
+
+                Section(header: Text("Add Recipient")) {
+                    TextField("Address", text: $newAddress)
+                        .textInputAutocapitalization(.never)
+                        .autocorrectionDisabled()
+
+                    TextField(
+                        "Amount (\(metadata.selectedUnit == .btc ? "BTC" : "sats"))",
+                        text: $newAmount
+                    )
+                    .keyboardType(.decimalPad)
+
+                    Button("Add", action: addRecipient)
+                        .disabled(newRecipient == nil)
+                }
+
+                if let selectedFeeRate {
+                    Section(header: Text("Summary")) {
+                        Button(action: { presenter.sheetState = TaggedItem(.fee) }) {
+                            HStack {
+                                Text("Network Fee")
+                                Spacer()
+                                Text(manager.amountFmtUnit(selectedFeeRate.totalFee()))
+                                Image(systemName: "chevron.right")
+                                    .font(.caption)
+                                    .foregroundStyle(.secondary)
+                            }
+                        }
+                        .foregroundStyle(.primary)
+
+                        HStack {
+                            Text("Total Sending")
+                            Spacer()
+                            Text(manager.amountFmtUnit(totalSending))
+                        }
+
+                        if let totalSpent {
+                            HStack {
+                                Text("Total Spent")
+                                    .fontWeight(.semibold)
+                                Spacer()
+                                Text(manager.amountFmtUnit(totalSpent))
+                                    .fontWeight(.semibold)
+                            }
+                        }
+                    }
+                }
+            }
+            .scrollContentBackground(.hidden)
+            .background(colorScheme == .light ? .white : .black)
+
+            Button(action: next) {
+                Text("Next")
+                    .font(.footnote)
+                    .fontWeight(.semibold)
+                    .frame(maxWidth: .infinity)
+                    .padding()
+                    .background(Color.midnightBtn)
+                    .foregroundColor(.white)
+                    .cornerRadius(10)
+            }
+            .disabled(recipients.isEmpty || selectedFeeRate == nil)
+            .padding()
+        }
+        .navigationTitle("Send to \(recipients.count) Recipients")
+        .navigationBarTitleDisplayMode(.inline)
+        .task { await getFeeRateOptions() }
+        .sheet(item: presenter.sheetStateBinding, content: SheetContent)
+    }
+
+    @ViewBuilder
+    private func RecipientRow(_ recipient: AddressAndAmount) -> some View {
+        VStack(alignment: .leading, spacing: 4) {
+            Text(manager.amountFmtUnit(recipient.amount))
+                .font(.subheadline)
+                .fontWeight(.medium)
+
+            Text(recipient.address.spacedOut())
+                .font(.caption)
+                .foregroundStyle(.secondary)
+                .lineLimit(2)
+        }
+    }
+
+    @ViewBuilder
+    private func SheetContent(_ state: TaggedItem<SheetState>) -> some View {
+        switch state.item {
+        case .qr:
+            EmptyView()
+        case .fee:
+            SendFlowSelectFeeRateView(
+                manager: manager,
+                feeOptions: Binding(get: { feeRateOptions! }, set: { feeRateOptions = $0 }),
+                selectedOption: Binding(
+                    get: { selectedFeeRate! },
+                    set: { selectedFeeRate = $0 }
+                ),
+                selectedPresentationDetent: $selectedPresentationDetent
+            )
+            .presentationDetents(
+                [.height(440), .height(550), .large],
+                selection: $selectedPresentationDetent
+            )
+        }
+    }
+}
@@ -230,6 +230,8 @@ struct SettingsScreen: View {
 
             NodeSelectionView()
 
+            ProxySettingsView()
+
             FiatCurrencySection
 
             SecuritySection
@@ -0,0 +1,295 @@
+//
+//  PsbtSheetView.swift
+//  Cove
+//
+
+import MijickPopupView
+import SwiftUI
+
+/// A psbt that still needs signatures, sign it with the selected hot wallet, combine it with a
+/// copy signed on another device, and broadcast it once every input is signed
+struct PsbtSheetView: View {
+    @Environment(\.dismiss) private var dismiss
+
+    @Bindable var app: AppManager
+    @State var psbt: Psbt
+
+    // private
+    @State private var manager: WalletManager? = nil
+    @State private var details: PsbtDetails? = nil
+    @State private var isScanning = false
+    @State private var scannedCode: TaggedItem<StringOrData>? = .none
+    @State private var isWorking = false
+    @State private var errorMessage: String? = nil
+    @State private var confirmingBroadcast = false
+
+    private var canSign: Bool {
+        manager?.walletMetadata.walletType == .hot
+    }
+
+    private var signedInputs: Int {
+        details?.inputs.filter(\.isSigned).count ?? 0
+    }
+
+    private var allInputsSigned: Bool {
+        guard let details else { return false }
+        return signedInputs == details.inputs.count
+    }
+
+    private func loadManager() {
+        guard let id = Database().globalConfig().selectedWallet() else { return }
+
+        do {
+            manager = try app.getWalletManager(id: id)
+        } catch {
+            Log.error("Unable to get wallet manager for psbt: \(error)")
+        }
+    }
+
+    private func refreshDetails() {
+        Task {
+            guard let manager else { return await MainActor.run { details = psbt.details() } }
+
+            do {
+                let details = try await manager.rust.psbtDetails(psbt: psbt)
+                await MainActor.run { self.details = details }
+            } catch {
+                Log.error("Unable to get psbt details: \(error)")
+                await MainActor.run { details = psbt.details() }
+            }
+        }
+    }
+
+    private func update(_ psbt: Psbt) {
+        self.psbt = psbt
+        app.importedPsbt = psbt
+        refreshDetails()
+    }
+
+    // MARK: Actions
+
+    private func sign() {
+        guard let manager else { return }
+        isWorking = true
+
+        Task {
+            defer { Task { @MainActor in isWorking = false } }
+
+            do {
+                let signed = try await manager.rust.signPsbt(psbt: psbt)
+                await MainActor.run { update(signed) }
+            } catch {
+                Log.error("Unable to sign psbt: \(error)")
+                await MainActor.run { errorMessage = error.localizedDescription }
+            }
+        }
+    }
+
+    private func combine(_ stringOrData: StringOrData) {
+        do {
+            guard case let .psbt(other) = try stringOrData.toMultiFormat() else {
+                errorMessage = "Scanned code is not a PSBT"
+                return
+            }
+
+            let combined =
+                if let manager {
+                    try manager.rust.combinePsbts(psbt: psbt, other: other)
+                } else {
+                    try psbt.combine(other: other)
+                }
+
+            update(combined)
+        } catch {
+            Log.error("Unable to combine psbts: \(error)")
+            errorMessage = error.localizedDescription
+        }
+    }
+
+    private func broadcast() {
+        guard let manager else { return }
+        isWorking = true
+
+        Task {
+            defer { Task { @MainActor in isWorking = false } }
+
+            do {
+                guard let transaction = try await manager.rust.finalizePsbt(psbt: psbt) else {
+                    await MainActor.run {
+                        errorMessage = "PSBT is not fully signed, sign it before broadcasting"
+                    }
+                    return
+                }
+
+                let status = try await manager.rust.broadcastTransaction(
+                    signedTransaction: transaction
+                )
+
+                await MainActor.run {
+                    app.importedPsbt = .none
+                    dismiss()
+
+                    // compact filter peers don't report rejections, it's only known once it confirms
+                    let message = switch status {
+                    case .accepted: "Transaction broadcast"
+                    case .unverified: "Sent to peer, unconfirmed until it is in a block"
+                    }
+
+                    MiddlePopup(state: .success(message)).showAndStack()
+                        .dismissAfter(3)
+                }
+            } catch {
+                Log.error("Unable to broadcast psbt: \(error)")
+                await MainActor.run { errorMessage = error.localizedDescription }
+            }
+        }
+    }
+
+    // MARK: Views
+
+    var body: some View {
+        NavigationStack {
+            List {
+                Section(header: Text("Transaction")) {
+                    LabeledContent("Transaction ID") {
+                        Text(psbt.txId().asHashString())
+                            .font(.caption)
+                            .lineLimit(1)
+                            .truncationMode(.middle)
+                    }
+
+                    if let details {
+                        LabeledContent(
+                            "Signed Inputs", value: "\(signedInputs) of \(details.inputs.count)"
+                        )
+
+                        if let fee = details.fee {
+                            LabeledContent("Network Fee", value: fee.satsStringWithUnit())
+                        }
+                    }
+                }
+
+                if let details {
+                    Section(header: Text("Inputs")) {
+                        ForEach(Array(details.inputs.enumerated()), id: \.offset) { _, input in
+                            InputRow(input)
+                        }
+                    }
+
+                    Section(header: Text("Outputs")) {
+                        ForEach(Array(details.outputs.enumerated()), id: \.offset) { _, output in
+                            OutputRow(output)
+                        }
+                    }
+                }
+
+                Section {
+                    if canSign {
+                        Button(action: sign) {
+                            Label("Sign with This Wallet", systemImage: "signature")
+                        }
+                    }
+
+                    Button(action: { isScanning = true }) {
+                        Label("Combine with Another Signed Copy", systemImage: "qrcode.viewfinder")
+                    }
+
+                    ShareLink(item: psbt.toBase64()) {
+                        Label("Share PSBT", systemImage: "square.and.arrow.up")
+                    }
+
+                    Button(action: { confirmingBroadcast = true }) {
+                        Label("Finalize and Broadcast", systemImage: "paperplane")
+                    }
+                    .disabled(manager == nil || !allInputsSigned)
+                }
+                .disabled(isWorking)
+            }
+            .navigationTitle("Partially Signed Transaction")
+            .navigationBarTitleDisplayMode(.inline)
+            .toolbar {
+                ToolbarItem(placement: .cancellationAction) {
+                    Button("Close") { dismiss() }
+                }
+            }
+            .overlay {
+                if isWorking { ProgressView() }
+            }
+        }
+        .onAppear {
+            loadManager()
+            refreshDetails()
+        }
+        .sheet(isPresented: $isScanning) {
+            QrCodeScanView(app: app, scannedCode: $scannedCode)
+        }
+        .onChange(of: scannedCode) { _, scannedCode in
+            guard let scannedCode else { return }
+            isScanning = false
+            combine(scannedCode.item)
+        }
+        .alert(
+            "Broadcast Transaction?",
+            isPresented: $confirmingBroadcast,
+            actions: {
+                Button("Broadcast", action: broadcast)
+                Button("Cancel", role: .cancel) {}
+            },
+            message: {
+                Text("Check the outputs and fee, a broadcast transaction can't be undone")
+            }
+        )
+        .alert(
+            "Error",
+            isPresented: Binding(
+                get: { errorMessage != nil },
+                set: { if !$0 { errorMessage = .none } }
+            ),
+            actions: { Button("OK") { errorMessage = .none } },
+            message: { Text(errorMessage ?? "") }
+        )
+    }
+
+    @ViewBuilder
+    private func InputRow(_ input: PsbtInput) -> some View {
+        HStack {
+            VStack(alignment: .leading, spacing: 4) {
+                Text(input.amount?.satsStringWithUnit() ?? "Unknown amount")
+                    .font(.subheadline)
+
+                Text(input.address?.string() ?? input.outpoint.asString())
+                    .font(.caption)
+                    .foregroundStyle(.secondary)
+                    .lineLimit(1)
+                    .truncationMode(.middle)
+            }
+
+            Spacer()
+
+            Image(systemName: input.isSigned ? "checkmark.seal.fill" : "seal")
+                .foregroundStyle(input.isSigned ? .green : .secondary)
+        }
+    }
+
+    @ViewBuilder
+    private func OutputRow(_ output: PsbtOutput) -> some View {
+        VStack(alignment: .leading, spacing: 4) {
+            HStack {
+                Text(output.amount.satsStringWithUnit())
+                    .font(.subheadline)
+
+                if output.isChange {
+                    Text("Change")
+                        .font(.caption2)
+                        .foregroundStyle(.secondary)
+                }
+            }
+
+            Text(output.address?.string() ?? "No address")
+                .font(.caption)
+                .foregroundStyle(.secondary)
+                .lineLimit(1)
+                .truncationMode(.middle)
+        }
+    }
+}
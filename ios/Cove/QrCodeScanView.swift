@@ -108,14 +108,14 @@ struct QrCodeScanView: View {
                     }()
 
             // single QR
-            if !multiQr.isBbqr() {
+            if !multiQr.isMultiPart() {
                 scanComplete = true
                 scannedCode = TaggedItem(qr)
                 dismiss()
                 return
             }
 
-            // BBQr
+            // BBQr or animated UR
             guard case let .string(stringValue) = qr else { return }
 
             let result = try multiQr.addPart(qr: stringValue)
@@ -123,7 +123,8 @@ struct QrCodeScanView: View {
 
             if result.isComplete() {
                 scanComplete = true
-                let data = try result.finalResult()
+                // binary for BBQr PSBTs and transactions
+                let data = try result.finalResultStringOrData()
                 scannedCode = TaggedItem(data)
                 dismiss()
             }
@@ -33,6 +33,18 @@ struct NodeSelectionView: View {
         selectedNodeName.hasPrefix("Custom")
     }
 
+    /// Picker tag for each custom node type
+    private let customNodeTypes: [(String, ApiType)] = [
+        ("Custom Electrum", .electrum),
+        ("Custom Esplora", .esplora),
+        ("Custom Bitcoin Core RPC", .rpc),
+        ("Custom Compact Filters", .cbf),
+    ]
+
+    var customApiType: ApiType? {
+        customNodeTypes.first { $0.0 == selectedNodeName }?.1
+    }
+
     func cancelCheckUrlTask() {
         if let checkUrlTask {
             checkUrlTask.cancel()
@@ -78,9 +90,11 @@ struct NodeSelectionView: View {
             .textInputAutocapitalization(.never)
 
         Button("Save \(selectedNodeName)") {
+            guard let apiType = customApiType else { return }
+
             var node: Node? = nil
             do {
-                node = try nodeSelector.parseCustomNode(url: customUrl, name: selectedNodeName, enteredName: customNodeName)
+                node = try nodeSelector.parseCustomNode(url: customUrl, apiType: apiType, enteredName: customNodeName)
                 customUrl = node?.url ?? customUrl
                 customNodeName = node?.name ?? customNodeName
             } catch {
@@ -120,8 +134,9 @@ struct NodeSelectionView: View {
                         .tag(node.name)
                 }
 
-                Text("Custom Electrum").tag("Custom Electrum")
-                Text("Custom Esplora").tag("Custom Esplora")
+                ForEach(customNodeTypes, id: \.0) { name, _ in
+                    Text(name).tag(name)
+                }
             }
 
             if showCustomUrlField {
@@ -130,16 +145,11 @@ struct NodeSelectionView: View {
         }
         .onChange(of: selectedNodeName) { _, newSelectedNodeName in
             if selectedNodeName.hasPrefix("Custom") {
-                if case let .custom(savedSelectedNode) = nodeSelector.selectedNode() {
-                    if savedSelectedNode.apiType == .electrum, selectedNodeName.contains("Electrum") {
-                        customUrl = savedSelectedNode.url
-                        customNodeName = savedSelectedNode.name
-                    }
-
-                    if savedSelectedNode.apiType == .esplora, selectedNodeName.contains("Esplora") {
-                        customUrl = savedSelectedNode.url
-                        customNodeName = savedSelectedNode.name
-                    }
+                if case let .custom(savedSelectedNode) = nodeSelector.selectedNode(),
+                   savedSelectedNode.apiType == customApiType
+                {
+                    customUrl = savedSelectedNode.url
+                    customNodeName = savedSelectedNode.name
                 }
 
                 return
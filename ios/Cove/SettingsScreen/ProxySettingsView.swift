@@ -0,0 +1,72 @@
+//
+//  ProxySettingsView.swift
+//  Cove
+//
+
+import MijickPopupView
+import SwiftUI
+
+struct ProxySettingsView: View {
+    private let globalConfig = Database().globalConfig()
+
+    @State private var isEnabled: Bool
+    @State private var host: String
+    @State private var port: String
+    @State private var streamIsolation: Bool
+
+    init() {
+        let proxy = globalConfig.proxy()
+
+        isEnabled = proxy != nil
+        host = proxy?.host ?? "127.0.0.1"
+        port = String(proxy?.port ?? 9050)
+        streamIsolation = proxy?.streamIsolation ?? true
+    }
+
+    private func save() {
+        let proxy: ProxySettings? = if isEnabled {
+            ProxySettings(host: host, port: UInt16(port) ?? 0, streamIsolation: streamIsolation)
+        } else {
+            nil
+        }
+
+        do {
+            try globalConfig.setProxy(proxy: proxy)
+        } catch {
+            Log.error("Unable to save proxy settings: \(error)")
+            MiddlePopup(state: .failure("Invalid proxy\n\(error.localizedDescription)"))
+                .showAndStack()
+                .dismissAfter(5)
+        }
+    }
+
+    var body: some View {
+        Section(header: Text("Tor / SOCKS5 Proxy")) {
+            Toggle(isOn: $isEnabled) {
+                Label("Connect Through Proxy", systemImage: "network.badge.shield.half.filled")
+            }
+            .onChange(of: isEnabled) { _, _ in save() }
+
+            if isEnabled {
+                TextField("Host, ex: 127.0.0.1", text: $host)
+                    .keyboardType(.URL)
+                    .textInputAutocapitalization(.never)
+                    .autocorrectionDisabled()
+                    .onSubmit(save)
+
+                TextField("Port, ex: 9050", text: $port)
+                    .keyboardType(.numberPad)
+                    .onSubmit(save)
+
+                Toggle("Stream Isolation", isOn: $streamIsolation)
+                    .onChange(of: streamIsolation) { _, _ in save() }
+            }
+        }
+    }
+}
+
+#Preview {
+    Form {
+        ProxySettingsView()
+    }
+}
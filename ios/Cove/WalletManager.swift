@@ -122,6 +122,14 @@ extension WeakReconciler: WalletManagerReconciler where Reconciler == WalletMana
                 case let .scanComplete(txns):
                     self.loadState = .loaded(txns)
 
+                case let .newTransaction(txn):
+                    self.logger.info("new transaction pushed by node \(txn.id.asHashString())")
+                    if txn.sentAndReceived().direction() == .incoming {
+                        FloaterPopup(text: "Payment received")
+                            .showAndStack()
+                            .dismissAfter(2)
+                    }
+
                 case .walletBalanceChanged:
                     Task {
                         let balance = await rust.balance()
@@ -133,6 +141,13 @@ extension WeakReconciler: WalletManagerReconciler where Reconciler == WalletMana
                 case .unsignedTransactionsChanged:
                     self.unsignedTransactions = (try? rust.getUnsignedTransactions()) ?? []
 
+                case let .labelsChanged(txns):
+                    switch self.loadState {
+                    case .loaded: self.loadState = .loaded(txns)
+                    case .scanning: self.loadState = .scanning(txns)
+                    case .loading: break
+                    }
+
                 case let .walletMetadataChanged(metadata):
                     withAnimation {
                         self.walletMetadata = metadata
@@ -150,6 +165,12 @@ extension WeakReconciler: WalletManagerReconciler where Reconciler == WalletMana
                     self.logger.error(error)
                     self.logger.error("set errorAlert")
 
+                case let .nodeSwitched(node):
+                    self.logger.info("node unavailable, switched to \(node.name)")
+                    FloaterPopup(text: "Switched to \(node.name)")
+                        .showAndStack()
+                        .dismissAfter(2)
+
                 case let .walletError(error):
                     self.logger.error("WalletError \(error)")
 
@@ -10,6 +10,7 @@ struct WalletSettingsSheet: View {
 
     @State private var showingDeleteConfirmation = false
     @State private var showingSecretWordsConfirmation = false
+    @State private var descriptorDetails: DescriptorDetails? = nil
 
     let colors: [WalletColor] = WalletColor.red.all()
 
@@ -56,6 +57,68 @@ struct WalletSettingsSheet: View {
                     .tint(manager.walletMetadata.color.toColor())
                 }
 
+                if let details = descriptorDetails, manager.walletMetadata.walletType != .hot {
+                    Section(
+                        header: Text("Descriptor"),
+                        footer: Text(
+                            details.isStandard
+                                ? "Standard derivation path"
+                                : "This wallet uses a non-standard derivation path, make sure your signing device uses the same path."
+                        )
+                    ) {
+                        ForEach(details.keys, id: \.self) { key in
+                            HStack {
+                                Text(key.fingerprint?.uppercased() ?? "Unknown")
+                                Spacer()
+                                Text(key.derivationPath ?? "No origin info")
+                                    .foregroundColor(key.isStandard ? .secondary : .orange)
+                            }
+                        }
+
+                        Text(details.external)
+                            .font(.caption)
+                            .foregroundColor(.secondary)
+                            .textSelection(.enabled)
+                    }
+                }
+
+                Section(
+                    header: Text("Scanning"),
+                    footer: Text(
+                        "Raise the gap limit if the wallet was used with a service that skips addresses and funds are missing. Bitcoin Core and compact filter nodes only scan blocks after the birthday height."
+                    )
+                ) {
+                    Stepper(
+                        "Gap Limit: \(manager.walletMetadata.scanSettings.gapLimit)",
+                        value: Binding(
+                            get: { Int(manager.walletMetadata.scanSettings.gapLimit) },
+                            set: { updateGapLimit($0) }
+                        ),
+                        in: 5 ... 1000,
+                        step: 5
+                    )
+
+                    HStack {
+                        Text("Birthday Height")
+                        Spacer()
+                        TextField(
+                            "Genesis",
+                            text: Binding(
+                                get: { manager.walletMetadata.scanSettings.birthdayHeight.map(String.init) ?? "" },
+                                set: { updateBirthdayHeight($0) }
+                            )
+                        )
+                        .keyboardType(.numberPad)
+                        .multilineTextAlignment(.trailing)
+                        .foregroundColor(.secondary)
+                    }
+
+                    Button("Deep Rescan") {
+                        Task { await manager.rust.startDeepRescan() }
+                        dismiss()
+                    }
+                }
+
                 Section(header: Text("App Settings")) {
                     Button(action: {
                         dismiss()
@@ -144,10 +207,35 @@ struct WalletSettingsSheet: View {
                 )
             }
         }
+        .task {
+            descriptorDetails = try? await manager.rust.descriptorDetails()
+        }
         .onDisappear {
             manager.validateMetadata()
         }
     }
+
+    func updateGapLimit(_ gapLimit: Int) {
+        var settings = manager.walletMetadata.scanSettings
+        settings.gapLimit = UInt32(gapLimit)
+
+        do {
+            try manager.rust.setScanSettings(settings: settings)
+        } catch {
+            Log.error("Unable to update scan settings: \(error)")
+        }
+    }
+
+    func updateBirthdayHeight(_ text: String) {
+        var settings = manager.walletMetadata.scanSettings
+        settings.birthdayHeight = UInt32(text)
+
+        do {
+            try manager.rust.setScanSettings(settings: settings)
+        } catch {
+            Log.error("Unable to update scan settings: \(error)")
+        }
+    }
 }
 
 #Preview {
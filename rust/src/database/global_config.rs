@@ -11,6 +11,7 @@ use crate::{
     fiat::FiatCurrency,
     network::Network,
     node::Node,
+    proxy::ProxySettings,
     wallet::metadata::{WalletId, WalletMode},
 };
 
@@ -27,6 +28,8 @@ pub enum GlobalConfigKey {
     SelectedNetwork,
     SelectedFiatCurrency,
     SelectedNode(Network),
+    BackupNodes(Network),
+    Proxy,
     ColorScheme,
     AuthType,
     HashedPinCode,
@@ -46,6 +49,12 @@ impl From<GlobalConfigKey> for &'static str {
             GlobalConfigKey::SelectedNode(Network::Bitcoin) => "selected_node_bitcoin",
             GlobalConfigKey::SelectedNode(Network::Testnet) => "selected_node_testnet",
             GlobalConfigKey::SelectedNode(Network::Signet) => "selected_node_signet",
+            GlobalConfigKey::SelectedNode(Network::Regtest) => "selected_node_regtest",
+            GlobalConfigKey::BackupNodes(Network::Bitcoin) => "backup_nodes_bitcoin",
+            GlobalConfigKey::BackupNodes(Network::Testnet) => "backup_nodes_testnet",
+            GlobalConfigKey::BackupNodes(Network::Signet) => "backup_nodes_signet",
+            GlobalConfigKey::BackupNodes(Network::Regtest) => "backup_nodes_regtest",
+            GlobalConfigKey::Proxy => "proxy",
             GlobalConfigKey::ColorScheme => "color_scheme",
             GlobalConfigKey::AuthType => "auth_type",
             GlobalConfigKey::HashedPinCode => "hashed_pin_code",
@@ -82,6 +91,9 @@ pub enum GlobalConfigTableError {
 
     #[error("pin code must be hashed before saving")]
     PinCodeMustBeHashed,
+
+    #[error("invalid proxy: {0}")]
+    InvalidProxy(String),
 }
 
 impl GlobalConfigTable {
@@ -115,6 +127,26 @@ impl GlobalConfigTable {
     //     GlobalConfigKey::AuthType,
     //     AuthType
     // );
+
+    /// Nodes to fail over to when the selected node is down, in order, none until the user
+    /// adds them, so a private node never fails over to a public one without asking
+    pub fn backup_nodes(&self, network: Network) -> Vec<Node> {
+        let nodes_json = self
+            .get(GlobalConfigKey::BackupNodes(network))
+            .unwrap_or(None)
+            .unwrap_or("".to_string());
+
+        serde_json::from_str(&nodes_json).unwrap_or_default()
+    }
+
+    pub fn set_backup_nodes(&self, network: Network, nodes: &[Node]) -> Result<()> {
+        let nodes_json = serde_json::to_string(nodes)
+            .map_err(|error| SerdeError::SerializationError(error.to_string()))?;
+
+        self.set(GlobalConfigKey::BackupNodes(network), nodes_json)?;
+
+        Ok(())
+    }
 }
 
 impl GlobalConfigTable {
@@ -274,6 +306,30 @@ impl GlobalConfigTable {
         Ok(())
     }
 
+    /// SOCKS5 proxy for all outbound connections, `None` connects directly
+    pub fn proxy(&self) -> Option<ProxySettings> {
+        let proxy_json = self.get(GlobalConfigKey::Proxy).unwrap_or(None)?;
+        serde_json::from_str(&proxy_json).ok()
+    }
+
+    pub fn set_proxy(&self, proxy: Option<ProxySettings>) -> Result<()> {
+        let Some(proxy) = proxy else {
+            self.delete(GlobalConfigKey::Proxy)?;
+            return Ok(());
+        };
+
+        proxy
+            .validate()
+            .map_err(GlobalConfigTableError::InvalidProxy)?;
+
+        let proxy_json = serde_json::to_string(&proxy)
+            .map_err(|error| SerdeError::SerializationError(error.to_string()))?;
+
+        self.set(GlobalConfigKey::Proxy, proxy_json)?;
+
+        Ok(())
+    }
+
     #[uniffi::method(name = "selectedFiatCurrency")]
     fn _selected_fiat_currency(&self) -> FiatCurrency {
         self.fiat_currency().unwrap_or_default()
@@ -5,8 +5,9 @@ use redb::TableDefinition;
 use tracing::debug;
 
 use crate::{
+    psbt::{Psbt, PsbtError},
     redb::Json,
-    transaction::TxId,
+    transaction::{ffi::BitcoinTransaction, TxId},
     wallet::{confirm::ConfirmDetails, metadata::WalletId},
 };
 
@@ -65,6 +66,21 @@ impl UnsignedTransactionsTable {
         self.get(tx_id)
     }
 
+    /// The wallet's record for a signed transaction, matched on the ntxid because signing
+    /// legacy and wrapped segwit inputs fills in the script sigs, which changes the txid
+    pub fn get_tx_by_ntxid(
+        &self,
+        wallet_id: &WalletId,
+        ntxid: Hash,
+    ) -> Result<Option<UnsignedTransactionRecord>, Error> {
+        let record = self
+            .get_by_wallet_id(wallet_id)?
+            .into_iter()
+            .find(|record| record.confirm_details.psbt.0.unsigned_tx.compute_ntxid() == ntxid);
+
+        Ok(record)
+    }
+
     pub fn save_tx(&self, tx_id: TxId, record: UnsignedTransactionRecord) -> Result<(), Error> {
         let wallet_id = record.wallet_id.clone();
 
@@ -83,6 +99,19 @@ impl UnsignedTransactionsTable {
         Ok(())
     }
 
+    /// Replace an existing record, ex: after adding cosigner signatures to its psbt
+    pub fn update_tx(&self, record: UnsignedTransactionRecord) -> Result<(), Error> {
+        let tx_id = record.tx_id;
+
+        if self.get(&tx_id)?.is_none() {
+            return Err(UnsignedTransactionsTableError::NoRecordFound.into());
+        }
+
+        self.set(tx_id, record)?;
+
+        Ok(())
+    }
+
     pub fn delete_tx(&self, tx_id: &TxId) -> Result<(), Error> {
         let record = self
             .get(tx_id)?
@@ -268,6 +297,28 @@ impl UnsignedTransactionRecord {
     pub fn created_at(&self) -> u64 {
         self.created_at
     }
+
+    /// Merge a signed psbt returned by a hardware wallet into the saved one,
+    /// and extract the signed transaction, only if it spends the same unsigned transaction
+    #[uniffi::method]
+    pub fn finalize_signed_psbt(&self, signed: Arc<Psbt>) -> Result<BitcoinTransaction, PsbtError> {
+        if signed.tx_id() != self.tx_id {
+            let error = "signed psbt does not match the unsigned transaction".to_string();
+            return Err(PsbtError::CombineError(error));
+        }
+
+        let psbt = self.confirm_details.psbt.combine(signed)?;
+        let transaction = psbt.extract_transaction()?;
+
+        // the txid of legacy and wrapped segwit inputs changes when signed, the ntxid doesn't
+        let unsigned_ntxid = self.confirm_details.psbt.unsigned_tx.compute_ntxid();
+        if transaction.compute_ntxid() != unsigned_ntxid {
+            let error = "signed transaction does not match the unsigned transaction".to_string();
+            return Err(PsbtError::CombineError(error));
+        }
+
+        Ok(transaction)
+    }
 }
 
 // MARK: redb serd/de impls
@@ -317,3 +368,112 @@ impl redb::Value for TxId {
         redb::TypeName::new(std::any::type_name::<TxId>())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute,
+        ecdsa::Signature,
+        hashes::Hash as _,
+        secp256k1::{Message, Secp256k1, SecretKey},
+        sighash::{EcdsaSighashType, SighashCache},
+        transaction, Amount, CompressedPublicKey, OutPoint, PublicKey, ScriptBuf, Transaction,
+        TxIn, TxOut, Txid, WPubkeyHash,
+    };
+
+    use super::*;
+    use crate::psbt::BdkPsbt;
+
+    const INPUT_AMOUNT: Amount = Amount::from_sat(100_000);
+    const FEE: Amount = Amount::from_sat(1_000);
+
+    /// An unsigned psbt spending a P2SH-P2WPKH (wrapped segwit) output of `public_key`
+    fn wrapped_segwit_psbt(public_key: &CompressedPublicKey) -> BdkPsbt {
+        let redeem_script = ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash());
+
+        let unsigned_tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 0),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: INPUT_AMOUNT - FEE,
+                script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([1; 20])),
+            }],
+        };
+
+        let mut psbt = BdkPsbt::from_unsigned_tx(unsigned_tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: INPUT_AMOUNT,
+            script_pubkey: ScriptBuf::new_p2sh(&redeem_script.script_hash()),
+        });
+        psbt.inputs[0].redeem_script = Some(redeem_script);
+
+        psbt
+    }
+
+    fn record(psbt: &BdkPsbt) -> UnsignedTransactionRecord {
+        let confirm_details = ConfirmDetails::new_for_test(psbt.clone(), FEE);
+
+        UnsignedTransactionRecord {
+            wallet_id: WalletId::preview_new(),
+            tx_id: confirm_details.psbt.tx_id(),
+            confirm_details,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_finalize_signed_wrapped_segwit_psbt() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let public_key = CompressedPublicKey(secret_key.public_key(&secp));
+
+        let unsigned = wrapped_segwit_psbt(&public_key);
+        let record = record(&unsigned);
+
+        // sign the way a hardware wallet would, adding only the partial signature
+        let mut signed = unsigned.clone();
+        let script_code = ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash());
+        let sighash = SighashCache::new(&signed.unsigned_tx)
+            .p2wpkh_signature_hash(0, &script_code, INPUT_AMOUNT, EcdsaSighashType::All)
+            .unwrap();
+
+        let message = Message::from_digest(sighash.to_byte_array());
+        let signature = Signature::sighash_all(secp.sign_ecdsa(&message, &secret_key));
+        signed.inputs[0]
+            .partial_sigs
+            .insert(PublicKey::new(public_key.0), signature);
+
+        let transaction = record
+            .finalize_signed_psbt(Arc::new(signed.into()))
+            .unwrap();
+
+        // the script sig pushing the redeem script changes the txid, but not the ntxid
+        assert!(!transaction.input[0].script_sig.is_empty());
+        assert_ne!(transaction.tx_id(), record.tx_id);
+        assert_eq!(
+            transaction.compute_ntxid(),
+            unsigned.unsigned_tx.compute_ntxid()
+        );
+    }
+
+    #[test]
+    fn test_finalize_rejects_psbt_for_another_transaction() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let public_key = CompressedPublicKey(secret_key.public_key(&secp));
+
+        let unsigned = wrapped_segwit_psbt(&public_key);
+        let record = record(&unsigned);
+
+        let mut other = unsigned.clone();
+        other.unsigned_tx.output[0].value = FEE;
+        let other = BdkPsbt::from_unsigned_tx(other.unsigned_tx).unwrap();
+
+        let result = record.finalize_signed_psbt(Arc::new(other.into()));
+        assert!(matches!(result, Err(PsbtError::CombineError(_))));
+    }
+}
@@ -1,5 +1,6 @@
 use std::{path::PathBuf, sync::Arc};
 
+use bitcoin::OutPoint;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use redb::{ReadOnlyTable, TableDefinition};
@@ -9,7 +10,11 @@ use tracing::{debug, error, info};
 use crate::{
     consts::WALLET_DATA_DIR,
     redb::Json,
-    wallet::{metadata::WalletId, WalletAddressType},
+    wallet::{
+        label::{Label, Labels},
+        metadata::WalletId,
+        WalletAddressType,
+    },
 };
 
 use ahash::AHashMap as HashMap;
@@ -34,11 +39,19 @@ const TABLE: TableDefinition<&'static str, Json<WalletData>> =
 pub enum WalletData {
     /// number of addresses scanned
     ScanState(ScanState),
+
+    /// utxos the user never wants spent
+    FrozenUtxos(Vec<OutPoint>),
+
+    /// user labels, BIP-329
+    Labels(Labels),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, uniffi::Enum)]
 pub enum WalletDataKey {
     ScanState(WalletAddressType),
+    FrozenUtxos,
+    Labels,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, uniffi::Enum)]
@@ -114,6 +127,54 @@ impl WalletDataDb {
         self.set(key, value)
     }
 
+    pub fn get_frozen_utxos(&self) -> Result<Vec<OutPoint>> {
+        let value = self.get(WalletDataKey::FrozenUtxos)?;
+
+        let Some(WalletData::FrozenUtxos(frozen)) = value else {
+            return Ok(vec![]);
+        };
+
+        Ok(frozen)
+    }
+
+    pub fn set_frozen_utxos(&self, frozen_utxos: Vec<OutPoint>) -> Result<()> {
+        let key = WalletDataKey::FrozenUtxos;
+        let value = WalletData::FrozenUtxos(frozen_utxos);
+
+        self.set(key, value)
+    }
+
+    pub fn set_utxo_frozen(&self, outpoint: OutPoint, frozen: bool) -> Result<()> {
+        debug!("setting utxo {outpoint} frozen: {frozen}");
+
+        let mut frozen_utxos = self.get_frozen_utxos()?;
+        frozen_utxos.retain(|frozen_outpoint| *frozen_outpoint != outpoint);
+
+        if frozen {
+            frozen_utxos.push(outpoint);
+        }
+
+        self.set_frozen_utxos(frozen_utxos)
+    }
+
+    pub fn get_labels(&self) -> Result<Labels> {
+        let value = self.get(WalletDataKey::Labels)?;
+
+        let Some(WalletData::Labels(labels)) = value else {
+            return Ok(Labels::default());
+        };
+
+        Ok(labels)
+    }
+
+    /// Add or replace labels, labels with no text or spendable state are removed
+    pub fn save_labels(&self, new_labels: Vec<Label>) -> Result<()> {
+        let mut labels = self.get_labels()?;
+        labels.extend(new_labels);
+
+        self.set(WalletDataKey::Labels, WalletData::Labels(labels))
+    }
+
     fn get(&self, key: WalletDataKey) -> Result<Option<WalletData>> {
         let table = self.read_table()?;
 
@@ -232,6 +293,9 @@ impl WalletDataKey {
                 "scan_state_wrapped_segwit"
             }
             WalletDataKey::ScanState(WalletAddressType::Legacy) => "scan_state_legacy",
+            WalletDataKey::ScanState(WalletAddressType::Taproot) => "scan_state_taproot",
+            WalletDataKey::FrozenUtxos => "frozen_utxos",
+            WalletDataKey::Labels => "labels",
         }
     }
 }
@@ -101,6 +101,12 @@ impl FiatClient {
         Ok(value_in_currency)
     }
 
+    /// Goes through the proxy when one is set
+    fn client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        let client = crate::proxy::http_client()?.unwrap_or_else(|| self.client.clone());
+        Ok(client)
+    }
+
     pub async fn get_prices(&self) -> Result<PriceResponse, reqwest::Error> {
         if let Some(prices) = PRICES.load().as_ref() {
             let now_secs = Timestamp::now().as_second() as u64;
@@ -109,7 +115,7 @@ impl FiatClient {
             }
         }
 
-        let response = self.client.get(&self.url).send().await?;
+        let response = self.client()?.get(&self.url).send().await?;
         let prices: PriceResponse = response.json().await?;
 
         // update global prices
@@ -1,6 +1,7 @@
 use std::str::FromStr as _;
 
-use bdk_wallet::bitcoin::bip32::{DerivationPath, Fingerprint};
+use bdk_wallet::bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint};
+use bdk_wallet::bitcoin::secp256k1::Secp256k1;
 use bdk_wallet::descriptor::ExtendedDescriptor;
 use bdk_wallet::keys::bip39::Mnemonic;
 use bdk_wallet::keys::{
@@ -8,10 +9,10 @@ use bdk_wallet::keys::{
 };
 use bdk_wallet::keys::{DescriptorPublicKey as BdkDescriptorPublicKey, KeyMap};
 use bdk_wallet::miniscript::descriptor::{DescriptorXKey, Wildcard};
-use bdk_wallet::template::{Bip44, Bip49, Bip84, Bip84Public, DescriptorTemplate as _};
+use bdk_wallet::template::{Bip44, Bip49, Bip84, Bip84Public, Bip86, DescriptorTemplate as _};
 use bdk_wallet::{CreateParams, KeychainKind};
 
-use crate::network::Network;
+use crate::{network::Network, wallet::WalletAddressType};
 
 pub type Seed = [u8; 64];
 
@@ -160,6 +161,96 @@ impl Descriptor {
         }
     }
 
+    /// BIP86 for P2TR key-path spends (Taproot)
+    pub(crate) fn new_bip86(
+        secret_key: &DescriptorSecretKey,
+        keychain_kind: KeychainKind,
+        network: Network,
+    ) -> Self {
+        let derivable_key = &secret_key.0;
+
+        match derivable_key {
+            BdkDescriptorSecretKey::Single(_) => {
+                unreachable!()
+            }
+            BdkDescriptorSecretKey::XPrv(descriptor_x_key) => {
+                let derivable_key = descriptor_x_key.xkey;
+                let (extended_descriptor, key_map, _) = Bip86(derivable_key, keychain_kind)
+                    .build(network.into())
+                    .unwrap();
+                Self {
+                    extended_descriptor,
+                    key_map,
+                }
+            }
+            BdkDescriptorSecretKey::MultiXPrv(_) => {
+                unreachable!()
+            }
+        }
+    }
+
+    /// Single sig descriptor at any account, the BIP templates above only derive account 0
+    ///
+    /// ex: `wpkh([73c5da0a/84'/0'/1']xprv.../0/*)` for account 1 of a native segwit wallet
+    pub(crate) fn new_for_account(
+        secret_key: &DescriptorSecretKey,
+        address_type: WalletAddressType,
+        keychain_kind: KeychainKind,
+        network: Network,
+        account: u32,
+    ) -> Self {
+        let BdkDescriptorSecretKey::XPrv(descriptor_x_key) = &secret_key.0 else {
+            unreachable!()
+        };
+
+        let purpose = match address_type {
+            WalletAddressType::Legacy => 44,
+            WalletAddressType::WrappedSegwit => 49,
+            WalletAddressType::NativeSegwit => 84,
+            WalletAddressType::Taproot => 86,
+        };
+
+        let coin_type = match network {
+            Network::Bitcoin => 0,
+            Network::Testnet | Network::Signet | Network::Regtest => 1,
+        };
+
+        let account_path: DerivationPath = [purpose, coin_type, account]
+            .into_iter()
+            .map(|index| ChildNumber::from_hardened_idx(index).expect("valid account index"))
+            .collect::<Vec<_>>()
+            .into();
+
+        let secp = Secp256k1::new();
+        let master = descriptor_x_key.xkey;
+        let account_xprv = master
+            .derive_priv(&secp, &account_path)
+            .expect("hardened derivation never fails");
+
+        let keychain = match keychain_kind {
+            KeychainKind::External => 0,
+            KeychainKind::Internal => 1,
+        };
+
+        let fingerprint = master.fingerprint(&secp);
+        let key = format!("[{fingerprint}/{account_path}]{account_xprv}/{keychain}/*");
+        let descriptor = match address_type {
+            WalletAddressType::Legacy => format!("pkh({key})"),
+            WalletAddressType::WrappedSegwit => format!("sh(wpkh({key}))"),
+            WalletAddressType::NativeSegwit => format!("wpkh({key})"),
+            WalletAddressType::Taproot => format!("tr({key})"),
+        };
+
+        let (extended_descriptor, key_map) =
+            ExtendedDescriptor::parse_descriptor(&secp, &descriptor)
+                .expect("descriptor built from a valid xprv");
+
+        Self {
+            extended_descriptor,
+            key_map,
+        }
+    }
+
     pub fn into_tuple(self) -> (ExtendedDescriptor, KeyMap) {
         (self.extended_descriptor, self.key_map)
     }
@@ -198,3 +289,91 @@ impl From<pubport::descriptor::Descriptors> for Descriptors {
         Self { external, internal }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regtest_descriptors() {
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+
+        let network = Network::Regtest;
+        let secret_key = DescriptorSecretKey::new(network, mnemonic, None);
+
+        let external = Descriptor::new_bip84(&secret_key, KeychainKind::External, network);
+        let internal = Descriptor::new_bip84(&secret_key, KeychainKind::Internal, network);
+
+        let wallet = Descriptors { external, internal }
+            .into_create_params()
+            .network(network.into())
+            .create_wallet_no_persist()
+            .unwrap();
+
+        let address = wallet.peek_address(KeychainKind::External, 0);
+        assert!(address.address.to_string().starts_with("bcrt1q"));
+    }
+
+    #[test]
+    fn test_bip86_descriptors() {
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+
+        let network = Network::Bitcoin;
+        let secret_key = DescriptorSecretKey::new(network, mnemonic, None);
+
+        let external = Descriptor::new_bip86(&secret_key, KeychainKind::External, network);
+        let internal = Descriptor::new_bip86(&secret_key, KeychainKind::Internal, network);
+
+        let wallet = Descriptors { external, internal }
+            .into_create_params()
+            .network(network.into())
+            .create_wallet_no_persist()
+            .unwrap();
+
+        // first receive address from the BIP86 test vectors
+        let address = wallet.peek_address(KeychainKind::External, 0);
+        assert_eq!(
+            address.address.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+    }
+
+    #[test]
+    fn test_account_descriptors() {
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+
+        let network = Network::Bitcoin;
+        let secret_key = DescriptorSecretKey::new(network, mnemonic, None);
+        let native_segwit = WalletAddressType::NativeSegwit;
+
+        // account 0 matches the BIP84 template
+        let template = Descriptor::new_bip84(&secret_key, KeychainKind::External, network);
+        let account_0 = Descriptor::new_for_account(
+            &secret_key,
+            native_segwit,
+            KeychainKind::External,
+            network,
+            0,
+        );
+
+        assert_eq!(
+            template.extended_descriptor.to_string(),
+            account_0.extended_descriptor.to_string()
+        );
+
+        let account_1 = Descriptor::new_for_account(
+            &secret_key,
+            native_segwit,
+            KeychainKind::Internal,
+            network,
+            1,
+        );
+
+        let descriptor = account_1.extended_descriptor.to_string();
+        assert!(descriptor.starts_with("wpkh([73c5da0a/84'/0'/1']xpub"));
+        assert!(descriptor.contains("/1/*)"));
+    }
+}
@@ -22,12 +22,14 @@ mod header_icon_presenter;
 mod keychain;
 mod keys;
 mod manager;
+mod message_signing;
 mod mnemonic;
 mod multi_format;
 mod network;
 mod node;
 mod node_connect;
 mod pending_wallet;
+mod proxy;
 mod psbt;
 mod push_tx;
 mod qr;
@@ -36,6 +38,7 @@ mod seed_qr;
 mod task;
 mod transaction;
 mod unblock;
+mod ur;
 mod util;
 mod wallet;
 mod wallet_scanner;
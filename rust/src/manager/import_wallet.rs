@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use bdk_wallet::bitcoin::bip32::ChildNumber;
 use bip39::{Language, Mnemonic};
 use crossbeam::channel::{Receiver, Sender};
 use parking_lot::RwLock;
@@ -10,7 +11,7 @@ use crate::{
     mnemonic::MnemonicExt as _,
     wallet::{
         fingerprint::Fingerprint,
-        metadata::{WalletId, WalletMetadata},
+        metadata::{DiscoveryState, WalletId, WalletMetadata},
         Wallet,
     },
 };
@@ -94,9 +95,23 @@ impl RustImportWalletManager {
         });
     }
 
-    /// Import wallet view from entered words
-    #[uniffi::method]
-    pub fn import_wallet(&self, entered_words: Vec<Vec<String>>) -> Result<WalletMetadata, Error> {
+    /// Import wallet view from entered words, an empty `passphrase` is the same as no passphrase
+    ///
+    /// `account` is the BIP44/49/84/86 account to import, 0 for most wallets
+    #[uniffi::method(default(passphrase = None, account = 0))]
+    pub fn import_wallet(
+        &self,
+        entered_words: Vec<Vec<String>>,
+        passphrase: Option<String>,
+        account: u32,
+    ) -> Result<WalletMetadata, Error> {
+        let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+
+        if ChildNumber::from_hardened_idx(account).is_err() {
+            let error = format!("invalid account index: {account}");
+            return Err(ImportWalletError::WalletImportError(error));
+        }
+
         let words = entered_words
             .into_iter()
             .flatten()
@@ -110,13 +125,17 @@ impl RustImportWalletManager {
         let mode = Database::global().global_config.wallet_mode();
 
         // make sure its not already imported
-        let fingerprint: Fingerprint = mnemonic.xpub(network.into()).fingerprint().into();
+        let fingerprint: Fingerprint = mnemonic
+            .xpub_with_passphrase(network.into(), passphrase.as_deref())
+            .fingerprint()
+            .into();
         let all_fingerprints: Vec<(WalletId, Fingerprint)> = Database::global()
             .wallets
             .get_all(network, mode)
             .map(|wallets| {
                 wallets
                     .into_iter()
+                    .filter(|wallet_metadata| wallet_metadata.account_index == account)
                     .filter_map(|wallet_metadata| {
                         let fingerprint = Fingerprint::try_new(&wallet_metadata.id).ok()?;
                         Some((wallet_metadata.id, fingerprint))
@@ -136,11 +155,28 @@ impl RustImportWalletManager {
         let number_of_wallets = Database::global().wallets.len(network, mode).unwrap_or(0);
 
         let name = format!("Wallet {}", number_of_wallets + 1);
-        let wallet_metadata =
+        let mut wallet_metadata =
             WalletMetadata::new_imported_from_mnemonic(name, network, fingerprint);
 
-        Wallet::try_new_persisted_and_selected(wallet_metadata.clone(), mnemonic.clone(), None)
-            .map_err(|e| ImportWalletError::WalletImportError(e.to_string()))?;
+        // the scanner can't derive the other address types without the passphrase
+        if passphrase.is_some() {
+            wallet_metadata.has_passphrase = true;
+            wallet_metadata.discovery_state = DiscoveryState::Single;
+        }
+
+        // address type discovery only looks at account 0
+        if account > 0 {
+            wallet_metadata.account_index = account;
+            wallet_metadata.name = format!("{} #{account}", wallet_metadata.name);
+            wallet_metadata.discovery_state = DiscoveryState::Single;
+        }
+
+        Wallet::try_new_persisted_and_selected(
+            wallet_metadata.clone(),
+            mnemonic.clone(),
+            passphrase,
+        )
+        .map_err(|e| ImportWalletError::WalletImportError(e.to_string()))?;
 
         Ok(wallet_metadata)
     }
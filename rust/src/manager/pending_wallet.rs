@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::UNIX_EPOCH};
 
 use crossbeam::channel::{Receiver, Sender};
 use parking_lot::RwLock;
@@ -10,7 +10,7 @@ use crate::{
     mnemonic::{GroupedWord, MnemonicExt as _, NumberOfBip39Words, WordAccess as _},
     multi_format::MultiFormatError,
     pending_wallet::PendingWallet,
-    wallet::{fingerprint::Fingerprint, metadata::WalletMetadata, Wallet},
+    wallet::{fingerprint::Fingerprint, metadata::WalletMetadata, Wallet, WalletAddressType},
 };
 
 type Error = PendingWalletManagerError;
@@ -27,6 +27,7 @@ pub enum PendingWalletManagerError {
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
 pub enum PendingWalletManagerReconcileMessage {
     Words(NumberOfBip39Words),
+    AddressType(WalletAddressType),
 }
 
 #[uniffi::export(callback_interface)]
@@ -47,11 +48,13 @@ pub struct RustPendingWalletManager {
 pub struct PendingWalletManagerState {
     pub number_of_words: NumberOfBip39Words,
     pub wallet: Arc<PendingWallet>,
+    pub address_type: WalletAddressType,
 }
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
 pub enum PendingWalletManagerAction {
     UpdateWords(NumberOfBip39Words),
+    UpdateAddressType(WalletAddressType),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, uniffi::Error, thiserror::Error)]
@@ -108,8 +111,10 @@ impl RustPendingWalletManager {
         self.state.read().number_of_words.to_word_count() as u8 / 6
     }
 
-    #[uniffi::method]
-    pub fn save_wallet(&self) -> Result<WalletMetadata, Error> {
+    /// Save the new wallet, an empty `passphrase` is the same as no passphrase
+    #[uniffi::method(default(passphrase = None))]
+    pub fn save_wallet(&self, passphrase: Option<String>) -> Result<WalletMetadata, Error> {
+        let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
         let network = self.state.read().wallet.network;
         let mode = Database::global().global_config.wallet_mode();
 
@@ -122,17 +127,23 @@ impl RustPendingWalletManager {
             .read()
             .wallet
             .mnemonic
-            .xpub(network.into())
+            .xpub_with_passphrase(network.into(), passphrase.as_deref())
             .fingerprint()
             .into();
 
-        let wallet_metadata = WalletMetadata::new(name, fingerprint);
+        let mut wallet_metadata = WalletMetadata::new(name, fingerprint);
+        wallet_metadata.address_type = self.state.read().address_type;
+        wallet_metadata.has_passphrase = passphrase.is_some();
+
+        // a freshly generated seed has no history, scans can skip the blocks before it existed
+        let now = UNIX_EPOCH.elapsed().unwrap_or_default();
+        wallet_metadata.scan_settings.birthday_height = network.estimated_birthday_height(now);
 
         // create, persist and select the wallet
         Wallet::try_new_persisted_and_selected(
             wallet_metadata.clone(),
             self.state.read().wallet.mnemonic.clone(),
-            None,
+            passphrase,
         )?;
 
         Ok(wallet_metadata)
@@ -171,6 +182,16 @@ impl RustPendingWalletManager {
                     .send(PendingWalletManagerReconcileMessage::Words(words))
                     .expect("failed to send update");
             }
+
+            PendingWalletManagerAction::UpdateAddressType(address_type) => {
+                self.state.write().address_type = address_type;
+
+                self.reconciler
+                    .send(PendingWalletManagerReconcileMessage::AddressType(
+                        address_type,
+                    ))
+                    .expect("failed to send update");
+            }
         }
     }
 }
@@ -180,6 +201,7 @@ impl PendingWalletManagerState {
         Self {
             number_of_words,
             wallet: PendingWallet::new(number_of_words, None).into(),
+            address_type: WalletAddressType::default(),
         }
     }
 }
@@ -201,6 +223,7 @@ impl From<crate::wallet::WalletError> for WalletCreationError {
             WalletError::PersistError(error) => Self::Persist(error),
             WalletError::MultiFormatError(error) => Self::MultiFormatError(error),
             WalletError::ParseXpubError(error) => Self::Import(error.to_string()),
+            WalletError::MultisigError(error) => Self::Import(error.to_string()),
             WalletError::WalletAlreadyExists(id) => {
                 Self::Import(format!("wallet already exists: {id}"))
             }
@@ -15,15 +15,16 @@ use tracing::{debug, error, warn};
 use crate::{
     app::FfiApp,
     converter::{ConverterError, CONVERTER},
-    database::{error::DatabaseError, Database},
+    database::{error::DatabaseError, wallet_data::WalletDataDb, Database},
     fiat::{
         client::{PriceResponse, FIAT_CLIENT},
         FiatCurrency,
     },
     format::NumberFormatter,
     keychain::{Keychain, KeychainError},
-    psbt::Psbt,
-    router::Route,
+    node::{pool::NodeHealth, Node},
+    psbt::{Psbt, PsbtDetails},
+    router::{Route, SendRoute},
     task::{self, spawn_actor},
     transaction::{
         fees::{
@@ -32,16 +33,25 @@ use crate::{
         },
         ffi::BitcoinTransaction,
         unsigned_transaction::UnsignedTransaction,
-        Amount, FeeRate, SentAndReceived, Transaction, TransactionDetails, TxId, Unit,
+        Amount, FeeRate, OutPoint, SentAndReceived, Transaction, TransactionDetails, TxId, Unit,
     },
     wallet::{
+        address_list::AddressList,
         balance::Balance,
         confirm::{AddressAndAmount, ConfirmDetails, SplitOutput},
+        descriptor::DescriptorDetails,
         fingerprint::Fingerprint,
-        metadata::{DiscoveryState, FiatOrBtc, WalletColor, WalletId, WalletMetadata},
+        label::{Label, LabelType, Labels},
+        metadata::{
+            DiscoveryState, FiatOrBtc, ScanSettings, WalletColor, WalletId, WalletMetadata,
+            WalletType,
+        },
+        multisig::{Cosigner, MultisigInfo, MultisigSigningState, SigningProgress},
+        safety_check::SafetyCheckError,
+        utxo::{CoinControl, Utxo},
         Address, AddressInfo, Wallet, WalletAddressType, WalletError,
     },
-    wallet_scanner::{ScannerResponse, WalletScanner},
+    wallet_scanner::{FoundAccount, ScannerResponse, WalletScanner},
     word_validator::WordValidator,
 };
 
@@ -51,7 +61,12 @@ pub enum WalletManagerReconcileMessage {
     AvailableTransactions(Vec<Transaction>),
     ScanComplete(Vec<Transaction>),
 
+    /// A transaction the node pushed to the wallet, sent before the updated transaction list
+    NewTransaction(Transaction),
+
     NodeConnectionFailed(String),
+    /// The node in use was down, the wallet failed over to this node
+    NodeSwitched(Node),
     WalletMetadataChanged(WalletMetadata),
     WalletBalanceChanged(Arc<Balance>),
 
@@ -62,6 +77,9 @@ pub enum WalletManagerReconcileMessage {
 
     UnsignedTransactionsChanged,
 
+    /// User labels were changed or imported, transactions are reloaded with the new labels
+    LabelsChanged(Vec<Transaction>),
+
     SendFlowError(SendFlowErrorAlert),
 }
 
@@ -92,10 +110,54 @@ pub enum WalletErrorAlert {
     NoBalance,
 }
 
+/// What the node told us about a transaction we sent
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Enum)]
+pub enum BroadcastStatus {
+    /// The node accepted the transaction
+    Accepted,
+    /// Sent to a compact block filter peer, peers don't say if they rejected a transaction,
+    /// so it shows as unconfirmed until it is found in a block
+    Unverified,
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, uniffi::Enum)]
 pub enum SendFlowErrorAlert {
     SignAndBroadcast(String),
     ConfirmDetails(String),
+    AbsurdFee {
+        fee: Arc<Amount>,
+        sending_amount: Arc<Amount>,
+    },
+    DustOutput {
+        address: Arc<Address>,
+        amount: Arc<Amount>,
+    },
+    DustScriptOutput {
+        script: String,
+        amount: Arc<Amount>,
+    },
+    TransactionMismatch(String),
+}
+
+impl From<SafetyCheckError> for SendFlowErrorAlert {
+    fn from(error: SafetyCheckError) -> Self {
+        match error {
+            SafetyCheckError::AbsurdFee {
+                fee,
+                sending_amount,
+            } => Self::AbsurdFee {
+                fee,
+                sending_amount,
+            },
+            SafetyCheckError::DustOutput { address, amount } => {
+                Self::DustOutput { address, amount }
+            }
+            SafetyCheckError::DustScriptOutput { script, amount } => {
+                Self::DustScriptOutput { script, amount }
+            }
+            SafetyCheckError::TransactionMismatch(error) => Self::TransactionMismatch(error),
+        }
+    }
 }
 
 #[uniffi::export(callback_interface)]
@@ -182,6 +244,42 @@ pub enum WalletManagerError {
     #[error("Unable to sign and broadcast transaction, {0}")]
     SignAndBroadcastError(String),
 
+    #[error("Unable to bump fee, {0}")]
+    FeeBumpError(String),
+
+    #[error("Unable to speed up transaction, {0}")]
+    CpfpError(String),
+
+    #[error("Unable to get utxos, {0}")]
+    GetUtxosError(String),
+
+    #[error("Unable to get addresses, {0}")]
+    GetAddressesError(String),
+
+    #[error("Invalid scan settings, {0}")]
+    InvalidScanSettings(String),
+
+    #[error("Unable to add account, {0}")]
+    AccountError(String),
+
+    #[error("Unable to freeze utxo, {0}")]
+    FreezeUtxoError(String),
+
+    #[error("Unable to save or load labels, {0}")]
+    LabelsError(String),
+
+    #[error("Unable to add multisig signatures, {0}")]
+    MultisigError(String),
+
+    #[error("Unable to process PSBT, {0}")]
+    PsbtError(String),
+
+    #[error("Unable to sign message, {0}")]
+    MessageSigningError(String),
+
+    #[error("Transaction failed safety check, {0}")]
+    SafetyCheckFailed(#[from] SafetyCheckError),
+
     #[error(transparent)]
     ConverterError(#[from] ConverterError),
 
@@ -268,6 +366,23 @@ impl RustWalletManager {
         })
     }
 
+    #[uniffi::constructor(name = "try_new_from_multisig")]
+    pub fn try_new_from_multisig(threshold: u8, cosigners: Vec<Cosigner>) -> Result<Self, Error> {
+        let info = MultisigInfo::try_new(threshold, cosigners)
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        Self::try_new_from_multisig_info(info)
+    }
+
+    /// Import a `wsh(sortedmulti(...))` descriptor, ex: from a coordinator like Sparrow
+    #[uniffi::constructor(name = "try_new_from_multisig_descriptor")]
+    pub fn try_new_from_multisig_descriptor(descriptor: String) -> Result<Self, Error> {
+        let info = MultisigInfo::try_from_descriptor(&descriptor)
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        Self::try_new_from_multisig_info(info)
+    }
+
     #[uniffi::method]
     pub fn selected_fiat_currency(&self) -> FiatCurrency {
         Database::global()
@@ -310,6 +425,7 @@ impl RustWalletManager {
                 tx_id,
                 confirm_details,
                 created_at: jiff::Timestamp::now().as_second() as u64,
+                user_label: None,
             }
             .into(),
         )?;
@@ -340,9 +456,18 @@ impl RustWalletManager {
         let db = Database::global();
         let txns = db.unsigned_transactions().get_by_wallet_id(wallet_id)?;
 
+        let labels = WalletDataDb::new(wallet_id.clone())
+            .get_labels()
+            .tap_err(|error| error!("unable to load labels: {error}"))
+            .unwrap_or_default();
+
         let txns = txns
             .into_iter()
-            .map(|txn| Arc::new(txn.into()))
+            .map(|txn| {
+                let mut txn = UnsignedTransaction::from(txn);
+                txn.user_label = labels.tx_label(&txn.tx_id.0);
+                Arc::new(txn)
+            })
             .collect::<Vec<Arc<UnsignedTransaction>>>();
 
         Ok(txns)
@@ -361,46 +486,327 @@ impl RustWalletManager {
         Ok(())
     }
 
+    /// Signatures collected so far, `None` if this isn't a multisig wallet
     #[uniffi::method]
-    pub async fn balance(&self) -> Balance {
-        call!(self.actor.balance()).await.unwrap_or_default()
+    pub fn multisig_signing_progress(&self, psbt: Arc<Psbt>) -> Option<SigningProgress> {
+        let metadata = self.metadata.read();
+        let info = metadata.multisig.as_ref()?;
+
+        Some(info.signing_progress(&psbt.0))
     }
 
+    /// Add the signatures from a cosigner's device to a saved multisig transaction
     #[uniffi::method]
-    pub async fn sign_and_broadcast_transaction(&self, psbt: Arc<Psbt>) -> Result<(), Error> {
-        let psbt = Arc::unwrap_or_clone(psbt);
-        call!(self.actor.sign_and_broadcast_transaction(psbt.into()))
+    pub async fn add_cosigner_signatures(
+        &self,
+        tx_id: Arc<TxId>,
+        psbt: Arc<Psbt>,
+    ) -> Result<MultisigSigningState, Error> {
+        let db = Database::global().unsigned_transactions();
+        let mut record = db
+            .get_tx(&tx_id)?
+            .ok_or_else(|| Error::MultisigError("transaction not found".to_string()))?;
+
+        let unsigned = record.confirm_details.psbt.0.clone();
+        let signed = Arc::unwrap_or_clone(psbt).0;
+
+        let (psbt, state) = call!(self.actor.add_multisig_signatures(unsigned, signed))
             .await
-            .map_err(|_error| {
-                Error::SignAndBroadcastError("sign and broadcast failed".to_string())
-            })?;
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
 
-        self.force_wallet_scan().await;
+        record.confirm_details.psbt = Psbt(psbt);
+        db.update_tx(record)?;
+
+        self.reconciler
+            .send(WalletManagerReconcileMessage::UnsignedTransactionsChanged)
+            .expect("failed to send update");
+
+        Ok(state)
+    }
+
+    /// Sign a saved multisig transaction with the cosigners that are hot wallets on this device
+    #[uniffi::method]
+    pub async fn sign_with_local_cosigners(
+        &self,
+        tx_id: Arc<TxId>,
+    ) -> Result<MultisigSigningState, Error> {
+        let db = Database::global().unsigned_transactions();
+        let mut record = db
+            .get_tx(&tx_id)?
+            .ok_or_else(|| Error::MultisigError("transaction not found".to_string()))?;
+
+        let unsigned = record.confirm_details.psbt.0.clone();
+        let (psbt, state) = call!(self.actor.sign_multisig_with_local_cosigners(unsigned))
+            .await
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        record.confirm_details.psbt = Psbt(psbt);
+        db.update_tx(record)?;
+
+        self.reconciler
+            .send(WalletManagerReconcileMessage::UnsignedTransactionsChanged)
+            .expect("failed to send update");
+
+        Ok(state)
+    }
+
+    /// Inputs and outputs of an imported psbt, marking the ones that belong to this wallet
+    #[uniffi::method]
+    pub async fn psbt_details(&self, psbt: Arc<Psbt>) -> Result<PsbtDetails, Error> {
+        let psbt = Arc::unwrap_or_clone(psbt).0;
+
+        call!(self.actor.psbt_details(psbt))
+            .await
+            .map_err(|error| Error::PsbtError(error.to_string()))
+    }
+
+    /// Merge the signatures from another copy of the same psbt, ex: scanned from another signer,
+    /// a saved unsigned transaction of this wallet is updated with the combined signatures
+    #[uniffi::method]
+    pub fn combine_psbts(&self, psbt: Arc<Psbt>, other: Arc<Psbt>) -> Result<Arc<Psbt>, Error> {
+        let combined = psbt
+            .combine(other)
+            .map_err(|error| Error::PsbtError(error.to_string()))?;
+
+        let db = Database::global().unsigned_transactions();
+        let record = db.get_tx(&combined.tx_id())?;
+
+        if let Some(mut record) = record.filter(|record| record.wallet_id == self.id) {
+            record.confirm_details.psbt = combined.clone();
+            db.update_tx(record)?;
+
+            self.reconciler
+                .send(WalletManagerReconcileMessage::UnsignedTransactionsChanged)
+                .expect("failed to send update");
+        }
+
+        Ok(Arc::new(combined))
+    }
+
+    /// Sign an imported psbt, only hot wallets can sign
+    #[uniffi::method(default(passphrase = None))]
+    pub async fn sign_psbt(
+        &self,
+        psbt: Arc<Psbt>,
+        passphrase: Option<String>,
+    ) -> Result<Arc<Psbt>, Error> {
+        if self.metadata.read().wallet_type != WalletType::Hot {
+            let error = "only hot wallets can sign on this device".to_string();
+            return Err(Error::PsbtError(error));
+        }
+
+        let psbt = Arc::unwrap_or_clone(psbt).0;
+        let (psbt, _finalized) = call!(self.actor.sign_psbt(psbt, passphrase))
+            .await
+            .map_err(|error| Error::PsbtError(error.to_string()))?;
+
+        Ok(Arc::new(Psbt(psbt)))
+    }
+
+    /// The signed transaction, ready to broadcast, `None` if the psbt still needs signatures
+    #[uniffi::method]
+    pub async fn finalize_psbt(
+        &self,
+        psbt: Arc<Psbt>,
+    ) -> Result<Option<Arc<BitcoinTransaction>>, Error> {
+        let psbt = Arc::unwrap_or_clone(psbt).0;
+
+        let transaction = call!(self.actor.finalize_psbt(psbt))
+            .await
+            .map_err(|error| Error::PsbtError(error.to_string()))?;
+
+        Ok(transaction.map(|transaction| Arc::new(transaction.into())))
+    }
+
+    /// Sign a message with the key of one of this wallet's addresses, only hot wallets can sign
+    #[uniffi::method(default(passphrase = None))]
+    pub async fn sign_message(
+        &self,
+        address: Arc<Address>,
+        message: String,
+        passphrase: Option<String>,
+    ) -> Result<String, Error> {
+        if self.metadata.read().wallet_type != WalletType::Hot {
+            let error = "only hot wallets can sign on this device".to_string();
+            return Err(Error::MessageSigningError(error));
+        }
+
+        let address = Arc::unwrap_or_clone(address).into();
+        call!(self.actor.sign_message(address, message, passphrase))
+            .await
+            .map_err(|_| Error::MessageSigningError("unable to sign message".to_string()))?
+    }
+
+    /// Verify a BIP-137 or BIP-322 signature, the address doesn't need to be in this wallet
+    #[uniffi::method]
+    pub fn verify_message(
+        &self,
+        address: Arc<Address>,
+        message: String,
+        signature: String,
+    ) -> Result<bool, Error> {
+        crate::message_signing::verify_message(&message, &address, &signature)
+            .map_err(|error| Error::MessageSigningError(error.to_string()))
+    }
+
+    /// Every unspent output in the wallet, for manual coin selection
+    #[uniffi::method]
+    pub async fn list_utxos(&self) -> Result<Vec<Utxo>, Error> {
+        let utxos = call!(self.actor.utxos())
+            .await
+            .map_err(|error| Error::GetUtxosError(error.to_string()))?;
+
+        Ok(utxos)
+    }
+
+    /// Frozen utxos are never spent, and are not included in the spendable balance
+    #[uniffi::method]
+    pub async fn set_utxo_frozen(
+        &self,
+        outpoint: Arc<OutPoint>,
+        frozen: bool,
+    ) -> Result<(), Error> {
+        let outpoint = Arc::unwrap_or_clone(outpoint);
+
+        call!(self.actor.set_utxo_frozen(outpoint, frozen))
+            .await
+            .map_err(|error| Error::FreezeUtxoError(error.to_string()))?;
 
         Ok(())
     }
 
+    /// Set the user's label for a transaction, an empty label removes it
+    #[uniffi::method]
+    pub async fn set_transaction_label(
+        &self,
+        tx_id: Arc<TxId>,
+        label: String,
+    ) -> Result<(), Error> {
+        self.save_label(Label::new(LabelType::Tx, tx_id.0.to_string(), label))
+            .await
+    }
+
+    /// Set the user's label for an address, an empty label removes it
+    #[uniffi::method]
+    pub async fn set_address_label(
+        &self,
+        address: Arc<Address>,
+        label: String,
+    ) -> Result<(), Error> {
+        self.save_label(Label::new(LabelType::Addr, address.to_string(), label))
+            .await
+    }
+
+    /// Set the user's label for an output, an empty label removes it
+    #[uniffi::method]
+    pub async fn set_output_label(
+        &self,
+        outpoint: Arc<OutPoint>,
+        label: String,
+    ) -> Result<(), Error> {
+        self.save_label(Label::new(LabelType::Output, outpoint.as_string(), label))
+            .await
+    }
+
+    /// Save any kind of label, including xpub and pubkey labels
+    #[uniffi::method]
+    pub async fn save_label(&self, label: Label) -> Result<(), Error> {
+        call!(self.actor.save_labels(vec![label]))
+            .await
+            .map_err(|error| Error::LabelsError(error.to_string()))
+    }
+
+    /// Import labels from BIP-329 JSONL, returns the number of labels imported
+    #[uniffi::method]
+    pub async fn import_labels(&self, jsonl: String) -> Result<u32, Error> {
+        let labels =
+            Labels::parse_jsonl(&jsonl).map_err(|error| Error::LabelsError(error.to_string()))?;
+
+        let count = labels.len() as u32;
+        call!(self.actor.save_labels(labels))
+            .await
+            .map_err(|error| Error::LabelsError(error.to_string()))?;
+
+        Ok(count)
+    }
+
+    /// Export all the user's labels as BIP-329 JSONL
+    #[uniffi::method]
+    pub async fn export_labels(&self) -> Result<String, Error> {
+        call!(self.actor.export_labels())
+            .await
+            .map_err(|error| Error::LabelsError(error.to_string()))
+    }
+
+    #[uniffi::method]
+    pub async fn balance(&self) -> Balance {
+        // the actor reports the reason to the ui, an empty balance is shown until then
+        call!(self.actor.balance())
+            .await
+            .tap_err(|error| error!("unable to get balance: {error}"))
+            .unwrap_or_default()
+    }
+
+    #[uniffi::method(default(passphrase = None))]
+    /// Sign the transaction the user approved in `details` and broadcast it,
+    /// the signed transaction must pass the safety checks before it is sent.
+    /// Wallets created with a BIP39 passphrase need it here, it is not stored
+    pub async fn sign_and_broadcast_transaction(
+        &self,
+        details: Arc<ConfirmDetails>,
+        passphrase: Option<String>,
+    ) -> Result<BroadcastStatus, Error> {
+        let details = Arc::unwrap_or_clone(details);
+        let status = call!(self
+            .actor
+            .sign_and_broadcast_transaction(details, passphrase))
+        .await
+        .map_err(|_error| Error::SignAndBroadcastError("sign and broadcast failed".to_string()))?;
+
+        self.force_wallet_scan().await;
+
+        Ok(status)
+    }
+
     #[uniffi::method]
     pub async fn broadcast_transaction(
         &self,
         signed_transaction: Arc<BitcoinTransaction>,
-    ) -> Result<(), Error> {
+    ) -> Result<BroadcastStatus, Error> {
         let txn = Arc::unwrap_or_clone(signed_transaction);
-        let tx_id = txn.tx_id();
+        let ntxid = txn.0.compute_ntxid();
 
-        call!(self.actor.broadcast_transaction(txn.into()))
-            .await
-            .map_err(|_error| {
-                Error::SignAndBroadcastError("broadcast transaction failed".to_string())
-            })?;
+        // if we have the details the user approved, check the signed transaction against them
+        let approved = Database::global()
+            .unsigned_transactions()
+            .get_tx_by_ntxid(&self.id, ntxid)
+            .tap_err(|error| error!("unable to get unsigned transaction record: {error}"))
+            .ok()
+            .flatten();
+
+        let status = match &approved {
+            Some(record) => {
+                let details = record.confirm_details.clone();
+                call!(self
+                    .actor
+                    .check_and_broadcast_transaction(txn.into(), details))
+                .await
+            }
+            None => call!(self.actor.broadcast_transaction(txn.into())).await,
+        }
+        .map_err(|_error| {
+            Error::SignAndBroadcastError("broadcast transaction failed".to_string())
+        })?;
 
-        if let Err(error) = self.delete_unsigned_transaction(tx_id.into()) {
-            error!("unable to delete unsigned transaction record: {error}");
+        if let Some(record) = approved {
+            if let Err(error) = self.delete_unsigned_transaction(record.tx_id.into()) {
+                error!("unable to delete unsigned transaction record: {error}");
+            }
         }
 
         self.force_wallet_scan().await;
 
-        Ok(())
+        Ok(status)
     }
 
     #[uniffi::method]
@@ -568,6 +974,76 @@ impl RustWalletManager {
         Ok(address)
     }
 
+    /// Accounts 0..`number_of_accounts` of this wallet's seed that have history
+    #[uniffi::method(default(passphrase = None))]
+    pub async fn find_used_accounts(
+        &self,
+        number_of_accounts: u32,
+        passphrase: Option<String>,
+    ) -> Result<Vec<FoundAccount>, Error> {
+        if self.metadata.read().wallet_type != WalletType::Hot {
+            let error = "only wallets with a seed can have other accounts".to_string();
+            return Err(Error::AccountError(error));
+        }
+
+        let accounts = call!(self
+            .actor
+            .find_used_accounts(number_of_accounts, passphrase))
+        .await
+        .map_err(|error| Error::AccountError(error.to_string()))?;
+
+        Ok(accounts)
+    }
+
+    /// Create a wallet for another account of this wallet's seed and select it
+    #[uniffi::method(default(passphrase = None))]
+    pub async fn create_account_wallet(
+        &self,
+        account: u32,
+        passphrase: Option<String>,
+    ) -> Result<WalletMetadata, Error> {
+        if self.metadata.read().wallet_type != WalletType::Hot {
+            let error = "only wallets with a seed can have other accounts".to_string();
+            return Err(Error::AccountError(error));
+        }
+
+        let metadata = call!(self.actor.create_account_wallet(account, passphrase))
+            .await
+            .map_err(|error| Error::AccountError(error.to_string()))?;
+
+        Ok(metadata)
+    }
+
+    /// Every receive and change address handed out so far, and how far past the gap limit they go
+    #[uniffi::method]
+    pub async fn list_addresses(&self) -> Result<AddressList, Error> {
+        let addresses = call!(self.actor.address_list())
+            .await
+            .map_err(|error| Error::GetAddressesError(error.to_string()))?;
+
+        Ok(addresses)
+    }
+
+    /// The wallet's public descriptors, and whether their derivation paths are standard
+    #[uniffi::method]
+    pub async fn descriptor_details(&self) -> Result<DescriptorDetails, Error> {
+        let details = call!(self.actor.descriptor_details())
+            .await
+            .map_err(|_| Error::ActorNotFound)?;
+
+        Ok(details)
+    }
+
+    /// Health and latency of the selected and backup nodes, from the last check
+    #[uniffi::method]
+    pub async fn node_health(&self) -> Result<Vec<NodeHealth>, Error> {
+        let health = call!(self.actor.node_health())
+            .await
+            .map_err(|_| Error::ActorNotFound)?;
+
+        Ok(health)
+    }
+
     /// Get address at the given index
     #[uniffi::method]
     pub async fn address_at(&self, index: u32) -> Result<AddressInfo, Error> {
@@ -656,6 +1132,53 @@ impl RustWalletManager {
         });
     }
 
+    /// Full scan with a much larger gap limit, for wallets restored from services that skip
+    /// addresses and are missing funds
+    #[uniffi::method]
+    pub async fn start_deep_rescan(&self) {
+        debug!("start_deep_rescan: {}", self.id);
+
+        let actor = self.actor.clone();
+        tokio::spawn(async move {
+            send!(actor.start_deep_rescan());
+        });
+    }
+
+    /// Gap limit and batch size used for this wallet's scans, applied from the next scan
+    #[uniffi::method]
+    pub fn set_scan_settings(&self, settings: ScanSettings) -> Result<(), Error> {
+        if settings.gap_limit == 0 {
+            let error = "gap limit must be at least 1".to_string();
+            return Err(Error::InvalidScanSettings(error));
+        }
+
+        if settings.batch_size == Some(0) {
+            let error = "batch size must be at least 1".to_string();
+            return Err(Error::InvalidScanSettings(error));
+        }
+
+        send!(self.actor.set_scan_settings(settings));
+
+        let metadata = {
+            let mut metadata = self.metadata.write();
+            metadata.scan_settings = settings;
+            metadata.clone()
+        };
+
+        self.reconciler
+            .send(WalletManagerReconcileMessage::WalletMetadataChanged(
+                metadata.clone(),
+            ))
+            .expect("failed to send update");
+
+        Database::global()
+            .wallets
+            .update_wallet_metadata(metadata)
+            .map_err(|error| Error::InvalidScanSettings(error.to_string()))?;
+
+        Ok(())
+    }
+
     #[uniffi::method]
     pub fn mark_wallet_as_verified(&self) -> Result<(), Error> {
         {
@@ -755,28 +1278,41 @@ impl RustWalletManager {
         Ok(fees.into())
     }
 
+    #[uniffi::method(default(coin_control = None))]
     pub async fn fee_rate_options_with_total_fee_for_drain(
         &self,
         fee_rate_options: Arc<FeeRateOptionsWithTotalFee>,
         address: Arc<Address>,
+        coin_control: Option<CoinControl>,
     ) -> Result<FeeRateOptionsWithTotalFee, Error> {
         let address = Arc::unwrap_or_clone(address);
         let mut options = Arc::unwrap_or_clone(fee_rate_options);
+        let coin_control = coin_control.unwrap_or_default();
 
         let fast_fee_rate = options.fast.fee_rate;
-        let fast_psbt: Psbt = call!(self.actor.build_drain_tx(address.clone(), fast_fee_rate))
+        let fast_psbt: Psbt =
+            call!(self
+                .actor
+                .build_drain_tx(address.clone(), fast_fee_rate, coin_control.clone()))
             .await
             .map_err(|_| Error::UnknownError("failed to get max send amount".to_string()))?
             .into();
 
         let medium_fee_rate = options.medium.fee_rate;
-        let medium_psbt: Psbt = call!(self.actor.build_drain_tx(address.clone(), medium_fee_rate))
-            .await
-            .map_err(|_| Error::UnknownError("failed to get max send amount".to_string()))?
-            .into();
+        let medium_psbt: Psbt = call!(self.actor.build_drain_tx(
+            address.clone(),
+            medium_fee_rate,
+            coin_control.clone()
+        ))
+        .await
+        .map_err(|_| Error::UnknownError("failed to get max send amount".to_string()))?
+        .into();
 
         let slow_fee_rate = options.slow.fee_rate;
-        let slow_psbt: Psbt = call!(self.actor.build_drain_tx(address.clone(), slow_fee_rate))
+        let slow_psbt: Psbt =
+            call!(self
+                .actor
+                .build_drain_tx(address.clone(), slow_fee_rate, coin_control.clone()))
             .await
             .map_err(|_| Error::UnknownError("failed to get max send amount".to_string()))?
             .into();
@@ -795,7 +1331,10 @@ impl RustWalletManager {
 
         if let Some(mut custom) = options.custom {
             let custom_fee_rate = custom.fee_rate;
-            let custom_psbt: Psbt = call!(self.actor.build_drain_tx(address, custom_fee_rate))
+            let custom_psbt: Psbt =
+                call!(self
+                    .actor
+                    .build_drain_tx(address, custom_fee_rate, coin_control))
                 .await
                 .map_err(|_| Error::UnknownError("failed to get max send amount".to_string()))?
                 .into();
@@ -814,29 +1353,44 @@ impl RustWalletManager {
         amount: Arc<Amount>,
         address: Arc<Address>,
     ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let recipients = vec![AddressAndAmount { address, amount }];
+        self.fee_rate_options_with_total_fee_for_batch(fee_rate_options, recipients, None)
+            .await
+    }
+
+    /// Total fee for each fee rate option, for a transaction paying all the recipients
+    #[uniffi::method(default(coin_control = None))]
+    pub async fn fee_rate_options_with_total_fee_for_batch(
+        &self,
+        fee_rate_options: Option<Arc<FeeRateOptions>>,
+        recipients: Vec<AddressAndAmount>,
+        coin_control: Option<CoinControl>,
+    ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let coin_control = coin_control.unwrap_or_default();
         let fee_rate_options = match fee_rate_options {
             Some(fee_rate_options) => Arc::unwrap_or_clone(fee_rate_options),
             None => self.fee_rate_options().await?,
         };
 
-        let amount = Arc::unwrap_or_clone(amount).into();
-        let address: Address = Arc::unwrap_or_clone(address);
-
         let fast_fee_rate = fee_rate_options.fast.fee_rate.into();
         let medium_fee_rate = fee_rate_options.medium.fee_rate.into();
         let slow_fee_rate = fee_rate_options.slow.fee_rate.into();
 
-        let fast_psbt = call!(self.actor.build_tx(amount, address.clone(), fast_fee_rate))
+        let fast_psbt =
+            call!(self
+                .actor
+                .build_tx(recipients.clone(), fast_fee_rate, coin_control.clone()))
             .await
             .map_err(|error| Error::BuildTxError(error.to_string()))?;
 
-        let medium_psbt = call!(self
-            .actor
-            .build_tx(amount, address.clone(), medium_fee_rate))
-        .await
-        .map_err(|error| Error::BuildTxError(error.to_string()))?;
+        let medium_psbt =
+            call!(self
+                .actor
+                .build_tx(recipients.clone(), medium_fee_rate, coin_control.clone()))
+            .await
+            .map_err(|error| Error::BuildTxError(error.to_string()))?;
 
-        let slow_psbt = call!(self.actor.build_tx(amount, address.clone(), slow_fee_rate))
+        let slow_psbt = call!(self.actor.build_tx(recipients, slow_fee_rate, coin_control))
             .await
             .map_err(|error| Error::BuildTxError(error.to_string()))?;
 
@@ -865,15 +1419,18 @@ impl RustWalletManager {
         Ok(options)
     }
 
+    #[uniffi::method(default(coin_control = None))]
     pub async fn build_drain_transaction(
         &self,
         address: Arc<Address>,
         fee: Arc<FeeRate>,
+        coin_control: Option<CoinControl>,
     ) -> Result<Psbt, Error> {
         let address = Arc::unwrap_or_clone(address);
         let fee = Arc::unwrap_or_clone(fee);
+        let coin_control = coin_control.unwrap_or_default();
 
-        let psbt: Psbt = call!(self.actor.build_drain_tx(address, fee))
+        let psbt: Psbt = call!(self.actor.build_drain_tx(address, fee, coin_control))
             .await
             .map_err(|_| Error::UnknownError("failed to get max send psbt".to_string()))?
             .into();
@@ -901,13 +1458,25 @@ impl RustWalletManager {
         address: Arc<Address>,
         fee_rate: Arc<FeeRate>,
     ) -> Result<Psbt, Error> {
-        let actor = self.actor.clone();
+        let recipients = vec![AddressAndAmount { address, amount }];
+        self.build_batch_transaction(recipients, fee_rate, None)
+            .await
+    }
 
-        let amount = Arc::unwrap_or_clone(amount).into();
-        let address = Arc::unwrap_or_clone(address);
+    /// Build a single transaction paying all the recipients, spending only the coins allowed
+    /// by `coin_control`
+    #[uniffi::method(default(coin_control = None))]
+    pub async fn build_batch_transaction(
+        &self,
+        recipients: Vec<AddressAndAmount>,
+        fee_rate: Arc<FeeRate>,
+        coin_control: Option<CoinControl>,
+    ) -> Result<Psbt, Error> {
+        let actor = self.actor.clone();
         let fee_rate = Arc::unwrap_or_clone(fee_rate).into();
+        let coin_control = coin_control.unwrap_or_default();
 
-        let psbt = call!(actor.build_tx(amount, address, fee_rate))
+        let psbt = call!(actor.build_tx(recipients, fee_rate, coin_control))
             .await
             .map_err(|error| Error::BuildTxError(error.to_string()))?;
 
@@ -919,9 +1488,22 @@ impl RustWalletManager {
         amount: Arc<Amount>,
         address: Arc<Address>,
         fee_rate: Arc<FeeRate>,
+    ) -> Result<ConfirmDetails, Error> {
+        let recipients = vec![AddressAndAmount { address, amount }];
+        self.get_confirm_details_for_batch(recipients, fee_rate, None)
+            .await
+    }
+
+    /// The inputs of the returned details are the coins picked, either by the user or by BDK
+    #[uniffi::method(default(coin_control = None))]
+    pub async fn get_confirm_details_for_batch(
+        &self,
+        recipients: Vec<AddressAndAmount>,
+        fee_rate: Arc<FeeRate>,
+        coin_control: Option<CoinControl>,
     ) -> Result<ConfirmDetails, Error> {
         let psbt = self
-            .build_transaction_with_fee_rate(amount, address, fee_rate.clone())
+            .build_batch_transaction(recipients, fee_rate.clone(), coin_control)
             .await?;
 
         let fee_rate: FeeRate = Arc::unwrap_or_clone(fee_rate);
@@ -934,6 +1516,130 @@ impl RustWalletManager {
         Ok(details)
     }
 
+    /// Details for a replacement of the unconfirmed transaction `tx_id`, paying `fee_rate`
+    pub async fn get_fee_bump_confirm_details(
+        &self,
+        tx_id: Arc<TxId>,
+        fee_rate: Arc<FeeRate>,
+    ) -> Result<ConfirmDetails, Error> {
+        let tx_id = Arc::unwrap_or_clone(tx_id);
+        let fee_rate = Arc::unwrap_or_clone(fee_rate).into();
+
+        let psbt = call!(self.actor.build_fee_bump_tx(tx_id, fee_rate))
+            .await
+            .map_err(|_| Error::FeeBumpError("failed to build replacement".to_string()))??;
+
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate))
+            .await
+            .map_err(|_| {
+                Error::GetConfirmDetailsError("failed to get confirm details".to_string())
+            })?;
+
+        Ok(details)
+    }
+
+    /// Total fee the child transaction pays for each fee rate option, the fee rate of each option
+    /// is the fee rate of the parent + child package
+    pub async fn cpfp_fee_rate_options_with_total_fee(
+        &self,
+        tx_id: Arc<TxId>,
+        fee_rate_options: Option<Arc<FeeRateOptions>>,
+    ) -> Result<FeeRateOptionsWithTotalFee, Error> {
+        let tx_id = Arc::unwrap_or_clone(tx_id);
+        let fee_rate_options = match fee_rate_options {
+            Some(fee_rate_options) => Arc::unwrap_or_clone(fee_rate_options),
+            None => self.fee_rate_options().await?,
+        };
+
+        let fast_fee_rate = fee_rate_options.fast.fee_rate.into();
+        let medium_fee_rate = fee_rate_options.medium.fee_rate.into();
+        let slow_fee_rate = fee_rate_options.slow.fee_rate.into();
+
+        let fast_psbt = call!(self.actor.build_cpfp_tx(tx_id, fast_fee_rate))
+            .await
+            .map_err(|error| Error::CpfpError(error.to_string()))?;
+
+        let medium_psbt = call!(self.actor.build_cpfp_tx(tx_id, medium_fee_rate))
+            .await
+            .map_err(|error| Error::CpfpError(error.to_string()))?;
+
+        let slow_psbt = call!(self.actor.build_cpfp_tx(tx_id, slow_fee_rate))
+            .await
+            .map_err(|error| Error::CpfpError(error.to_string()))?;
+
+        let options = FeeRateOptionsWithTotalFee {
+            fast: FeeRateOptionWithTotalFee::new(
+                fee_rate_options.fast,
+                fast_psbt
+                    .fee()
+                    .map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            medium: FeeRateOptionWithTotalFee::new(
+                fee_rate_options.medium,
+                medium_psbt
+                    .fee()
+                    .map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            slow: FeeRateOptionWithTotalFee::new(
+                fee_rate_options.slow,
+                slow_psbt
+                    .fee()
+                    .map_err(|e| Error::FeesError(e.to_string()))?,
+            ),
+            custom: None,
+        };
+
+        Ok(options)
+    }
+
+    /// Details for a child transaction that speeds up the incoming transaction `tx_id`,
+    /// `fee_rate` is the target fee rate for the parent + child package
+    pub async fn get_cpfp_confirm_details(
+        &self,
+        tx_id: Arc<TxId>,
+        fee_rate: Arc<FeeRate>,
+    ) -> Result<ConfirmDetails, Error> {
+        let tx_id = Arc::unwrap_or_clone(tx_id);
+        let fee_rate = Arc::unwrap_or_clone(fee_rate).into();
+
+        let psbt = call!(self.actor.build_cpfp_tx(tx_id, fee_rate))
+            .await
+            .map_err(|_| Error::CpfpError("failed to build child transaction".to_string()))?;
+
+        let details = call!(self.actor.get_confirm_details(psbt, fee_rate))
+            .await
+            .map_err(|_| {
+                Error::GetConfirmDetailsError("failed to get confirm details".to_string())
+            })?;
+
+        Ok(details)
+    }
+
+    /// Build the replacement transaction and return the route to finish the fee bump,
+    /// hot wallets go to the confirm screen to sign, cold wallets export the PSBT to the signer
+    pub async fn bump_fee(&self, tx_id: Arc<TxId>, fee_rate: Arc<FeeRate>) -> Result<Route, Error> {
+        let details = self.get_fee_bump_confirm_details(tx_id, fee_rate).await?;
+        let details = Arc::new(details);
+
+        let id = self.id.clone();
+        let wallet_type = self.metadata.read().wallet_type;
+
+        let send_route = match wallet_type {
+            WalletType::Hot => SendRoute::Confirm {
+                id,
+                details,
+                signed_transaction: None,
+            },
+            WalletType::Cold => {
+                // keep the record so the signed transaction can be matched when it comes back
+                self.save_unsigned_transaction(details.clone())?;
+                SendRoute::HardwareExport { id, details }
+            }
+        };
+
+        Ok(Route::Send(send_route))
+    }
+
     #[uniffi::method]
     pub fn listen_for_updates(&self, reconciler: Box<dyn WalletManagerReconciler>) {
         let reconcile_receiver = self.reconcile_receiver.clone();
@@ -957,7 +1663,8 @@ impl RustWalletManager {
                 let descriptors = match wallet_address_type {
                     WalletAddressType::WrappedSegwit => json.bip49.clone(),
                     WalletAddressType::Legacy => json.bip44.clone(),
-                    _ => {
+                    WalletAddressType::Taproot => json.bip86.clone(),
+                    WalletAddressType::NativeSegwit => {
                         error!("trying to swtich to native segwit, but already segwit");
                         return Ok(());
                     }
@@ -1143,6 +1850,28 @@ impl RustWalletManager {
     }
 }
 
+impl RustWalletManager {
+    fn try_new_from_multisig_info(info: MultisigInfo) -> Result<Self, Error> {
+        let (sender, receiver) = crossbeam::channel::bounded(1000);
+
+        let wallet = Wallet::try_new_persisted_from_multisig(info)?;
+        let id = wallet.id.clone();
+        let metadata = wallet.metadata.clone();
+
+        // multisig wallets don't need address type discovery, so no scanner
+        let actor = task::spawn_actor(WalletActor::new(wallet, sender.clone()));
+
+        Ok(Self {
+            id,
+            actor,
+            metadata: Arc::new(RwLock::new(metadata)),
+            reconciler: sender,
+            reconcile_receiver: Arc::new(receiver),
+            scanner: None,
+        })
+    }
+}
+
 impl Drop for RustWalletManager {
     fn drop(&mut self) {
         debug!("[DROP] Wallet View manager: {}", self.id);
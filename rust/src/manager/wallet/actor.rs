@@ -1,40 +1,92 @@
 use crate::{
-    database::Database,
+    database::{
+        wallet_data::{WalletDataDb, WalletDataError},
+        Database,
+    },
     manager::wallet::{Error, SendFlowErrorAlert, WalletManagerError},
-    mnemonic,
-    node::client::NodeClient,
-    transaction::{fees::BdkFeeRate, FeeRate, Transaction, TransactionDetails, TxId},
+    message_signing, mnemonic,
+    node::{
+        client::{
+            electrum::{ElectrumClient, SubscriptionEvents},
+            NodeClient,
+        },
+        pool::{NodeHealth, NodePool, NodePoolError},
+        ApiType,
+    },
+    psbt::PsbtDetails,
+    transaction::{fees::BdkFeeRate, FeeRate, OutPoint, Transaction, TransactionDetails, TxId},
     wallet::{
+        address_list::{self, AddressList},
         balance::Balance,
         confirm::{AddressAndAmount, ConfirmDetails, InputOutputDetails, SplitOutput},
-        metadata::BlockSizeLast,
+        descriptor::DescriptorDetails,
+        fingerprint::Fingerprint,
+        label::{Label, LabelType},
+        metadata::{BlockSizeLast, DiscoveryState, ScanSettings, WalletMetadata},
+        multisig::{MultisigInfo, MultisigSigningState},
+        safety_check,
+        utxo::{CoinControl, Utxo},
         Address, AddressInfo, Wallet, WalletAddressType,
     },
+    wallet_scanner::{FoundAccount, WalletScanner},
 };
 use act_zero::*;
 use bdk_chain::{
     bitcoin::Psbt,
-    spk_client::{FullScanResponse, SyncResponse},
+    spk_client::{FullScanResponse, SyncRequest, SyncResponse},
+    ChainPosition,
 };
 use bdk_wallet::{KeychainKind, TxOrdering};
-use bitcoin::{params::Params, Transaction as BdkTransaction};
+use bitcoin::{
+    bip32::{ChildNumber, Xpriv},
+    secp256k1::Secp256k1,
+    Address as BdkAddress, ScriptBuf, Transaction as BdkTransaction, Txid,
+};
 use bitcoin_units::Amount;
-use crossbeam::channel::Sender;
+use crossbeam::channel::{Sender, TryRecvError};
 use eyre::Context as _;
-use std::time::{Duration, UNIX_EPOCH};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
 use tap::TapFallible as _;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use self::mnemonic::{Mnemonic, MnemonicExt as _};
 
-use super::WalletManagerReconcileMessage;
+use super::{BroadcastStatus, WalletManagerReconcileMessage};
+
+/// How often the electrum connection is checked for pushed notifications
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The electrum subscription running in a task, dropping it stops the task
+#[derive(Debug)]
+struct ElectrumSubscription {
+    client: ElectrumClient,
+    /// Newly revealed scripts for the task to subscribe to
+    scripts: Sender<Vec<ScriptBuf>>,
+    /// The last index of each keychain sent to the task
+    last_revealed: BTreeMap<KeychainKind, u32>,
+}
 
 #[derive(Debug)]
 pub struct WalletActor {
     pub addr: WeakAddr<Self>,
     pub reconciler: Sender<WalletManagerReconcileMessage>,
     pub wallet: Wallet,
-    pub node_client: Option<NodeClient>,
+    pub node_pool: NodePool,
+    pub db: WalletDataDb,
+
+    /// Nodes failed over to since the last successful scan
+    scan_failovers: usize,
+
+    /// Replaced when the node client changes, which stops the running subscription
+    subscription: Option<ElectrumSubscription>,
+
+    /// The last details handed to the ui to confirm, hot wallet sends are checked against this
+    /// copy, not against the one the ui sends back with the psbt to sign
+    confirm_details: Option<ConfirmDetails>,
 
     last_scan_finished_: Option<Duration>,
     last_height_fetched_: Option<(Duration, usize)>,
@@ -80,6 +132,10 @@ impl Actor for WalletActor {
                 ));
             }
 
+            Error::SafetyCheckFailed(error) => {
+                self.send(WalletManagerReconcileMessage::SendFlowError(error.into()));
+            }
+
             Error::GetConfirmDetailsError(_) => {
                 self.send(WalletManagerReconcileMessage::SendFlowError(
                     SendFlowErrorAlert::ConfirmDetails(error.to_string()),
@@ -97,11 +153,18 @@ impl Actor for WalletActor {
 
 impl WalletActor {
     pub fn new(wallet: Wallet, reconciler: Sender<WalletManagerReconcileMessage>) -> Self {
+        let db = WalletDataDb::new(wallet.id.clone());
+        let node_pool = NodePool::load(wallet.network);
+
         Self {
             addr: Default::default(),
             reconciler,
             wallet,
-            node_client: None,
+            node_pool,
+            db,
+            scan_failovers: 0,
+            subscription: None,
+            confirm_details: None,
             last_scan_finished_: None,
             last_height_fetched_: None,
             state: ActorState::Initial,
@@ -110,46 +173,381 @@ impl WalletActor {
 
     pub async fn balance(&mut self) -> ActorResult<Balance> {
         let balance = self.wallet.balance();
-        Produces::ok(balance)
+        let frozen_utxos = self
+            .frozen_utxos()
+            .tap_err(|error| error!("unable to load frozen utxos: {error}"))
+            .map_err(|error| Error::WalletBalanceError(error.to_string()))?;
+
+        if frozen_utxos.is_empty() {
+            return Produces::ok(balance);
+        }
+
+        // split the frozen coins up the same way bdk does for the full balance
+        let mut frozen = bdk_wallet::Balance::default();
+        let frozen_outputs = self
+            .wallet
+            .list_unspent()
+            .filter(|output| frozen_utxos.contains(&output.outpoint));
+
+        for output in frozen_outputs {
+            let amount = output.txout.value;
+            match output.chain_position {
+                ChainPosition::Confirmed { .. } => frozen.confirmed += amount,
+                ChainPosition::Unconfirmed { .. } if output.keychain == KeychainKind::Internal => {
+                    frozen.trusted_pending += amount
+                }
+                ChainPosition::Unconfirmed { .. } => frozen.untrusted_pending += amount,
+            }
+        }
+
+        Produces::ok(Balance::with_frozen(balance.balance, frozen))
+    }
+
+    /// Every revealed receive and change address, with usage and balance
+    pub async fn address_list(&mut self) -> ActorResult<AddressList> {
+        let labels = self.db.get_labels()?;
+        let gap_limit = self.wallet.metadata.scan_settings.gap_limit;
+        let addresses = AddressList::new(&self.wallet, &labels, gap_limit);
+
+        Produces::ok(addresses)
+    }
+
+    pub async fn descriptor_details(&mut self) -> ActorResult<DescriptorDetails> {
+        Produces::ok(DescriptorDetails::new(&self.wallet))
+    }
+
+    /// All the wallet's unspent outputs, largest first
+    pub async fn utxos(&mut self) -> ActorResult<Vec<Utxo>> {
+        let network = self.wallet.network();
+        let frozen_utxos = self.frozen_utxos()?;
+        let labels = self.db.get_labels()?;
+
+        let mut utxos = self
+            .wallet
+            .list_unspent()
+            .map(|output| {
+                let outpoint = output.outpoint;
+                let is_frozen = frozen_utxos.contains(&outpoint);
+                let utxo = Utxo::try_new(output, network, is_frozen)?;
+
+                Ok(Utxo {
+                    label: labels.output_label(&outpoint),
+                    ..utxo
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()
+            .context("unable to get address for utxo")?;
+
+        utxos.sort_unstable_by(|a, b| b.amount.cmp(&a.amount));
+
+        Produces::ok(utxos)
+    }
+
+    /// Freeze or unfreeze a utxo, frozen utxos are never spent by new transactions
+    pub async fn set_utxo_frozen(&mut self, outpoint: OutPoint, frozen: bool) -> ActorResult<()> {
+        self.db.set_utxo_frozen((&outpoint).into(), frozen)?;
+
+        let balance = self
+            .balance()
+            .await?
+            .await
+            .map_err(|error| Error::WalletBalanceError(error.to_string()))?;
+
+        self.send(WalletManagerReconcileMessage::WalletBalanceChanged(
+            balance.into(),
+        ));
+
+        Produces::ok(())
     }
 
-    pub async fn build_drain_tx(&mut self, address: Address, fee: FeeRate) -> ActorResult<Psbt> {
+    /// Send everything to `address`, when coins are included only those are sent
+    pub async fn build_drain_tx(
+        &mut self,
+        address: Address,
+        fee: FeeRate,
+        coin_control: CoinControl,
+    ) -> ActorResult<Psbt> {
+        let coin_control = self.with_frozen_excluded(coin_control)?;
         let script_pubkey = address.script_pubkey();
         let mut tx_builder = self.wallet.build_tx();
 
-        tx_builder
-            .drain_wallet()
-            .drain_to(script_pubkey)
-            .fee_rate(fee.into());
+        if coin_control.include.is_empty() {
+            tx_builder.drain_wallet();
+        }
+
+        coin_control
+            .apply(&mut tx_builder)
+            .map_err(|error| Error::BuildTxError(error.to_string()))?;
+
+        tx_builder.drain_to(script_pubkey).fee_rate(fee.into());
 
         let psbt = tx_builder.finish()?;
         Produces::ok(psbt)
     }
 
+    /// Build a transaction paying every recipient, outputs are kept in the order given
     pub async fn build_tx(
         &mut self,
-        amount: Amount,
-        address: Address,
+        recipients: Vec<AddressAndAmount>,
         fee_rate: BdkFeeRate,
+        coin_control: CoinControl,
     ) -> ActorResult<Psbt> {
-        let script_pubkey = address.script_pubkey();
+        if recipients.is_empty() {
+            return Err(Error::BuildTxError("no recipients".to_string()).into());
+        }
+
+        let coin_control = self.with_frozen_excluded(coin_control)?;
 
         let mut tx_builder = self.wallet.build_tx();
         tx_builder.ordering(TxOrdering::Untouched);
-        tx_builder.add_recipient(script_pubkey, amount);
         tx_builder.fee_rate(fee_rate);
 
+        coin_control
+            .apply(&mut tx_builder)
+            .map_err(|error| Error::BuildTxError(error.to_string()))?;
+
+        for recipient in recipients {
+            let amount: Amount = (*recipient.amount).into();
+            tx_builder.add_recipient(recipient.address.script_pubkey(), amount);
+        }
+
         let psbt = tx_builder.finish()?;
 
         Produces::ok(psbt)
     }
 
+    /// Build a replacement for an unconfirmed transaction we sent, paying the new fee rate (RBF)
+    ///
+    /// Returns the error in the value, so the caller gets BDK's reason instead of a cancelled call
+    pub async fn build_fee_bump_tx(
+        &mut self,
+        tx_id: TxId,
+        fee_rate: BdkFeeRate,
+    ) -> ActorResult<Result<Psbt, Error>> {
+        Produces::ok(self.fee_bump_psbt(tx_id, fee_rate))
+    }
+
+    fn fee_bump_psbt(&mut self, tx_id: TxId, fee_rate: BdkFeeRate) -> Result<Psbt, Error> {
+        let frozen_utxos = self
+            .frozen_utxos()
+            .map_err(|error| Error::FeeBumpError(error.to_string()))?;
+
+        let mut tx_builder = self
+            .wallet
+            .build_fee_bump(tx_id.0)
+            .tap_err(|error| error!("unable to bump fee: {error}"))
+            .map_err(|error| Error::FeeBumpError(error.to_string()))?;
+
+        tx_builder.fee_rate(fee_rate);
+
+        // extra inputs might be needed to pay the higher fee, never take frozen ones
+        for outpoint in frozen_utxos {
+            tx_builder.add_unspendable(outpoint);
+        }
+
+        tx_builder
+            .finish()
+            .map_err(|error| Error::FeeBumpError(error.to_string()))
+    }
+
+    /// Build a child transaction spending our outputs of the unconfirmed transaction `tx_id` back
+    /// to the wallet, paying enough so the parent + child package reaches `target_fee_rate` (CPFP)
+    pub async fn build_cpfp_tx(
+        &mut self,
+        tx_id: TxId,
+        target_fee_rate: BdkFeeRate,
+    ) -> ActorResult<Psbt> {
+        fn err(s: &str) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            Error::CpfpError(s.to_string()).into()
+        }
+
+        let parent = self
+            .wallet
+            .get_tx(tx_id.0)
+            .ok_or_else(|| err("transaction not found"))?;
+
+        if parent.chain_position.is_confirmed() {
+            return Err(err("transaction is already confirmed"));
+        }
+
+        let parent_tx = parent.tx_node.tx.clone();
+        let parent_vsize = parent_tx.vsize() as u64;
+        let parent_fee = self
+            .wallet
+            .calculate_fee(&parent_tx)
+            .tap_err(|error| error!("unable to calculate parent fee: {error}"))
+            .map_err(|_| err("unable to determine the fee of the unconfirmed transaction"))?;
+
+        // a child can only speed up the parent if the package pays more than the parent does on
+        // its own, this also rules out a zero target fee rate
+        let parent_weight = parent_tx.weight().to_wu();
+        if target_fee_rate
+            .to_sat_per_kwu()
+            .saturating_mul(parent_weight)
+            <= parent_fee.to_sat().saturating_mul(1000)
+        {
+            return Err(err(
+                "fee rate must be higher than the unconfirmed transaction's fee rate",
+            ));
+        }
+
+        let frozen_utxos = self.frozen_utxos()?;
+        let outpoints = self
+            .wallet
+            .list_unspent()
+            .filter(|utxo| utxo.outpoint.txid == tx_id.0)
+            .filter(|utxo| !frozen_utxos.contains(&utxo.outpoint))
+            .map(|utxo| utxo.outpoint)
+            .collect::<Vec<_>>();
+
+        if outpoints.is_empty() {
+            return Err(err("no unspent outputs to spend from this transaction"));
+        }
+
+        // peek instead of revealing, so building a child the user never sends doesn't use up a
+        // change address, scanning the broadcast child reveals it
+        let change_script = match self
+            .wallet
+            .list_unused_addresses(KeychainKind::Internal)
+            .next()
+        {
+            Some(address) => address.script_pubkey(),
+            None => {
+                let index = self.wallet.next_derivation_index(KeychainKind::Internal);
+                let address = self.wallet.peek_address(KeychainKind::Internal, index);
+                address.script_pubkey()
+            }
+        };
+
+        // build once at the target rate, to find out how big the child is
+        let mut tx_builder = self.wallet.build_tx();
+        tx_builder
+            .add_utxos(&outpoints)
+            .map_err(|error| Error::CpfpError(error.to_string()))?
+            .manually_selected_only()
+            .drain_to(change_script.clone())
+            .fee_rate(target_fee_rate);
+
+        let child_psbt = tx_builder.finish()?;
+        let child_fee_at_target = child_psbt.fee()?;
+        let child_vsize = (child_fee_at_target.to_sat() * 250)
+            .checked_div(target_fee_rate.to_sat_per_kwu())
+            .ok_or_else(|| err("fee rate must be above zero"))?;
+
+        let child_fee = crate::transaction::fees::cpfp_child_fee(
+            parent_fee,
+            parent_vsize,
+            child_vsize,
+            target_fee_rate,
+        )
+        .ok_or_else(|| err("fee overflow"))?
+        .max(child_fee_at_target);
+
+        // rebuild paying the fee for the whole package
+        let mut tx_builder = self.wallet.build_tx();
+        tx_builder
+            .add_utxos(&outpoints)
+            .map_err(|error| Error::CpfpError(error.to_string()))?
+            .manually_selected_only()
+            .drain_to(change_script)
+            .fee_absolute(child_fee);
+
+        let psbt = tx_builder
+            .finish()
+            .map_err(|error| Error::CpfpError(error.to_string()))?;
+
+        Produces::ok(psbt)
+    }
+
+    /// Coins the user froze, frozen coins that have since been spent are dropped from the set
+    fn frozen_utxos(&self) -> Result<Vec<bitcoin::OutPoint>, WalletDataError> {
+        let mut frozen_utxos = self.db.get_frozen_utxos()?;
+        let count = frozen_utxos.len();
+
+        // only drop coins of transactions we know about, unknown ones might not be scanned yet
+        frozen_utxos.retain(|outpoint| {
+            self.wallet.get_tx(outpoint.txid).is_none() || self.wallet.get_utxo(*outpoint).is_some()
+        });
+
+        if frozen_utxos.len() != count {
+            debug!("pruning {} spent frozen utxos", count - frozen_utxos.len());
+            self.db.set_frozen_utxos(frozen_utxos.clone())?;
+        }
+
+        Ok(frozen_utxos)
+    }
+
+    /// Add the frozen utxos to the coins the transaction is not allowed to spend
+    fn with_frozen_excluded(
+        &self,
+        mut coin_control: CoinControl,
+    ) -> Result<CoinControl, WalletDataError> {
+        let frozen_utxos = self.frozen_utxos()?;
+        let frozen_utxos = frozen_utxos
+            .into_iter()
+            .map(|outpoint| Arc::new(outpoint.into()));
+
+        coin_control.exclude.extend(frozen_utxos);
+        Ok(coin_control)
+    }
+
+    /// Save user labels, output labels marked as not spendable freeze the output
+    pub async fn save_labels(&mut self, labels: Vec<Label>) -> ActorResult<()> {
+        let spendable_changes = labels
+            .iter()
+            .filter_map(|label| Some((label.outpoint()?, label.spendable?)))
+            .collect::<Vec<_>>();
+
+        self.db.save_labels(labels)?;
+
+        for (outpoint, spendable) in spendable_changes {
+            self.db.set_utxo_frozen(outpoint, !spendable)?;
+        }
+
+        let transactions = self
+            .transactions()
+            .await?
+            .await
+            .map_err(|error| Error::TransactionsRetrievalError(error.to_string()))?;
+
+        self.send(WalletManagerReconcileMessage::LabelsChanged(transactions));
+
+        Produces::ok(())
+    }
+
+    /// All the user's labels as BIP-329 JSONL, frozen outputs are exported as not spendable
+    pub async fn export_labels(&mut self) -> ActorResult<String> {
+        let mut labels = self.db.get_labels()?;
+
+        for outpoint in self.frozen_utxos()? {
+            let reference = outpoint.to_string();
+            let label = labels
+                .get(LabelType::Output, &reference)
+                .cloned()
+                .unwrap_or_else(|| Label {
+                    label_type: LabelType::Output,
+                    reference,
+                    label: None,
+                    origin: None,
+                    spendable: None,
+                });
+
+            labels.insert(Label {
+                spendable: Some(false),
+                ..label
+            });
+        }
+
+        Produces::ok(labels.to_jsonl()?)
+    }
+
     pub async fn transactions(&mut self) -> ActorResult<Vec<Transaction>> {
         let zero = Amount::ZERO.into();
+        let labels = self.db.get_labels()?;
         let mut transactions = self
             .wallet
             .transactions()
-            .map(|tx| Transaction::new(&self.wallet, tx))
+            .map(|tx| Transaction::new(&self.wallet, tx, &labels))
             .filter(|tx| tx.sent_and_received().amount() > zero)
             .collect::<Vec<Transaction>>();
 
@@ -187,38 +585,45 @@ impl WalletActor {
             WalletManagerError::GetConfirmDetailsError(s.to_string()).into()
         }
 
-        let external_outputs = psbt
+        let mut recipient_outputs = psbt
             .unsigned_tx
             .output
             .iter()
             .filter(|output| !self.wallet.is_mine(output.script_pubkey.clone()))
             .collect::<Vec<&bitcoin::TxOut>>();
 
-        if external_outputs.len() > 1 {
-            return Err(error(
-                "multiple address to send to found, not currently supported",
-            ));
-        }
+        // if there are no external outputs, this is a consolidation txn,
+        // sending to the same wallet so use the first output
+        if recipient_outputs.is_empty() {
+            let output = psbt
+                .unsigned_tx
+                .output
+                .first()
+                .ok_or_else(|| error("no addess to send to found"))?;
 
-        // if there is an external output, use that
-        // otherwise this is a consolidation txn, sending to the same wallet so use the first output
-        let output = external_outputs
-            .first()
-            .cloned()
-            .or_else(|| psbt.unsigned_tx.output.first())
-            .ok_or_else(|| error("no addess to send to found"))?;
+            recipient_outputs.push(output);
+        }
 
-        let params = Params::from(self.wallet.network());
-        let sending_to = bitcoin::Address::from_script(&output.script_pubkey, params)
+        let network = self.wallet.network();
+        let recipients = recipient_outputs
+            .iter()
+            .map(|output| AddressAndAmount::try_new(output, network))
+            .collect::<Result<Vec<_>, _>>()
             .context("unable to get address from script")?;
 
-        let sending_amount = output.value;
+        let sending_to = Address::clone(&recipients[0].address);
+        let sending_amount = recipient_outputs
+            .iter()
+            .try_fold(Amount::ZERO, |total, output| {
+                total.checked_add(output.value)
+            })
+            .ok_or_else(|| error("amount overflow, cannot calculate sending amount"))?;
+
         let fee = psbt.fee()?;
         let spending_amount = sending_amount
             .checked_add(fee)
             .ok_or_else(|| error("fee overflow, cannot calculate spending amount"))?;
 
-        let network = self.wallet.network();
         let psbt = psbt.into();
         let more_details = InputOutputDetails::new(&psbt, network);
         let details = ConfirmDetails {
@@ -226,31 +631,74 @@ impl WalletActor {
             sending_amount: sending_amount.into(),
             fee_total: fee.into(),
             fee_rate: fee_rate.into(),
-            sending_to: sending_to.into(),
+            sending_to,
             psbt,
             more_details,
+            recipients,
         };
 
+        self.confirm_details = Some(details.clone());
         Produces::ok(details)
     }
 
-    pub async fn sign_and_broadcast_transaction(&mut self, mut psbt: Psbt) -> ActorResult<()> {
+    /// The passphrase is only used to derive the keys for signing, it is never saved
+    pub async fn sign_and_broadcast_transaction(
+        &mut self,
+        details: ConfirmDetails,
+        passphrase: Option<String>,
+    ) -> ActorResult<BroadcastStatus> {
         fn err(s: &str) -> Box<dyn std::error::Error + Send + Sync + 'static> {
             Error::SignAndBroadcastError(s.to_string()).into()
         }
 
-        // TODO: temporary, remove to allow sending on mainnet
-        if self.wallet.network == crate::network::Network::Bitcoin {
-            return Err(err("sending on mainnet not supported yet"));
+        let approved = self
+            .confirm_details
+            .clone()
+            .ok_or_else(|| err("no approved transaction to sign"))?;
+
+        let mut psbt = details.psbt.0.clone();
+        let finalized = self.sign_with_mnemonic(&mut psbt, passphrase)?;
+
+        if !finalized {
+            return Err(err("transaction not finalized, unable to sign"));
+        }
+
+        let transaction = psbt
+            .extract_tx()
+            .tap_err(|error| error!("failed to extract transaction: {error}"))
+            .map_err(|_| err("failed to extract transaction"))?;
+
+        self.check_and_broadcast_transaction(transaction, approved)
+            .await
+    }
+
+    /// Sign an imported psbt with this hot wallet, returns the psbt and if it is now finalized
+    pub async fn sign_psbt(
+        &mut self,
+        mut psbt: Psbt,
+        passphrase: Option<String>,
+    ) -> ActorResult<(Psbt, bool)> {
+        let finalized = self.sign_with_mnemonic(&mut psbt, passphrase)?;
+        Produces::ok((psbt, finalized))
+    }
+
+    /// Sign with the keys derived from the mnemonic, true if the psbt was finalized
+    fn sign_with_mnemonic(
+        &self,
+        psbt: &mut Psbt,
+        passphrase: Option<String>,
+    ) -> Result<bool, Error> {
+        fn err(s: &str) -> Error {
+            Error::SignAndBroadcastError(s.to_string())
         }
 
         let network = self.wallet.network;
-        let mnemonic = Mnemonic::try_from_id(&self.wallet.metadata.id)
-            .tap_err(|error| error!("failed to get mnemonic for wallet: {error}"))
-            .map_err(|_| err("failed to get mnemonic for wallet"))?;
+        let (mnemonic, passphrase) = self.mnemonic_for_signing(passphrase, err)?;
 
+        let address_type = self.wallet.metadata.address_type;
+        let account = self.wallet.metadata.account_index;
         let descriptors =
-            mnemonic.into_descriptors(None, network, self.wallet.metadata.address_type);
+            mnemonic.into_descriptors_for_account(passphrase, network, address_type, account);
 
         let create_params = descriptors.into_create_params().network(network.into());
 
@@ -260,38 +708,322 @@ impl WalletActor {
             .tap_err(|error| error!("failed to create wallet: {error}"))
             .map_err(|_| err("unable to sign"))?;
 
-        let finalized = wallet
-            .sign(&mut psbt, Default::default())
+        wallet
+            .sign(psbt, Default::default())
             .tap_err(|error| error!("failed to sign: {error}"))
-            .map_err(|_| err("unable to sign"))?;
+            .map_err(|_| err("unable to sign"))
+    }
+
+    /// The mnemonic and the checked passphrase, errors if the passphrase is missing or wrong
+    fn mnemonic_for_signing(
+        &self,
+        passphrase: Option<String>,
+        err: fn(&str) -> Error,
+    ) -> Result<(Mnemonic, Option<String>), Error> {
+        let network = self.wallet.network;
+        let mnemonic = Mnemonic::try_from_id(&self.wallet.metadata.id)
+            .tap_err(|error| error!("failed to get mnemonic for wallet: {error}"))
+            .map_err(|_| err("failed to get mnemonic for wallet"))?;
+
+        let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+        if self.wallet.metadata.has_passphrase {
+            let Some(passphrase) = passphrase.as_deref() else {
+                return Err(err("passphrase required to sign"));
+            };
+
+            // a wrong passphrase derives a different wallet, catch it before signing
+            let fingerprint: Fingerprint = mnemonic
+                .xpub_with_passphrase(network.into(), Some(passphrase))
+                .fingerprint()
+                .into();
+
+            if self.wallet.metadata.master_fingerprint.as_deref() != Some(&fingerprint) {
+                return Err(err("incorrect passphrase"));
+            }
+        }
+
+        Ok((mnemonic, passphrase))
+    }
+
+    /// Sign a message with the key of one of this wallet's addresses
+    pub async fn sign_message(
+        &mut self,
+        address: BdkAddress,
+        message: String,
+        passphrase: Option<String>,
+    ) -> ActorResult<Result<String, Error>> {
+        Produces::ok(self.message_signature(address, message, passphrase))
+    }
+
+    fn message_signature(
+        &self,
+        address: BdkAddress,
+        message: String,
+        passphrase: Option<String>,
+    ) -> Result<String, Error> {
+        fn err(s: &str) -> Error {
+            Error::MessageSigningError(s.to_string())
+        }
+
+        let (keychain, index) = self
+            .wallet
+            .derivation_of_spk(address.script_pubkey())
+            .ok_or_else(|| err("address does not belong to this wallet"))?;
+
+        let path = address_list::derivation_path(&self.wallet, keychain, index)
+            .ok_or_else(|| err("unable to get derivation path for address"))?;
+
+        let (mnemonic, passphrase) = self.mnemonic_for_signing(passphrase, err)?;
+        let seed = mnemonic.to_seed(passphrase.as_deref().unwrap_or(""));
+
+        let secp = Secp256k1::new();
+        let secret_key = Xpriv::new_master(self.wallet.network(), &seed)
+            .and_then(|master| master.derive_priv(&secp, &path))
+            .map_err(|error| err(&error.to_string()))?
+            .private_key;
+
+        message_signing::sign_message(&message, &address, &secret_key)
+            .map_err(|error| err(&error.to_string()))
+    }
+
+    /// Accounts of this wallet's seed with history, for the wallet's address type
+    pub async fn find_used_accounts(
+        &mut self,
+        number_of_accounts: u32,
+        passphrase: Option<String>,
+    ) -> ActorResult<Vec<FoundAccount>> {
+        fn err(s: &str) -> Error {
+            Error::AccountError(s.to_string())
+        }
+
+        let (mnemonic, passphrase) = self.mnemonic_for_signing(passphrase, err)?;
+        let settings = self.wallet.scan_settings();
+
+        let address_type = self.wallet.metadata.address_type;
+        let accounts = WalletScanner::find_used_accounts(
+            &mnemonic,
+            passphrase,
+            address_type,
+            number_of_accounts,
+            &mut self.node_pool,
+            settings,
+        )
+        .await;
+
+        self.report_node_switch();
+        let accounts = accounts.map_err(|error| err(&error.to_string()))?;
+
+        Produces::ok(accounts)
+    }
+
+    /// Create and select a new wallet for another account of this wallet's seed
+    pub async fn create_account_wallet(
+        &mut self,
+        account: u32,
+        passphrase: Option<String>,
+    ) -> ActorResult<WalletMetadata> {
+        fn err(s: &str) -> Error {
+            Error::AccountError(s.to_string())
+        }
+
+        if ChildNumber::from_hardened_idx(account).is_err() {
+            return Err(err("account index is too large").into());
+        }
+
+        let current = self.wallet.metadata.clone();
+        let network = Database::global().global_config.selected_network();
+        let mode = Database::global().global_config.wallet_mode();
+
+        let already_added = Database::global()
+            .wallets
+            .get_all(network, mode)?
+            .into_iter()
+            .any(|wallet| {
+                wallet.master_fingerprint == current.master_fingerprint
+                    && wallet.account_index == account
+            });
+
+        if already_added {
+            return Err(err("a wallet for this account already exists").into());
+        }
+
+        let (mnemonic, passphrase) = self.mnemonic_for_signing(passphrase, err)?;
+        let fingerprint = current.master_fingerprint.clone().unwrap_or_default();
+
+        let mut metadata = WalletMetadata::new(format!("{} #{account}", current.name), fingerprint);
+        metadata.verified = true;
+        metadata.address_type = current.address_type;
+        metadata.account_index = account;
+        metadata.has_passphrase = current.has_passphrase;
+        metadata.scan_settings = current.scan_settings;
+
+        // address type discovery only looks at account 0
+        metadata.discovery_state = DiscoveryState::Single;
+
+        Wallet::try_new_persisted_and_selected(metadata.clone(), mnemonic.into(), passphrase)
+            .map_err(|error| err(&error.to_string()))?;
+
+        Produces::ok(metadata)
+    }
+
+    /// Inputs and outputs of an imported psbt, matched against this wallet
+    pub async fn psbt_details(&mut self, psbt: Psbt) -> ActorResult<PsbtDetails> {
+        let wallet: &bdk_wallet::Wallet = &self.wallet;
+        let psbt = crate::psbt::Psbt::from(psbt);
+
+        Produces::ok(psbt.details_for_wallet(Some(wallet), self.wallet.network.into()))
+    }
+
+    /// Finalize a fully signed psbt, `None` if it still needs signatures
+    pub async fn finalize_psbt(&mut self, mut psbt: Psbt) -> ActorResult<Option<BdkTransaction>> {
+        let finalized = self
+            .wallet
+            .finalize_psbt(&mut psbt, Default::default())
+            .map_err(|error| Error::PsbtError(error.to_string()))?;
 
         if !finalized {
-            return Err(err("transaction not finalized, unable to sign"));
+            return Produces::ok(None);
         }
 
         let transaction = psbt
             .extract_tx()
-            .tap_err(|error| error!("failed to extract transaction: {error}"))
-            .map_err(|_| err("failed to extract transaction"))?;
+            .map_err(|error| Error::PsbtError(error.to_string()))?;
 
-        self.broadcast_transaction(transaction).await?;
+        Produces::ok(Some(transaction))
+    }
 
-        Produces::ok(())
+    /// Run the safety checks against the details the user approved, only broadcast if they pass
+    pub async fn check_and_broadcast_transaction(
+        &mut self,
+        transaction: BdkTransaction,
+        details: ConfirmDetails,
+    ) -> ActorResult<BroadcastStatus> {
+        let fee = self
+            .wallet
+            .calculate_fee(&transaction)
+            .tap_err(|error| error!("failed to calculate fee: {error}"))
+            .map_err(|error| Error::SignAndBroadcastError(error.to_string()))?;
+
+        safety_check::check_transaction(&transaction, &details, fee, self.wallet.network)
+            .tap_err(|error| error!("transaction failed safety check: {error}"))
+            .map_err(Error::SafetyCheckFailed)?;
+
+        self.broadcast_transaction(transaction).await
+    }
+
+    /// Merge the signatures a cosigner's device added into our copy of the psbt
+    pub async fn add_multisig_signatures(
+        &mut self,
+        mut psbt: Psbt,
+        signed: Psbt,
+    ) -> ActorResult<(Psbt, MultisigSigningState)> {
+        psbt.combine(signed)
+            .map_err(|error| Error::MultisigError(format!("psbt does not match: {error}")))?;
+
+        let state = self.finalize_multisig_psbt(&mut psbt)?;
+        Produces::ok((psbt, state))
+    }
+
+    /// Sign with the cosigners that are hot wallets on this device
+    pub async fn sign_multisig_with_local_cosigners(
+        &mut self,
+        mut psbt: Psbt,
+    ) -> ActorResult<(Psbt, MultisigSigningState)> {
+        let signed = self
+            .multisig_info()?
+            .sign_with_local_cosigners(&mut psbt, self.wallet.network)
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        if signed == 0 {
+            let error = "none of the cosigners are wallets on this device".to_string();
+            return Err(Error::MultisigError(error).into());
+        }
+
+        let state = self.finalize_multisig_psbt(&mut psbt)?;
+        Produces::ok((psbt, state))
+    }
+
+    fn multisig_info(&self) -> Result<MultisigInfo, Error> {
+        self.wallet
+            .metadata
+            .multisig
+            .clone()
+            .ok_or_else(|| Error::MultisigError("not a multisig wallet".to_string()))
+    }
+
+    /// Finalize the psbt once enough cosigners signed, otherwise only report the progress
+    fn finalize_multisig_psbt(&self, psbt: &mut Psbt) -> Result<MultisigSigningState, Error> {
+        let progress = self.multisig_info()?.signing_progress(psbt);
+        if progress.signatures < progress.required {
+            return Ok(MultisigSigningState {
+                progress,
+                transaction: None,
+            });
+        }
+
+        let finalized = self
+            .wallet
+            .finalize_psbt(psbt, Default::default())
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        if !finalized {
+            let error = "unable to finalize transaction".to_string();
+            return Err(Error::MultisigError(error));
+        }
+
+        let transaction = psbt
+            .clone()
+            .extract_tx()
+            .map_err(|error| Error::MultisigError(error.to_string()))?;
+
+        Ok(MultisigSigningState {
+            progress,
+            transaction: Some(Arc::new(transaction.into())),
+        })
     }
 
-    pub async fn broadcast_transaction(&mut self, transaction: BdkTransaction) -> ActorResult<()> {
+    pub async fn broadcast_transaction(
+        &mut self,
+        transaction: BdkTransaction,
+    ) -> ActorResult<BroadcastStatus> {
         fn err(s: &str) -> Box<dyn std::error::Error + Send + Sync + 'static> {
             Error::SignAndBroadcastError(s.to_string()).into()
         }
 
-        self.node_client
-            .as_ref()
-            .ok_or_else(|| err("node client not set"))?
-            .broadcast_transaction(transaction)
-            .await
-            .map_err(|_error| err("failed to broadcast transaction, try again"))?;
+        let settings = self.wallet.scan_settings();
+        let unconfirmed = transaction.clone();
+        let broadcast = move |client: NodeClient| {
+            let transaction = transaction.clone();
+            async move { client.broadcast_transaction(transaction).await }
+        };
 
-        Produces::ok(())
+        let result = self.node_pool.call(settings, broadcast).await;
+        self.report_node_switch();
+
+        // a rejection has the node's reason, ex: a fee too low for its mempool
+        result.map_err(|error| match error {
+            NodePoolError::BroadcastRejected(error) => err(&error.to_string()),
+            _ => err("failed to broadcast transaction, try again"),
+        })?;
+
+        // compact block filter peers can't tell us about the mempool, so the wallet only sees
+        // its own transaction before it confirms if it's added here
+        let is_cbf = self
+            .node_pool
+            .current_node()
+            .is_some_and(|node| node.api_type == ApiType::Cbf);
+
+        if !is_cbf {
+            return Produces::ok(BroadcastStatus::Accepted);
+        }
+
+        // peers stopped sending `reject` messages, so a rejected transaction looks the same
+        // as an accepted one, it stays unconfirmed until a later scan finds it in a block
+        let now = UNIX_EPOCH.elapsed().unwrap().as_secs();
+        self.wallet.apply_unconfirmed_txs([(unconfirmed, now)]);
+        self.wallet.persist()?;
+
+        Produces::ok(BroadcastStatus::Unverified)
     }
 
     pub async fn address_at(&mut self, index: u32) -> ActorResult<AddressInfo> {
@@ -301,29 +1033,66 @@ impl WalletActor {
 
     pub async fn next_address(&mut self) -> ActorResult<AddressInfo> {
         let address = self.wallet.get_next_address()?;
+        self.subscribe_revealed_scripts();
+
         Produces::ok(address)
     }
 
+    /// Only fails when none of the nodes in the pool can be reached
     pub async fn check_node_connection(&mut self) -> ActorResult<()> {
-        let node_client = match &self.node_client {
-            Some(node_client) => node_client,
-            None => {
-                let node = Database::global().global_config.selected_node();
-                let node_client = NodeClient::new(&node).await?;
-                self.node_client = Some(node_client);
+        let settings = self.wallet.scan_settings();
+        let result = self
+            .node_pool
+            .call(settings, |client| async move { client.check_url().await })
+            .await;
 
-                self.node_client.as_ref().expect("just checked")
-            }
-        };
+        self.report_node_switch();
+        result.map_err(|error| Error::NodeConnectionFailed(error.to_string()))?;
 
-        node_client
-            .check_url()
-            .await
-            .map_err(|error| Error::NodeConnectionFailed(error.to_string()))?;
+        Produces::ok(())
+    }
+
+    pub async fn node_health(&mut self) -> ActorResult<Vec<NodeHealth>> {
+        Produces::ok(self.node_pool.health())
+    }
 
+    async fn apply_node_health(&mut self, health: Vec<NodeHealth>) -> ActorResult<()> {
+        self.node_pool.apply_health(health);
         Produces::ok(())
     }
 
+    /// Check every node in the pool in the background, at most every few minutes
+    fn check_node_health_in_task(&mut self) {
+        if !self.node_pool.needs_health_check() {
+            return;
+        }
+
+        let nodes = self.node_pool.nodes();
+        let addr = self.addr.clone();
+        self.addr.send_fut(async move {
+            let health = NodePool::check_health(nodes).await;
+            send!(addr.apply_node_health(health));
+        });
+    }
+
+    /// Connect to the first healthy node in the pool, reports a switch to a different node
+    async fn connect_node(&mut self, settings: ScanSettings) -> Result<(), Error> {
+        self.node_pool.reload(self.wallet.network);
+        let result = self.node_pool.connect(settings).await;
+        self.report_node_switch();
+
+        result
+            .map(|_client| ())
+            .map_err(|error| Error::NodeConnectionFailed(error.to_string()))
+    }
+
+    fn report_node_switch(&mut self) {
+        if let Some(node) = self.node_pool.take_switch() {
+            info!("wallet switched to node {}", node.name);
+            self.send(WalletManagerReconcileMessage::NodeSwitched(node));
+        }
+    }
+
     pub async fn wallet_scan_and_notify(&mut self, force_scan: bool) -> ActorResult<()> {
         use WalletManagerReconcileMessage as Msg;
         debug!("wallet_scan_and_notify");
@@ -371,36 +1140,45 @@ impl WalletActor {
 
         self.reconciler.send(Msg::StartedWalletScan).unwrap();
 
-        let node = Database::global().global_config.selected_node();
-        let reconciler = self.reconciler.clone();
-
-        // save the node client
-        match NodeClient::new(&node).await {
-            Ok(client) => {
-                self.node_client = Some(client);
-            }
-            Err(error) => {
-                reconciler
-                    .send(Msg::NodeConnectionFailed(error.to_string()))
-                    .unwrap();
-
-                return Err(error.into());
-            }
-        }
-
-        assert!(self.node_client.is_some());
-
-        // check the node connection, and send frontend the error if it fails
-        send!(self.addr.check_node_connection());
+        // connecting checks the node, and fails over to the next one if it's down
+        let settings = self.wallet.scan_settings();
+        self.connect_node(settings).await?;
+        self.check_node_health_in_task();
 
         // perform that scanning in a background task
         let addr = self.addr.clone();
         if self.wallet.metadata.performed_full_scan {
-            send!(addr.perform_incremental_scan());
+            send!(addr.perform_incremental_scan(settings));
         } else {
-            send!(addr.perform_full_scan());
+            send!(addr.perform_full_scan(settings));
+        }
+
+        Produces::ok(())
+    }
+
+    /// Full scan that looks much further past the last used address than a normal scan
+    pub async fn start_deep_rescan(&mut self) -> ActorResult<()> {
+        use WalletManagerReconcileMessage as Msg;
+        debug!("start_deep_rescan");
+
+        if matches!(self.state, ActorState::PerformingFullScan) {
+            info!("skipping deep rescan, full scan already in progress");
+            return Produces::ok(());
         }
 
+        self.reconciler.send(Msg::StartedWalletScan).unwrap();
+
+        // the next regular scan recreates the client with the wallet's own settings
+        let settings = self.wallet.scan_settings().deep_rescan();
+        self.connect_node(settings).await?;
+
+        send!(self.addr.perform_full_scan(settings));
+
+        Produces::ok(())
+    }
+
+    pub async fn set_scan_settings(&mut self, settings: ScanSettings) -> ActorResult<()> {
+        self.wallet.metadata.scan_settings = settings;
         Produces::ok(())
     }
 
@@ -449,15 +1227,14 @@ impl WalletActor {
     }
 
     async fn update_height(&mut self) -> ActorResult<usize> {
-        let node_client = self
-            .node_client
-            .as_ref()
-            .ok_or(eyre::eyre!("node client not set"))?;
+        let settings = self.wallet.scan_settings();
+        let result = self
+            .node_pool
+            .call(settings, |client| async move { client.get_height().await })
+            .await;
 
-        let block_height = node_client
-            .get_height()
-            .await
-            .map_err(|_| Error::GetHeightError)?;
+        self.report_node_switch();
+        let block_height = result.map_err(|_| Error::GetHeightError)?;
 
         self.set_last_height_fetched(block_height);
         Produces::ok(block_height)
@@ -477,7 +1254,8 @@ impl WalletActor {
         Produces::ok(details)
     }
 
-    async fn perform_full_scan(&mut self) -> ActorResult<()> {
+    /// `settings` are the ones the node client was connected with, a failover reconnects with them
+    async fn perform_full_scan(&mut self, settings: ScanSettings) -> ActorResult<()> {
         debug!("starting full scan");
 
         self.state = ActorState::PerformingFullScan;
@@ -487,11 +1265,9 @@ impl WalletActor {
 
         let graph = self.wallet.tx_graph().clone();
         let node_client = self
-            .node_client
-            .clone()
-            .as_ref()
-            .ok_or(eyre::eyre!("node client not set"))?
-            .clone();
+            .node_pool
+            .client()
+            .ok_or(eyre::eyre!("node client not set"))?;
 
         let addr = self.addr.clone();
         self.addr.send_fut(async move {
@@ -503,13 +1279,13 @@ impl WalletActor {
             debug!("done full scan in {}s", now - start);
 
             // update wallet state
-            send!(addr.handle_full_scan_complete(full_scan_result));
+            send!(addr.handle_full_scan_complete(full_scan_result, settings));
         });
 
         Produces::ok(())
     }
 
-    async fn perform_incremental_scan(&mut self) -> ActorResult<()> {
+    async fn perform_incremental_scan(&mut self, settings: ScanSettings) -> ActorResult<()> {
         debug!("starting incremental scan");
         self.state = ActorState::PerformingIncrementalScan;
 
@@ -518,10 +1294,9 @@ impl WalletActor {
         let scan_request = self.wallet.start_sync_with_revealed_spks().build();
         let graph = self.wallet.tx_graph().clone();
         let node_client = self
-            .node_client
-            .as_ref()
-            .ok_or(eyre::eyre!("node client not set"))?
-            .clone();
+            .node_pool
+            .client()
+            .ok_or(eyre::eyre!("node client not set"))?;
 
         let addr = self.addr.clone();
         self.addr.send_fut(async move {
@@ -530,7 +1305,7 @@ impl WalletActor {
             debug!("done incremental scan in {}s", now - start);
 
             // update wallet state
-            send!(addr.handle_incremental_scan_complete(sync_result));
+            send!(addr.handle_incremental_scan_complete(sync_result, settings));
         });
 
         Produces::ok(())
@@ -539,10 +1314,14 @@ impl WalletActor {
     async fn handle_full_scan_complete(
         &mut self,
         full_scan_result: Result<FullScanResponse<KeychainKind>, crate::node::client::Error>,
+        settings: ScanSettings,
     ) -> ActorResult<()> {
         debug!("applying full scan result");
 
-        let full_scan_result = full_scan_result?;
+        let full_scan_result = match full_scan_result {
+            Ok(full_scan_result) => full_scan_result,
+            Err(error) => return self.fail_over_scan(error, true, settings).await,
+        };
 
         self.wallet.apply_update(full_scan_result)?;
         self.wallet.persist()?;
@@ -561,8 +1340,13 @@ impl WalletActor {
     async fn handle_incremental_scan_complete(
         &mut self,
         sync_result: Result<SyncResponse, crate::node::client::Error>,
+        settings: ScanSettings,
     ) -> ActorResult<()> {
-        let sync_result = sync_result?;
+        let sync_result = match sync_result {
+            Ok(sync_result) => sync_result,
+            Err(error) => return self.fail_over_scan(error, false, settings).await,
+        };
+
         self.wallet.apply_update(sync_result)?;
         self.wallet.persist()?;
         self.set_last_scan_finished();
@@ -572,6 +1356,33 @@ impl WalletActor {
         Produces::ok(())
     }
 
+    /// Retry a failed scan on the next node in the pool, until every node has been tried,
+    /// with the settings the scan started with, ex: a deep rescan's gap limit
+    async fn fail_over_scan(
+        &mut self,
+        error: crate::node::client::Error,
+        full_scan: bool,
+        settings: ScanSettings,
+    ) -> ActorResult<()> {
+        self.node_pool.mark_current_failed(&error);
+
+        self.scan_failovers += 1;
+        if self.scan_failovers >= self.node_pool.len() {
+            self.scan_failovers = 0;
+            return Err(Error::NodeConnectionFailed(error.to_string()).into());
+        }
+
+        self.connect_node(settings).await?;
+
+        if full_scan {
+            send!(self.addr.perform_full_scan(settings));
+        } else {
+            send!(self.addr.perform_incremental_scan(settings));
+        }
+
+        Produces::ok(())
+    }
+
     /// Mark the wallet as scanned
     /// Notify the frontend that the wallet scan is complete
     /// Ssend the wallet balance and transactions
@@ -580,6 +1391,7 @@ impl WalletActor {
 
         // set the scan state to complete
         self.state = ActorState::ScanComplete;
+        self.scan_failovers = 0;
 
         // get and send wallet balance
         let balance = self
@@ -599,6 +1411,192 @@ impl WalletActor {
 
         self.send(Msg::ScanComplete(transactions));
 
+        self.update_subscriptions();
+
+        Produces::ok(())
+    }
+
+    /// Electrum servers push updates for the revealed scripts and new blocks, so payments show
+    /// up without waiting for the next scan, other node types only update when scanned
+    ///
+    /// A new subscription is only started when the scan connected a new client, otherwise the
+    /// scripts revealed since are added to the running one
+    fn update_subscriptions(&mut self) {
+        let Some(NodeClient::Electrum(client)) = self.node_pool.client() else {
+            self.subscription = None;
+            return;
+        };
+
+        // sending fails once the task stopped, ex: after the connection failed
+        let is_subscribed = self.subscription.as_ref().is_some_and(|subscription| {
+            subscription.client.is_same_connection(&client)
+                && subscription.scripts.send(vec![]).is_ok()
+        });
+
+        if !is_subscribed {
+            self.start_subscriptions_in_task(client);
+        }
+
+        self.subscribe_revealed_scripts();
+    }
+
+    fn start_subscriptions_in_task(&mut self, client: ElectrumClient) {
+        let (sender, receiver) = crossbeam::channel::unbounded::<Vec<ScriptBuf>>();
+        let addr = self.addr.clone();
+
+        // replacing the sender stops the previous task
+        self.subscription = Some(ElectrumSubscription {
+            client: client.clone(),
+            scripts: sender,
+            last_revealed: BTreeMap::new(),
+        });
+
+        self.addr.send_fut(async move {
+            let mut subscriptions = client.subscriptions();
+
+            loop {
+                // stops once the actor is dropped or a newer subscription replaces this one
+                let mut scripts = vec![];
+                loop {
+                    match receiver.try_recv() {
+                        Ok(new_scripts) => scripts.extend(new_scripts),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => return,
+                    }
+                }
+
+                let events = match subscriptions.subscribe(scripts).await {
+                    Ok(()) => subscriptions.poll().await,
+                    Err(error) => Err(error),
+                };
+
+                match events {
+                    Ok(events) if events.is_empty() => {}
+                    Ok(events) => send!(addr.handle_subscription_events(events)),
+                    Err(error) => {
+                        warn!("stopping electrum subscriptions: {error}");
+                        return;
+                    }
+                }
+
+                tokio::time::sleep(SUBSCRIPTION_POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Send the scripts revealed since the last call to the running subscription
+    fn subscribe_revealed_scripts(&mut self) {
+        let Some(subscription) = &mut self.subscription else {
+            return;
+        };
+
+        let scripts = new_revealed_scripts(&self.wallet, &mut subscription.last_revealed);
+        if scripts.is_empty() {
+            return;
+        }
+
+        // the task stopped, the next scan starts a new one
+        if subscription.scripts.send(scripts).is_err() {
+            self.subscription = None;
+        }
+    }
+
+    async fn handle_subscription_events(&mut self, events: SubscriptionEvents) -> ActorResult<()> {
+        if let Some(block_height) = events.new_height {
+            debug!("new block at height {block_height}");
+            self.set_last_height_fetched(block_height);
+        }
+
+        let Some(sync_request) = self.subscription_sync_request(events.scripts) else {
+            return Produces::ok(());
+        };
+
+        let graph = self.wallet.tx_graph().clone();
+        let Some(node_client) = self.node_pool.client() else {
+            return Produces::ok(());
+        };
+
+        let addr = self.addr.clone();
+        self.addr.send_fut(async move {
+            let sync_result = node_client.sync(&graph, sync_request).await;
+            send!(addr.handle_subscription_sync_complete(sync_result));
+        });
+
+        Produces::ok(())
+    }
+
+    /// Sync request for only the scripts with new activity, `None` if none of them are ours
+    fn subscription_sync_request(
+        &self,
+        scripts: Vec<ScriptBuf>,
+    ) -> Option<SyncRequest<(KeychainKind, u32)>> {
+        let scripts: HashSet<ScriptBuf> = scripts.into_iter().collect();
+        let spks: Vec<_> = self
+            .wallet
+            .spk_index()
+            .revealed_spks(..)
+            .filter(|(_, script)| scripts.contains(script))
+            .collect();
+
+        if spks.is_empty() {
+            return None;
+        }
+
+        let sync_request = SyncRequest::builder()
+            .chain_tip(self.wallet.local_chain().tip())
+            .spks_with_indexes(spks)
+            .build();
+
+        Some(sync_request)
+    }
+
+    async fn handle_subscription_sync_complete(
+        &mut self,
+        sync_result: Result<SyncResponse, crate::node::client::Error>,
+    ) -> ActorResult<()> {
+        use WalletManagerReconcileMessage as Msg;
+
+        // the next regular scan will pick up the changes
+        let sync_result = match sync_result {
+            Ok(sync_result) => sync_result,
+            Err(error) => {
+                warn!("failed to sync scripts with new activity: {error}");
+                return Produces::ok(());
+            }
+        };
+
+        let known_txids: HashSet<Txid> = self
+            .wallet
+            .transactions()
+            .map(|tx| tx.tx_node.txid)
+            .collect();
+
+        self.wallet.apply_update(sync_result)?;
+        self.wallet.persist()?;
+        self.subscribe_revealed_scripts();
+
+        let balance = self
+            .balance()
+            .await?
+            .await
+            .map_err(|error| Error::WalletBalanceError(error.to_string()))?;
+
+        self.send(Msg::WalletBalanceChanged(balance.into()));
+
+        let transactions: Vec<Transaction> = self
+            .transactions()
+            .await?
+            .await
+            .map_err(|error| Error::TransactionsRetrievalError(error.to_string()))?;
+
+        for transaction in &transactions {
+            if !known_txids.contains(&transaction.id().0) {
+                self.send(Msg::NewTransaction(transaction.clone()));
+            }
+        }
+
+        self.send(Msg::ScanComplete(transactions));
+
         Produces::ok(())
     }
 
@@ -692,6 +1690,28 @@ fn elapsed_secs_since(earlier: Duration) -> u64 {
     (now - earlier).as_secs()
 }
 
+/// Scripts revealed after the indexes in `last_revealed`, which are moved up to the wallet's
+fn new_revealed_scripts(
+    wallet: &Wallet,
+    last_revealed: &mut BTreeMap<KeychainKind, u32>,
+) -> Vec<ScriptBuf> {
+    let spk_index = wallet.spk_index();
+
+    let mut scripts = vec![];
+    for (keychain, last_index) in spk_index.last_revealed_indices() {
+        let start = last_revealed.get(&keychain).map_or(0, |index| index + 1);
+        let revealed = spk_index
+            .revealed_keychain_spks(keychain)
+            .filter(|(index, _)| *index >= start)
+            .map(|(_, script)| script);
+
+        scripts.extend(revealed);
+        last_revealed.insert(keychain, last_index);
+    }
+
+    scripts
+}
+
 impl WalletActor {
     fn send(&self, msg: WalletManagerReconcileMessage) {
         self.reconciler.send(msg).unwrap();
@@ -703,3 +1723,372 @@ impl Drop for WalletActor {
         debug!("[DROP] Wallet Actor");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bdk_chain::TxUpdate;
+    use bdk_wallet::psbt::PsbtUtils as _;
+    use bitcoin::{
+        absolute, hashes::Hash as _, transaction, OutPoint as BdkOutPoint, Sequence, TxIn, TxOut,
+        WPubkeyHash,
+    };
+    use crossbeam::channel::Receiver;
+
+    use super::*;
+
+    fn test_actor() -> (WalletActor, Receiver<WalletManagerReconcileMessage>) {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        (WalletActor::new(Wallet::new_for_test(), sender), receiver)
+    }
+
+    /// Receive `sats` to a new address of the wallet, returns the outpoint of the new coin
+    fn receive(actor: &mut WalletActor, sats: u64) -> BdkOutPoint {
+        let address = actor.wallet.bdk.reveal_next_address(KeychainKind::External);
+        let seed = actor.wallet.bdk.transactions().count() as u8 + 1;
+        let tx = payment(&address, sats, seed);
+
+        let outpoint = BdkOutPoint::new(tx.compute_txid(), 0);
+        actor.wallet.bdk.apply_unconfirmed_txs([(tx, 1)]);
+        outpoint
+    }
+
+    /// A transaction paying `sats` to the address, `seed` makes the spent coin unique
+    fn payment(address: &BdkAddress, sats: u64, seed: u8) -> BdkTransaction {
+        BdkTransaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: BdkOutPoint::new(Txid::from_byte_array([seed; 32]), 0),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(sats),
+                script_pubkey: address.script_pubkey(),
+            }],
+        }
+    }
+
+    fn recipient(actor: &WalletActor, sats: u64) -> AddressAndAmount {
+        let txout = TxOut {
+            value: Amount::from_sat(sats),
+            script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([1; 20])),
+        };
+
+        AddressAndAmount::try_new(&txout, actor.wallet.network.into()).unwrap()
+    }
+
+    fn fee_rate(sat_per_vb: u64) -> BdkFeeRate {
+        BdkFeeRate::from_sat_per_vb_unchecked(sat_per_vb)
+    }
+
+    async fn build_tx(
+        actor: &mut WalletActor,
+        recipients: Vec<AddressAndAmount>,
+        coin_control: CoinControl,
+    ) -> Psbt {
+        actor
+            .build_tx(recipients, fee_rate(2), coin_control)
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+    }
+
+    /// Send a transaction from the wallet and see it in the mempool
+    async fn send(actor: &mut WalletActor, sequence: Sequence) -> BdkTransaction {
+        let recipients = vec![recipient(actor, 20_000)];
+        let psbt = build_tx(actor, recipients, CoinControl::default()).await;
+
+        let mut tx = psbt.unsigned_tx;
+        for input in tx.input.iter_mut() {
+            input.sequence = sequence;
+        }
+
+        actor.wallet.bdk.apply_unconfirmed_txs([(tx.clone(), 2)]);
+        tx
+    }
+
+    /// The coin as it shows up in the inputs of the confirm details
+    fn coin(actor: &WalletActor, outpoint: BdkOutPoint) -> AddressAndAmount {
+        let utxo = actor.wallet.bdk.get_utxo(outpoint).unwrap();
+        AddressAndAmount::try_new(&utxo.txout, actor.wallet.network.into()).unwrap()
+    }
+
+    fn spent_outpoints(psbt: &Psbt) -> Vec<BdkOutPoint> {
+        let inputs = psbt.unsigned_tx.input.iter();
+        inputs.map(|input| input.previous_output).collect()
+    }
+
+    async fn confirm_details(actor: &mut WalletActor, psbt: Psbt) -> ConfirmDetails {
+        let fee_rate = fee_rate(2);
+        actor
+            .get_confirm_details(psbt, fee_rate)
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+    }
+
+    async fn build_fee_bump_tx(
+        actor: &mut WalletActor,
+        tx: &BdkTransaction,
+        sat_per_vb: u64,
+    ) -> Result<Psbt, Error> {
+        let tx_id = tx.compute_txid().into();
+        let fee_rate = fee_rate(sat_per_vb);
+
+        actor
+            .build_fee_bump_tx(tx_id, fee_rate)
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fee_bump_pays_a_higher_fee_rate() {
+        let (mut actor, _receiver) = test_actor();
+        let funding = receive(&mut actor, 100_000);
+        let original = send(&mut actor, Sequence::ENABLE_RBF_NO_LOCKTIME).await;
+
+        let replacement = build_fee_bump_tx(&mut actor, &original, 10).await.unwrap();
+
+        let original_fee_rate = actor.wallet.bdk.calculate_fee_rate(&original).unwrap();
+        assert!(replacement.fee_rate().unwrap() > original_fee_rate);
+
+        // the replacement conflicts with the original by spending the same coin
+        let inputs = &replacement.unsigned_tx.input;
+        assert!(inputs.iter().any(|input| input.previous_output == funding));
+    }
+
+    #[tokio::test]
+    async fn test_fee_bump_rejects_non_rbf_transaction() {
+        let (mut actor, _receiver) = test_actor();
+        receive(&mut actor, 100_000);
+        let original = send(&mut actor, Sequence::MAX).await;
+
+        let error = build_fee_bump_tx(&mut actor, &original, 10)
+            .await
+            .unwrap_err();
+
+        // the reason from bdk is kept, not replaced by a generic message
+        assert!(matches!(error, Error::FeeBumpError(_)));
+        assert!(error.to_string().contains("can't be replaced"));
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_broadcast_needs_details_from_the_wallet() {
+        let (mut actor, _receiver) = test_actor();
+        receive(&mut actor, 100_000);
+        let recipients = vec![recipient(&actor, 20_000)];
+        let psbt = build_tx(&mut actor, recipients, CoinControl::default()).await;
+        let details = confirm_details(&mut actor, psbt).await;
+
+        // details this wallet never handed out can't be checked, so they are not signed
+        let (mut other, _receiver) = test_actor();
+        let error = other
+            .sign_and_broadcast_transaction(details, None)
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<Error>();
+        assert!(matches!(error, Some(Error::SignAndBroadcastError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cpfp_pays_for_the_parent_without_revealing_change() {
+        let (mut actor, _receiver) = test_actor();
+        receive(&mut actor, 100_000);
+        let parent = send(&mut actor, Sequence::MAX).await;
+        let change_index = actor.wallet.bdk.derivation_index(KeychainKind::Internal);
+
+        let tx_id = parent.compute_txid().into();
+        let child = actor.build_cpfp_tx(tx_id, fee_rate(10)).await.unwrap();
+        let child = child.await.unwrap();
+
+        let inputs = child.unsigned_tx.input.iter();
+        assert!(inputs.all(|input| input.previous_output.txid == parent.compute_txid()));
+        assert_eq!(
+            actor.wallet.bdk.derivation_index(KeychainKind::Internal),
+            change_index
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cpfp_rejects_fee_rate_not_above_the_parent() {
+        let (mut actor, _receiver) = test_actor();
+        receive(&mut actor, 100_000);
+        let parent = send(&mut actor, Sequence::MAX).await;
+
+        // the parent pays 2 sat/vb
+        for sat_per_vb in [0, 1] {
+            let tx_id = parent.compute_txid().into();
+            let error = actor
+                .build_cpfp_tx(tx_id, fee_rate(sat_per_vb))
+                .await
+                .unwrap_err();
+
+            let error = error.downcast_ref::<Error>();
+            assert!(matches!(error, Some(Error::CpfpError(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coin_control_only_include_spends_only_chosen_coins() {
+        let (mut actor, _receiver) = test_actor();
+        receive(&mut actor, 50_000);
+        let chosen = receive(&mut actor, 60_000);
+        receive(&mut actor, 70_000);
+
+        let coin_control = CoinControl {
+            include: vec![Arc::new(chosen.into())],
+            exclude: vec![],
+            only_include: true,
+        };
+
+        let recipients = vec![recipient(&actor, 20_000)];
+        let psbt = build_tx(&mut actor, recipients, coin_control).await;
+        assert_eq!(spent_outpoints(&psbt), vec![chosen]);
+
+        let details = confirm_details(&mut actor, psbt).await;
+        assert_eq!(details.inputs(), vec![coin(&actor, chosen)]);
+    }
+
+    #[tokio::test]
+    async fn test_coin_control_exclude_never_spends_excluded_coins() {
+        let (mut actor, _receiver) = test_actor();
+        let first = receive(&mut actor, 50_000);
+        let second = receive(&mut actor, 60_000);
+        let excluded = receive(&mut actor, 100_000);
+
+        let coin_control = CoinControl {
+            include: vec![],
+            exclude: vec![Arc::new(excluded.into())],
+            only_include: false,
+        };
+
+        // needs two coins, the excluded one alone would have been enough
+        let recipients = vec![recipient(&actor, 80_000)];
+        let psbt = build_tx(&mut actor, recipients, coin_control).await;
+
+        let mut spent = spent_outpoints(&psbt);
+        spent.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(spent, expected);
+
+        let details = confirm_details(&mut actor, psbt).await;
+        assert!(!details.inputs().contains(&coin(&actor, excluded)));
+        assert!(details.inputs().contains(&coin(&actor, first)));
+        assert!(details.inputs().contains(&coin(&actor, second)));
+    }
+
+    #[tokio::test]
+    async fn test_build_tx_leaves_frozen_coins_unspent() {
+        let (mut actor, _receiver) = test_actor();
+        let frozen = receive(&mut actor, 100_000);
+        let spendable = receive(&mut actor, 50_000);
+        let freeze = actor.set_utxo_frozen(frozen.into(), true).await.unwrap();
+        freeze.await.unwrap();
+
+        // the frozen coin alone would have been enough
+        let recipients = vec![recipient(&actor, 20_000)];
+        let psbt = build_tx(&mut actor, recipients, CoinControl::default()).await;
+        assert_eq!(spent_outpoints(&psbt), vec![spendable]);
+    }
+
+    #[tokio::test]
+    async fn test_spent_frozen_coins_are_pruned() {
+        let (mut actor, _receiver) = test_actor();
+        let frozen = receive(&mut actor, 100_000);
+        let freeze = actor.set_utxo_frozen(frozen.into(), true).await.unwrap();
+        freeze.await.unwrap();
+        assert_eq!(actor.frozen_utxos().unwrap(), vec![frozen]);
+
+        // spent somewhere else, ex: by another wallet with the same keys
+        let spend = BdkTransaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: frozen,
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(90_000),
+                script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([2; 20])),
+            }],
+        };
+        actor.wallet.bdk.apply_unconfirmed_txs([(spend, 2)]);
+
+        assert!(actor.frozen_utxos().unwrap().is_empty());
+        assert!(actor.db.get_frozen_utxos().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revealed_scripts_are_subscribed_once() {
+        let (mut actor, _receiver) = test_actor();
+        let first = actor.wallet.bdk.reveal_next_address(KeychainKind::External);
+
+        let mut last_revealed = BTreeMap::new();
+        let scripts = new_revealed_scripts(&actor.wallet, &mut last_revealed);
+        assert!(scripts.contains(&first.script_pubkey()));
+        assert!(new_revealed_scripts(&actor.wallet, &mut last_revealed).is_empty());
+
+        let second = actor.wallet.bdk.reveal_next_address(KeychainKind::External);
+        assert_eq!(
+            new_revealed_scripts(&actor.wallet, &mut last_revealed),
+            vec![second.script_pubkey()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscription_activity_notifies_only_new_transactions() {
+        let (mut actor, receiver) = test_actor();
+        receive(&mut actor, 50_000);
+        let known = actor.wallet.bdk.transactions().next().unwrap().tx_node.tx;
+
+        let address = actor.wallet.bdk.reveal_next_address(KeychainKind::External);
+        actor.wallet.bdk.reveal_next_address(KeychainKind::External);
+
+        // what the electrum server pushed, one of our addresses and a script that isn't ours
+        let events = SubscriptionEvents {
+            scripts: vec![
+                address.script_pubkey(),
+                ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([2; 20])),
+            ],
+            new_height: None,
+        };
+
+        let mut request = actor.subscription_sync_request(events.scripts).unwrap();
+        let synced: Vec<ScriptBuf> = request.iter_spks().collect();
+        assert_eq!(synced, vec![address.script_pubkey()]);
+
+        // the node returns the new payment and a transaction the wallet already has
+        let new = payment(&address, 30_000, 100);
+        let tx_update = TxUpdate {
+            txs: vec![known, Arc::new(new.clone())],
+            seen_ats: [(new.compute_txid(), 3)].into(),
+            ..Default::default()
+        };
+
+        let response = SyncResponse {
+            tx_update,
+            chain_update: None,
+        };
+
+        actor
+            .handle_subscription_sync_complete(Ok(response))
+            .await
+            .unwrap();
+
+        let notified: Vec<Txid> = receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                WalletManagerReconcileMessage::NewTransaction(tx) => Some(tx.id().0),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(notified, vec![new.compute_txid()]);
+    }
+}
@@ -0,0 +1,415 @@
+//! Sign and verify messages with the key of an address, BIP-137 for legacy and wrapped segwit
+//! addresses, BIP-322 simple signatures for native segwit and taproot addresses
+
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use bitcoin::{
+    absolute::LockTime,
+    consensus, ecdsa,
+    hashes::{sha256, Hash as _, HashEngine as _},
+    key::{Keypair, TapTweak as _},
+    opcodes::{all::OP_RETURN, OP_0},
+    script::Builder,
+    secp256k1::{
+        ecdsa::{RecoverableSignature, RecoveryId},
+        Message, Secp256k1, SecretKey, XOnlyPublicKey,
+    },
+    sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType},
+    sign_message::signed_msg_hash,
+    taproot,
+    transaction::Version,
+    Address as BdkAddress, AddressType, Amount, CompressedPublicKey, OutPoint, PublicKey,
+    ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+
+use crate::wallet::Address;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum MessageSigningError {
+    #[error("Unsupported address type, only legacy, wrapped segwit, native segwit and taproot addresses are supported")]
+    UnsupportedAddress,
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Unable to sign message: {0}")]
+    SigningFailed(String),
+}
+
+type Error = MessageSigningError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+// BIP-137 header bytes, the recovery id is added to these
+const HEADER_P2PKH_COMPRESSED: u8 = 31;
+const HEADER_P2SH_P2WPKH: u8 = 35;
+
+/// Sign the message with the key for the address, returns the base64 encoded signature
+pub fn sign_message(message: &str, address: &BdkAddress, secret_key: &SecretKey) -> Result<String> {
+    let signature = match address.address_type() {
+        Some(AddressType::P2pkh) => sign_bip137(message, secret_key, HEADER_P2PKH_COMPRESSED),
+        Some(AddressType::P2sh) => sign_bip137(message, secret_key, HEADER_P2SH_P2WPKH),
+        Some(AddressType::P2wpkh) | Some(AddressType::P2tr) => {
+            sign_bip322_simple(message, address.script_pubkey(), secret_key)?
+        }
+        _ => return Err(Error::UnsupportedAddress),
+    };
+
+    // catches a key that doesn't belong to the address
+    if !verify_message(message, address, &signature)? {
+        let error = "key does not match the address".to_string();
+        return Err(Error::SigningFailed(error));
+    }
+
+    Ok(signature)
+}
+
+/// Verify a base64 encoded BIP-137 or BIP-322 simple signature for the address
+pub fn verify_message(message: &str, address: &BdkAddress, signature: &str) -> Result<bool> {
+    let signature = BASE64_STANDARD
+        .decode(signature.trim())
+        .map_err(|error| Error::InvalidSignature(error.to_string()))?;
+
+    // a BIP-322 witness starts with the number of items, never a BIP-137 header
+    match signature.as_slice() {
+        [header @ 27..=42, ..] if signature.len() == 65 => {
+            verify_bip137(message, address.script_pubkey(), *header, &signature[1..])
+        }
+        _ => verify_bip322_simple(message, address, &signature),
+    }
+}
+
+fn sign_bip137(message: &str, secret_key: &SecretKey, header: u8) -> String {
+    let secp = Secp256k1::signing_only();
+    let message = Message::from_digest(signed_msg_hash(message).to_byte_array());
+
+    let signature = secp.sign_ecdsa_recoverable(&message, secret_key);
+    let (recovery_id, compact) = signature.serialize_compact();
+
+    let mut bytes = [0u8; 65];
+    bytes[0] = header + recovery_id.to_i32() as u8;
+    bytes[1..].copy_from_slice(&compact);
+
+    BASE64_STANDARD.encode(bytes)
+}
+
+/// Recovers the public key, then checks if any of the scripts for that key match the address,
+/// some signers always use the P2PKH header, so the header type is not enforced
+fn verify_bip137(
+    message: &str,
+    script_pubkey: ScriptBuf,
+    header: u8,
+    compact: &[u8],
+) -> Result<bool> {
+    let invalid = |error: bitcoin::secp256k1::Error| Error::InvalidSignature(error.to_string());
+
+    let recovery_id = RecoveryId::from_i32(((header - 27) & 0x03) as i32).map_err(invalid)?;
+    let signature = RecoverableSignature::from_compact(compact, recovery_id).map_err(invalid)?;
+
+    let secp = Secp256k1::verification_only();
+    let message = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let Ok(public_key) = secp.recover_ecdsa(&message, &signature) else {
+        return Ok(false);
+    };
+
+    // headers 27-30 are for uncompressed keys
+    if header < 31 {
+        let public_key = PublicKey::new_uncompressed(public_key);
+        return Ok(ScriptBuf::new_p2pkh(&public_key.pubkey_hash()) == script_pubkey);
+    }
+
+    let public_key = CompressedPublicKey(public_key);
+    let p2wpkh = ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash());
+
+    let matches = ScriptBuf::new_p2pkh(&public_key.pubkey_hash()) == script_pubkey
+        || ScriptBuf::new_p2sh(&p2wpkh.script_hash()) == script_pubkey
+        || p2wpkh == script_pubkey;
+
+    Ok(matches)
+}
+
+fn sign_bip322_simple(
+    message: &str,
+    script_pubkey: ScriptBuf,
+    secret_key: &SecretKey,
+) -> Result<String> {
+    let secp = Secp256k1::new();
+    let to_spend = to_spend(message, script_pubkey.clone());
+    let to_sign = to_sign(&to_spend);
+    let mut cache = SighashCache::new(&to_sign);
+
+    let witness = if script_pubkey.is_p2wpkh() {
+        let sighash = cache
+            .p2wpkh_signature_hash(0, &script_pubkey, Amount::ZERO, EcdsaSighashType::All)
+            .map_err(|error| Error::SigningFailed(error.to_string()))?;
+
+        let message = Message::from_digest(sighash.to_byte_array());
+        let signature = ecdsa::Signature::sighash_all(secp.sign_ecdsa(&message, secret_key));
+
+        Witness::p2wpkh(&signature, &secret_key.public_key(&secp))
+    } else {
+        let keypair = Keypair::from_secret_key(&secp, secret_key)
+            .tap_tweak(&secp, None)
+            .to_inner();
+
+        let sighash = cache
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&to_spend.output),
+                TapSighashType::Default,
+            )
+            .map_err(|error| Error::SigningFailed(error.to_string()))?;
+
+        let message = Message::from_digest(sighash.to_byte_array());
+        let signature = taproot::Signature {
+            signature: secp.sign_schnorr_no_aux_rand(&message, &keypair),
+            sighash_type: TapSighashType::Default,
+        };
+
+        Witness::p2tr_key_spend(&signature)
+    };
+
+    Ok(BASE64_STANDARD.encode(consensus::serialize(&witness)))
+}
+
+fn verify_bip322_simple(message: &str, address: &BdkAddress, witness: &[u8]) -> Result<bool> {
+    let witness: Witness = consensus::deserialize(witness)
+        .map_err(|error| Error::InvalidSignature(error.to_string()))?;
+
+    let secp = Secp256k1::verification_only();
+    let script_pubkey = address.script_pubkey();
+    let to_spend = to_spend(message, script_pubkey.clone());
+    let to_sign = to_sign(&to_spend);
+    let mut cache = SighashCache::new(&to_sign);
+
+    let items = witness.to_vec();
+    match (address.address_type(), items.as_slice()) {
+        (Some(AddressType::P2wpkh), [signature, public_key]) => {
+            let Ok(signature) = ecdsa::Signature::from_slice(signature) else {
+                return Ok(false);
+            };
+
+            let Ok(public_key) = CompressedPublicKey::from_slice(public_key) else {
+                return Ok(false);
+            };
+
+            if ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash()) != script_pubkey {
+                return Ok(false);
+            }
+
+            let Ok(sighash) = cache.p2wpkh_signature_hash(
+                0,
+                &script_pubkey,
+                Amount::ZERO,
+                signature.sighash_type,
+            ) else {
+                return Ok(false);
+            };
+
+            let message = Message::from_digest(sighash.to_byte_array());
+            let valid = secp.verify_ecdsa(&message, &signature.signature, &public_key.0);
+
+            Ok(valid.is_ok())
+        }
+
+        (Some(AddressType::P2tr), [signature]) => {
+            let Ok(signature) = taproot::Signature::from_slice(signature) else {
+                return Ok(false);
+            };
+
+            // witness program of the taproot output is the tweaked x-only key
+            let Ok(output_key) = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]) else {
+                return Ok(false);
+            };
+
+            let Ok(sighash) = cache.taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&to_spend.output),
+                signature.sighash_type,
+            ) else {
+                return Ok(false);
+            };
+
+            let message = Message::from_digest(sighash.to_byte_array());
+            let valid = secp.verify_schnorr(&signature.signature, &message, &output_key);
+
+            Ok(valid.is_ok())
+        }
+
+        (Some(AddressType::P2wpkh | AddressType::P2tr), _) => Ok(false),
+        _ => Err(Error::UnsupportedAddress),
+    }
+}
+
+/// BIP-322 tagged hash of the message
+fn message_hash(message: &str) -> sha256::Hash {
+    let tag = sha256::Hash::hash(b"BIP0322-signed-message").to_byte_array();
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag);
+    engine.input(&tag);
+    engine.input(message.as_bytes());
+
+    sha256::Hash::from_engine(engine)
+}
+
+/// The virtual transaction that commits to the message and the address
+fn to_spend(message: &str, script_pubkey: ScriptBuf) -> Transaction {
+    let script_sig = Builder::new()
+        .push_opcode(OP_0)
+        .push_slice(message_hash(message).to_byte_array())
+        .into_script();
+
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::all_zeros(),
+                vout: 0xFFFFFFFF,
+            },
+            script_sig,
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey,
+        }],
+    }
+}
+
+/// The virtual transaction spending `to_spend`, its witness is the signature
+fn to_sign(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: to_spend.compute_txid(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+// MARK: FFI
+#[uniffi::export]
+impl Address {
+    /// Verify a BIP-137 or BIP-322 simple signature, works for any address, not just our own
+    pub fn verify_message(&self, message: String, signature: String) -> Result<bool> {
+        verify_message(&message, self, &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr as _;
+
+    use bitcoin::{key::UntweakedPublicKey, Network, PrivateKey};
+
+    use super::*;
+
+    fn secret_key() -> SecretKey {
+        SecretKey::from_slice(&[0x42; 32]).unwrap()
+    }
+
+    fn addresses() -> Vec<BdkAddress> {
+        let secp = Secp256k1::new();
+        let public_key = CompressedPublicKey(secret_key().public_key(&secp));
+        let internal_key = UntweakedPublicKey::from(public_key.0);
+
+        vec![
+            BdkAddress::p2pkh(public_key.pubkey_hash(), Network::Bitcoin),
+            BdkAddress::p2shwpkh(&public_key, Network::Bitcoin),
+            BdkAddress::p2wpkh(&public_key, Network::Bitcoin),
+            BdkAddress::p2tr(&secp, internal_key, None, Network::Bitcoin),
+        ]
+    }
+
+    fn address(address: &str) -> BdkAddress {
+        BdkAddress::from_str(address).unwrap().assume_checked()
+    }
+
+    #[test]
+    fn test_bip322_message_hash() {
+        assert_eq!(
+            message_hash("").to_string(),
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1"
+        );
+
+        assert_eq!(
+            message_hash("Hello World").to_string(),
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify_all_address_types() {
+        for address in addresses() {
+            let signature = sign_message("Hello World", &address, &secret_key()).unwrap();
+
+            assert!(verify_message("Hello World", &address, &signature).unwrap());
+            assert!(!verify_message("Goodbye World", &address, &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_signature_for_a_different_address_fails() {
+        let other = address("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+
+        for address in addresses() {
+            let signature = sign_message("Hello World", &address, &secret_key()).unwrap();
+            assert!(!verify_message("Hello World", &other, &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_sign_with_wrong_key_fails() {
+        let address = addresses().remove(2);
+        let wrong_key = SecretKey::from_slice(&[0x43; 32]).unwrap();
+
+        assert!(sign_message("Hello World", &address, &wrong_key).is_err());
+    }
+
+    #[test]
+    fn test_bip322_official_vectors() {
+        let address = address("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+
+        let empty = "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        let hello_world = "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=";
+        let hello_world_other_nonce = "AkgwRQIhAOzyynlqt93lOKJr+wmmxIens//zPzl9tqIOua93wO6MAiBi5n5EyAcPScOjf1lAqIUIQtr3zKNeavYabHyR8eGhowEhAsfxIAMZZEKUPYWI4BruhAQjzFT8FSFSajuFwrDL1Yhy";
+
+        assert!(verify_message("", &address, empty).unwrap());
+        assert!(verify_message("Hello World", &address, hello_world).unwrap());
+        assert!(verify_message("Hello World", &address, hello_world_other_nonce).unwrap());
+
+        assert!(!verify_message("Hello World", &address, empty).unwrap());
+        assert!(!verify_message("", &address, hello_world).unwrap());
+    }
+
+    #[test]
+    fn test_bip322_sign_with_official_key() {
+        let address = address("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+        let private_key =
+            PrivateKey::from_wif("L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k").unwrap();
+
+        let signature = sign_message("Hello World", &address, &private_key.inner).unwrap();
+        assert!(verify_message("Hello World", &address, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_bip137_known_signature() {
+        // from bitcoin core's signmessagewithprivkey test
+        let address = address("mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB");
+        let signature = "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=";
+
+        assert!(verify_message("This is just a test message", &address, signature).unwrap());
+        assert!(!verify_message("This is just another message", &address, signature).unwrap());
+    }
+}
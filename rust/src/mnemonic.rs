@@ -65,7 +65,19 @@ pub trait MnemonicExt {
         wallet_address_type: WalletAddressType,
     ) -> Descriptors;
 
+    /// Descriptors derived at `account'` instead of account 0, ex: m/84'/0'/1' for account 1
+    fn into_descriptors_for_account(
+        self,
+        passphrase: Option<String>,
+        network: impl Into<crate::network::Network>,
+        wallet_address_type: WalletAddressType,
+        account: u32,
+    ) -> Descriptors;
+
     fn xpub(&self, network: Network) -> Xpub;
+
+    /// The xpub of the seed with the BIP39 passphrase, same as [`MnemonicExt::xpub`] when `None`
+    fn xpub_with_passphrase(&self, network: Network, passphrase: Option<&str>) -> Xpub;
 }
 
 #[derive(Debug, Clone, uniffi::Error, thiserror::Error)]
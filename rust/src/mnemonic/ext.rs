@@ -12,16 +12,55 @@ impl MnemonicExt for bip39::Mnemonic {
         passphrase: Option<String>,
         network: impl Into<crate::network::Network>,
         address_type: WalletAddressType,
+    ) -> Descriptors {
+        self.into_descriptors_for_account(passphrase, network, address_type, 0)
+    }
+
+    fn into_descriptors_for_account(
+        self,
+        passphrase: Option<String>,
+        network: impl Into<crate::network::Network>,
+        address_type: WalletAddressType,
+        account: u32,
     ) -> Descriptors {
         use crate::keys::{Descriptor, DescriptorSecretKey};
 
         let network = network.into();
         let descriptor_secret_key = DescriptorSecretKey::new(network, self, passphrase);
 
+        // change has always been native segwit, kept so existing wallets can still sign for it
+        let change_address_type = match address_type {
+            WalletAddressType::Taproot => WalletAddressType::Taproot,
+            _ => WalletAddressType::NativeSegwit,
+        };
+
+        if account > 0 {
+            let new_descriptor = |address_type, keychain_kind| {
+                Descriptor::new_for_account(
+                    &descriptor_secret_key,
+                    address_type,
+                    keychain_kind,
+                    network,
+                    account,
+                )
+            };
+
+            return Descriptors {
+                external: new_descriptor(address_type, bdk_wallet::KeychainKind::External),
+                internal: new_descriptor(change_address_type, bdk_wallet::KeychainKind::Internal),
+            };
+        }
+
         let new_descriptor = match address_type {
             WalletAddressType::NativeSegwit => Descriptor::new_bip84,
             WalletAddressType::WrappedSegwit => Descriptor::new_bip49,
             WalletAddressType::Legacy => Descriptor::new_bip44,
+            WalletAddressType::Taproot => Descriptor::new_bip86,
+        };
+
+        let new_change_descriptor = match change_address_type {
+            WalletAddressType::Taproot => Descriptor::new_bip86,
+            _ => Descriptor::new_bip84,
         };
 
         let descriptor = new_descriptor(
@@ -30,7 +69,7 @@ impl MnemonicExt for bip39::Mnemonic {
             network,
         );
 
-        let change_descriptor = Descriptor::new_bip84(
+        let change_descriptor = new_change_descriptor(
             &descriptor_secret_key,
             bdk_wallet::KeychainKind::Internal,
             network,
@@ -43,7 +82,11 @@ impl MnemonicExt for bip39::Mnemonic {
     }
 
     fn xpub(&self, network: Network) -> Xpub {
-        let seed = self.to_seed("");
+        self.xpub_with_passphrase(network, None)
+    }
+
+    fn xpub_with_passphrase(&self, network: Network, passphrase: Option<&str>) -> Xpub {
+        let seed = self.to_seed(passphrase.unwrap_or(""));
         let xkey: ExtendedKey = seed
             .into_extended_key()
             .expect("never fail proper mnemonic");
@@ -62,7 +105,22 @@ impl MnemonicExt for Mnemonic {
         self.0.into_descriptors(passphrase, network, address_type)
     }
 
+    fn into_descriptors_for_account(
+        self,
+        passphrase: Option<String>,
+        network: impl Into<crate::network::Network>,
+        address_type: WalletAddressType,
+        account: u32,
+    ) -> Descriptors {
+        self.0
+            .into_descriptors_for_account(passphrase, network, address_type, account)
+    }
+
     fn xpub(&self, network: Network) -> Xpub {
         self.0.xpub(network)
     }
+
+    fn xpub_with_passphrase(&self, network: Network, passphrase: Option<&str>) -> Xpub {
+        self.0.xpub_with_passphrase(network, passphrase)
+    }
 }
@@ -3,10 +3,12 @@ use std::sync::Arc;
 use tracing::{debug, warn};
 
 use crate::{
+    database::Database,
     hardware_export::HardwareExport,
     mnemonic::ParseMnemonic as _,
+    psbt::Psbt,
     transaction::ffi::BitcoinTransaction,
-    wallet::{address::AddressError, AddressWithNetwork},
+    wallet::{address::AddressError, bip21::Bip21Error, AddressWithNetwork},
 };
 
 #[derive(Debug, Clone, uniffi::Enum)]
@@ -21,6 +23,7 @@ pub enum MultiFormat {
     HardwareExport(Arc<HardwareExport>),
     Mnemonic(Arc<crate::mnemonic::Mnemonic>),
     Transaction(Arc<crate::transaction::ffi::BitcoinTransaction>),
+    Psbt(Arc<Psbt>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, uniffi::Error, thiserror::Error)]
@@ -34,8 +37,11 @@ pub enum MultiFormatError {
     #[error("Not a valid format, we only support addresses, SeedQr, mnemonic and XPUBs")]
     UnrecognizedFormat,
 
-    #[error("UR format not supported, please use a plain QR or a BBQr")]
-    UrFormatNotSupported,
+    #[error(transparent)]
+    InvalidUr(#[from] crate::ur::UrError),
+
+    #[error(transparent)]
+    InvalidPaymentRequest(#[from] Bip21Error),
 }
 
 type Result<T, E = MultiFormatError> = std::result::Result<T, E>;
@@ -44,6 +50,11 @@ impl MultiFormat {
     pub fn try_from_data(data: Vec<u8>) -> Result<Self> {
         debug!("MultiFormat::try_from_data");
 
+        // try parsing a binary psbt, ex: from a file or a BBQr of type 'P'
+        if let Ok(psbt) = Psbt::try_from_data(&data) {
+            return Ok(Self::Psbt(Arc::new(psbt)));
+        }
+
         // try parsing a signed transaction
         if let Ok(txn) = BitcoinTransaction::try_from_data(&data) {
             return Ok(Self::Transaction(Arc::new(txn)));
@@ -63,14 +74,23 @@ impl MultiFormat {
 
         let string = string.trim();
 
+        // single part UR, ex: a crypto-account or crypto-psbt
+        if crate::ur::is_ur(string) {
+            let decoded = crate::ur::decode(string)?;
+            return decoded.try_into();
+        }
+
         // try to parse address
-        match AddressWithNetwork::try_new(string) {
+        let network = Database::global().global_config.selected_network();
+        match AddressWithNetwork::try_new_for_network(string, network) {
             Ok(address) => return Ok(Self::Address(address.into())),
 
             Err(AddressError::UnsupportedNetwork) => {
                 return Err(MultiFormatError::UnsupportedNetworkAddress)
             }
 
+            Err(AddressError::InvalidPaymentRequest(error)) => return Err(error.into()),
+
             _ => {}
         }
 
@@ -91,15 +111,16 @@ impl MultiFormat {
             return Ok(Self::Mnemonic(Arc::new(mnemonic.into())));
         }
 
+        // try to parse a base64 or hex psbt
+        if let Ok(psbt) = Psbt::try_from_str(string) {
+            return Ok(Self::Psbt(Arc::new(psbt)));
+        }
+
         // try to parse a transaction
         if let Ok(txn) = BitcoinTransaction::try_from_str(string) {
             return Ok(Self::Transaction(Arc::new(txn)));
         }
 
-        if string.starts_with("UR:") || string.starts_with("ur:") {
-            return Err(MultiFormatError::UrFormatNotSupported);
-        }
-
         warn!("could not parse string as MultiFormat: {string}");
         Err(MultiFormatError::UnrecognizedFormat)
     }
@@ -1,5 +1,7 @@
+use std::time::Duration;
+
 use bdk_chain::bitcoin::params::Params;
-use bdk_wallet::bitcoin;
+use bdk_wallet::{bitcoin, miniscript::descriptor::DescriptorType};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -19,10 +21,21 @@ pub enum Network {
     Bitcoin,
     Testnet,
     Signet,
+    Regtest,
 }
 
 use strum::IntoEnumIterator;
 
+/// Mainnet halving block height and time, the clock is measured from here
+const MAINNET_HEIGHT_ANCHOR: (u32, u64) = (840_000, 1_713_571_767);
+
+/// Blocks are only about 10 minutes apart on average, stay well below the estimate
+const BIRTHDAY_SAFETY_MARGIN: u32 = 2016;
+
+/// Mainnet heights segwit and taproot activated at
+const SEGWIT_ACTIVATION_HEIGHT: u32 = 481_824;
+const TAPROOT_ACTIVATION_HEIGHT: u32 = 709_632;
+
 #[uniffi::export]
 fn network_to_string(network: Network) -> String {
     network.to_string()
@@ -30,7 +43,10 @@ fn network_to_string(network: Network) -> String {
 
 #[uniffi::export]
 fn all_networks() -> Vec<Network> {
-    Network::iter().collect()
+    // regtest is only for local test nodes, keep it out of the picker in release builds
+    Network::iter()
+        .filter(|network| cfg!(debug_assertions) || *network != Network::Regtest)
+        .collect()
 }
 
 impl TryFrom<&str> for Network {
@@ -42,6 +58,7 @@ impl TryFrom<&str> for Network {
             "testnet" | "Testnet" => Ok(Network::Testnet),
             "signet" | "Signet" => Ok(Network::Signet),
             "mutinynet" | "Mutinynet" => Ok(Network::Signet),
+            "regtest" | "Regtest" => Ok(Network::Regtest),
             _ => Err(format!("Unknown network: {}", value)),
         }
     }
@@ -53,6 +70,7 @@ impl From<Network> for bitcoin::Network {
             Network::Bitcoin => bitcoin::Network::Bitcoin,
             Network::Testnet => bitcoin::Network::Testnet,
             Network::Signet => bitcoin::Network::Signet,
+            Network::Regtest => bitcoin::Network::Regtest,
         }
     }
 }
@@ -62,6 +80,8 @@ impl From<bitcoin::Network> for Network {
         match network {
             bitcoin::Network::Bitcoin => Network::Bitcoin,
             bitcoin::Network::Testnet => Network::Testnet,
+            bitcoin::Network::Signet => Network::Signet,
+            bitcoin::Network::Regtest => Network::Regtest,
             network => panic!("unsupported network: {network:?}"),
         }
     }
@@ -73,6 +93,68 @@ impl From<Network> for Params {
             Network::Bitcoin => Params::MAINNET,
             Network::Testnet => Params::TESTNET3,
             Network::Signet => Params::SIGNET,
+            Network::Regtest => Params::REGTEST,
+        }
+    }
+}
+
+impl Network {
+    /// Height a new wallet can start scanning from, estimated from the clock so it works
+    /// before connecting to a node, `None` for test networks where block times are irregular
+    pub fn estimated_birthday_height(&self, now: Duration) -> Option<u32> {
+        if *self != Network::Bitcoin {
+            return None;
+        }
+
+        let (anchor_height, anchor_time) = MAINNET_HEIGHT_ANCHOR;
+        let blocks_since = now.as_secs().saturating_sub(anchor_time) / 600;
+        let height = anchor_height + blocks_since as u32;
+
+        Some(height.saturating_sub(BIRTHDAY_SAFETY_MARGIN))
+    }
+
+    /// First height a wallet with these scripts could have been paid at, full scans of wallets
+    /// without a birthday start here instead of genesis, only known for mainnet
+    pub fn first_usable_height(&self, desc_type: DescriptorType) -> u32 {
+        use DescriptorType as T;
+
+        if *self != Network::Bitcoin {
+            return 0;
+        }
+
+        match desc_type {
+            T::Tr => TAPROOT_ACTIVATION_HEIGHT,
+            T::Wpkh | T::ShWpkh | T::Wsh | T::ShWsh | T::WshSortedMulti | T::ShWshSortedMulti => {
+                SEGWIT_ACTIVATION_HEIGHT
+            }
+            T::Bare | T::Sh | T::Pkh | T::ShSortedMulti => 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_birthday_height() {
+        // 2025-01-01, mainnet was a little past block 877_000
+        let now = Duration::from_secs(1_735_689_600);
+        let height = Network::Bitcoin.estimated_birthday_height(now).unwrap();
+
+        assert!(height < 877_000);
+        assert!(height > 870_000);
+
+        assert_eq!(Network::Testnet.estimated_birthday_height(now), None);
+        assert_eq!(Network::Regtest.estimated_birthday_height(now), None);
+    }
+
+    #[test]
+    fn test_first_usable_height() {
+        let mainnet = Network::Bitcoin;
+        assert_eq!(mainnet.first_usable_height(DescriptorType::Wpkh), 481_824);
+        assert_eq!(mainnet.first_usable_height(DescriptorType::Tr), 709_632);
+        assert_eq!(mainnet.first_usable_height(DescriptorType::Pkh), 0);
+        assert_eq!(Network::Signet.first_usable_height(DescriptorType::Tr), 0);
+    }
+}
@@ -1,8 +1,11 @@
 pub mod client;
+pub mod pool;
 
 use crate::{
     network::Network,
-    node_connect::{NodeSelection, BITCOIN_ELECTRUM, SIGNET_ESPLORA, TESTNET_ESPLORA},
+    node_connect::{
+        NodeSelection, BITCOIN_ELECTRUM, REGTEST_ELECTRUM, SIGNET_ESPLORA, TESTNET_ESPLORA,
+    },
 };
 
 use client::NodeClient;
@@ -24,6 +27,8 @@ pub enum ApiType {
     Esplora,
     Electrum,
     Rpc,
+    /// Compact block filters (BIP157/158) from a bitcoin peer
+    Cbf,
 }
 
 #[derive(
@@ -74,6 +79,16 @@ impl Node {
                     url: url.to_string(),
                 }
             }
+
+            Network::Regtest => {
+                let (name, url) = REGTEST_ELECTRUM;
+                Self {
+                    name: name.to_string(),
+                    network,
+                    api_type: ApiType::Electrum,
+                    url: url.to_string(),
+                }
+            }
         }
     }
 
@@ -95,6 +110,27 @@ impl Node {
         }
     }
 
+    /// Bitcoin Core node, auth is passed in the url, see [`client::rpc::RpcClient`]
+    pub fn new_rpc(name: String, url: String, network: Network) -> Self {
+        Self {
+            name,
+            network,
+            api_type: ApiType::Rpc,
+            url,
+        }
+    }
+
+    /// Bitcoin peer serving compact block filters, the url is its `host:port`, see
+    /// [`client::cbf::CbfClient`]
+    pub fn new_cbf(name: String, url: String, network: Network) -> Self {
+        Self {
+            name,
+            network,
+            api_type: ApiType::Cbf,
+            url,
+        }
+    }
+
     pub async fn check_url(&self) -> Result<(), Error> {
         let client = NodeClient::new(self).await?;
         client.check_url().await?;
@@ -1,6 +1,9 @@
+pub mod cbf;
 pub mod electrum;
 pub mod esplora;
+pub mod rpc;
 
+use bdk_bitcoind_rpc::bitcoincore_rpc;
 use bdk_chain::{
     bitcoin::Address,
     spk_client::{SyncRequest, SyncResponse},
@@ -17,11 +20,14 @@ use bdk_wallet::{
 use bitcoin::{Transaction, Txid};
 use tracing::debug;
 
-use crate::node::Node;
+use crate::{
+    node::Node,
+    wallet::metadata::{ScanSettings, DEFAULT_GAP_LIMIT},
+};
 
 use super::ApiType;
 
-const STOP_GAP: usize = 25;
+const STOP_GAP: usize = DEFAULT_GAP_LIMIT as usize;
 const ELECTRUM_BATCH_SIZE: usize = 10;
 const ESPLORA_BATCH_SIZE: usize = 1;
 
@@ -29,6 +35,8 @@ const ESPLORA_BATCH_SIZE: usize = 1;
 pub enum NodeClient {
     Esplora(self::esplora::EsploraClient),
     Electrum(self::electrum::ElectrumClient),
+    Rpc(self::rpc::RpcClient),
+    Cbf(self::cbf::CbfClient),
 }
 
 impl core::fmt::Debug for NodeClient {
@@ -36,6 +44,8 @@ impl core::fmt::Debug for NodeClient {
         match self {
             NodeClient::Esplora(_) => write!(f, "Esplora"),
             NodeClient::Electrum(_) => write!(f, "Electrum"),
+            NodeClient::Rpc(_) => write!(f, "Rpc"),
+            NodeClient::Cbf(_) => write!(f, "Cbf"),
         }
     }
 }
@@ -71,12 +81,89 @@ pub enum Error {
 
     #[error("failed to broadcast transaction: {0}")]
     ElectrumBroadcast(electrum_client::Error),
+
+    #[error("failed to read node notifications: {0}")]
+    ElectrumSubscribe(electrum_client::Error),
+
+    #[error("invalid rpc node url: {0}")]
+    InvalidRpcUrl(String),
+
+    #[error("failed to create node client: {0}")]
+    CreateRpcClient(bitcoincore_rpc::Error),
+
+    #[error("bitcoin core rpc nodes can't connect through a proxy, turn off the proxy or use an electrum, esplora or compact filter node")]
+    RpcWithProxy,
+
+    #[error("failed to connect to node: {0}")]
+    RpcConnect(bitcoincore_rpc::Error),
+
+    #[error("failed to complete wallet scan: {0}")]
+    RpcScan(bitcoincore_rpc::Error),
+
+    #[error("failed to get a address: {0}")]
+    RpcAddress(bitcoincore_rpc::Error),
+
+    #[error("failed to broadcast transaction: {0}")]
+    RpcBroadcast(bitcoincore_rpc::Error),
+
+    #[error("invalid compact block filter peer: {0}")]
+    InvalidCbfPeer(String),
+
+    #[error("failed to connect to node: {0}")]
+    CbfConnect(cbf::CbfError),
+
+    #[error("failed to complete wallet scan: {0}")]
+    CbfScan(cbf::CbfError),
+
+    #[error("failed to get a address: {0}")]
+    CbfAddress(cbf::CbfError),
+
+    #[error("failed to broadcast transaction: {0}")]
+    CbfBroadcast(cbf::CbfError),
+}
+
+impl Error {
+    /// The node received the transaction and refused it, ex: already spent inputs or a fee too
+    /// low for its mempool, retrying or asking another node won't change the answer
+    pub fn is_broadcast_rejected(&self) -> bool {
+        use bitcoincore_rpc::jsonrpc;
+
+        match self {
+            Error::ElectrumBroadcast(electrum_client::Error::Protocol(_)) => true,
+            Error::EsploraBroadcast(esplora_client::Error::HttpResponse { status, .. }) => {
+                *status == 400
+            }
+            Error::RpcBroadcast(bitcoincore_rpc::Error::JsonRpc(jsonrpc::Error::Rpc(_))) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct NodeClientOptions {
     pub batch_size: usize,
     pub stop_gap: usize,
+    /// Height full scans start from on nodes without an address index (rpc and compact filters)
+    pub start_height: u32,
+}
+
+impl NodeClientOptions {
+    /// Options for scanning a wallet with its own settings, falls back to the node type defaults
+    pub fn new(api_type: ApiType, settings: ScanSettings) -> Self {
+        let default_batch_size = match api_type {
+            ApiType::Esplora => ESPLORA_BATCH_SIZE,
+            ApiType::Electrum => ELECTRUM_BATCH_SIZE,
+            ApiType::Rpc | ApiType::Cbf => 1,
+        };
+
+        Self {
+            batch_size: settings
+                .batch_size
+                .map_or(default_batch_size, |batch_size| batch_size as usize),
+            stop_gap: settings.gap_limit as usize,
+            start_height: settings.birthday_height.unwrap_or(0),
+        }
+    }
 }
 
 impl NodeClient {
@@ -93,8 +180,13 @@ impl NodeClient {
             }
 
             ApiType::Rpc => {
-                // TODO: implement rpc check, with auth
-                todo!()
+                let client = rpc::RpcClient::new_from_node(node)?;
+                Ok(Self::Rpc(client))
+            }
+
+            ApiType::Cbf => {
+                let client = cbf::CbfClient::new_from_node(node)?;
+                Ok(Self::Cbf(client))
             }
         }
     }
@@ -112,8 +204,13 @@ impl NodeClient {
             }
 
             ApiType::Rpc => {
-                // TODO: implement rpc check, with auth
-                todo!()
+                let client = rpc::RpcClient::new_from_node_and_options(node, options)?;
+                Ok(Self::Rpc(client))
+            }
+
+            ApiType::Cbf => {
+                let client = cbf::CbfClient::new_from_node_and_options(node, options)?;
+                Ok(Self::Cbf(client))
             }
         }
     }
@@ -127,6 +224,14 @@ impl NodeClient {
             NodeClient::Electrum(client) => {
                 client.get_height().await?;
             }
+
+            NodeClient::Rpc(client) => {
+                client.get_height().await?;
+            }
+
+            NodeClient::Cbf(client) => {
+                client.get_height().await?;
+            }
         }
 
         Ok(())
@@ -143,6 +248,16 @@ impl NodeClient {
                 let height = client.get_height().await?;
                 Ok(height)
             }
+
+            NodeClient::Rpc(client) => {
+                let height = client.get_height().await?;
+                Ok(height)
+            }
+
+            NodeClient::Cbf(client) => {
+                let height = client.get_height().await?;
+                Ok(height)
+            }
         }
     }
 
@@ -161,6 +276,16 @@ impl NodeClient {
                 debug!("starting electrum full scan");
                 client.full_scan(full_scan_request, tx_graph).await?
             }
+
+            NodeClient::Rpc(client) => {
+                debug!("starting rpc full scan");
+                client.full_scan(full_scan_request, tx_graph).await?
+            }
+
+            NodeClient::Cbf(client) => {
+                debug!("starting compact block filter full scan");
+                client.full_scan(full_scan_request, tx_graph).await?
+            }
         };
 
         Ok(full_scan_result)
@@ -174,6 +299,8 @@ impl NodeClient {
         let scan_result = match self {
             NodeClient::Esplora(client) => client.sync(scan_request).await?,
             NodeClient::Electrum(client) => client.sync(scan_request, tx_graph).await?,
+            NodeClient::Rpc(client) => client.sync(scan_request, tx_graph).await?,
+            NodeClient::Cbf(client) => client.sync(scan_request, tx_graph).await?,
         };
 
         Ok(scan_result)
@@ -190,6 +317,16 @@ impl NodeClient {
                 let address = client.check_address_for_txn(address).await?;
                 Ok(address)
             }
+
+            NodeClient::Rpc(client) => {
+                let address = client.check_address_for_txn(address).await?;
+                Ok(address)
+            }
+
+            NodeClient::Cbf(client) => {
+                let address = client.check_address_for_txn(address).await?;
+                Ok(address)
+            }
         }
     }
 
@@ -197,6 +334,8 @@ impl NodeClient {
         match self {
             NodeClient::Esplora(client) => client.broadcast_transaction(txn).await,
             NodeClient::Electrum(client) => client.broadcast_transaction(txn).await,
+            NodeClient::Rpc(client) => client.broadcast_transaction(txn).await,
+            NodeClient::Cbf(client) => client.broadcast_transaction(txn).await,
         }
     }
 }
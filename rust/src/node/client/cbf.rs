@@ -0,0 +1,1046 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::{self, BufReader, Write as _},
+    net::{SocketAddr, TcpStream, ToSocketAddrs as _},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use bdk_chain::{
+    bitcoin::{
+        bip158::{BlockFilter, FilterHash, FilterHeader},
+        block::Header,
+        Address, Block, BlockHash,
+    },
+    local_chain::CheckPoint,
+    BlockId, ConfirmationBlockTime, TxGraph, TxUpdate,
+};
+use bdk_core::spk_client::{FullScanRequest, FullScanResponse, SyncRequest, SyncResponse};
+use bdk_wallet::KeychainKind;
+use bitcoin::{
+    consensus::{encode, Decodable as _},
+    hashes::Hash as _,
+    p2p::{
+        self,
+        message::{NetworkMessage, RawNetworkMessage},
+        message_blockdata::{GetHeadersMessage, Inventory},
+        message_filter::{CFHeaders, GetCFCheckpt, GetCFHeaders, GetCFilters},
+        message_network::VersionMessage,
+        Magic, ServiceFlags,
+    },
+    ScriptBuf, Transaction, Txid,
+};
+use parking_lot::Mutex;
+use tracing::debug;
+use url::Url;
+
+use super::{
+    rpc::{apply_block, WatchedScripts},
+    Error, NodeClientOptions, STOP_GAP,
+};
+use crate::{network::Network, node::Node, proxy::ProxySettings};
+
+/// The basic filter type from BIP158, the only one peers serve
+const BASIC_FILTER: u8 = 0;
+
+/// Most filters a peer sends for a single `getcfilters` request
+const MAX_FILTERS_PER_REQUEST: u32 = 1000;
+
+/// Blocks between the filter headers a peer sends for `getcfcheckpt`
+const FILTER_CHECKPOINT_INTERVAL: u32 = 1000;
+
+/// Most filter bytes a client keeps in memory, filters past this are downloaded again when a
+/// later pass or address check needs them
+const MAX_CACHED_FILTER_BYTES: usize = 128 * 1024 * 1024;
+
+/// Most headers a peer sends for a single `getheaders` request
+const MAX_HEADERS_PER_REQUEST: usize = 2000;
+
+/// Peers disconnect if a locator has more hashes than this
+const MAX_LOCATOR_HASHES: usize = 101;
+
+/// Lowest protocol version with the BIP157 messages
+const PROTOCOL_VERSION: u32 = 70016;
+
+const USER_AGENT: &str = "/Cove/";
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum CbfError {
+    #[error("peer connection failed: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid message from peer: {0}")]
+    Decode(#[from] encode::Error),
+
+    #[error("peer is on a different network")]
+    WrongNetwork,
+
+    #[error("peer does not serve compact block filters, start bitcoind with -peerblockfilters")]
+    NoCompactFilters,
+
+    #[error("peer sent headers that do not connect to our chain")]
+    InvalidHeaders,
+
+    #[error("peer sent an invalid filter for block {0}")]
+    InvalidFilter(BlockHash),
+
+    #[error("peer sent filter headers that do not match its checkpoints")]
+    InvalidFilterHeaders,
+
+    #[error("peer sent an invalid block {0}")]
+    InvalidBlock(BlockHash),
+
+    #[error("peer does not have block {0}")]
+    BlockNotFound(BlockHash),
+}
+
+/// Light client that syncs from a single bitcoin peer using compact block filters (BIP157/158)
+///
+/// Only the filters, and the blocks whose filters match the wallet's scripts, are downloaded,
+/// so the peer never learns which addresses belong to the wallet. The node url is the peer's
+/// `host:port`, ex: a local bitcoind started with `-blockfilterindex -peerblockfilters`
+///
+/// Peers can't be asked about the mempool without revealing the wallet's scripts, so incoming
+/// transactions show up once they are confirmed
+///
+/// The peer is trusted: headers are checked for proof of work and filters against the filter
+/// headers and checkpoints the same peer sends. That catches corrupted filters, not a peer that
+/// lies about all of them to hide transactions, so connect to a node you run
+#[derive(Debug, Clone)]
+pub struct CbfClient {
+    host: String,
+    port: u16,
+    network: Network,
+    options: NodeClientOptions,
+    session: Arc<Mutex<Session>>,
+}
+
+/// Headers and checked filters this client already downloaded, shared by its clones so later
+/// scans and address checks only download what is new
+#[derive(Debug, Default)]
+struct Session {
+    /// Headers from genesis to the peer's tip when it was last synced
+    headers: Option<HeaderChain>,
+    filters: HashMap<BlockHash, Vec<u8>>,
+    filter_bytes: usize,
+}
+
+impl Session {
+    fn cache_filter(&mut self, block_hash: BlockHash, filter: &[u8]) {
+        if self.filter_bytes + filter.len() > MAX_CACHED_FILTER_BYTES {
+            return;
+        }
+
+        self.filter_bytes += filter.len();
+        self.filters.insert(block_hash, filter.to_vec());
+    }
+}
+
+impl CbfClient {
+    pub fn new_from_node(node: &Node) -> Result<Self, Error> {
+        Self::new_from_node_and_options(node, Self::default_options())
+    }
+
+    pub fn new_from_node_and_options(
+        node: &Node,
+        options: NodeClientOptions,
+    ) -> Result<Self, Error> {
+        let (host, port) = parse_peer_address(&node.url, node.network)?;
+
+        Ok(Self {
+            host,
+            port,
+            network: node.network,
+            options,
+            session: Arc::default(),
+        })
+    }
+
+    /// The peer's height when we connected
+    pub async fn get_height(&self) -> Result<usize, Error> {
+        let client = self.clone();
+        let height = crate::unblock::run_blocking(move || -> Result<_, CbfError> {
+            let peer = client.connect()?;
+            Ok(peer.start_height.max(0) as usize)
+        })
+        .await
+        .map_err(Error::CbfConnect)?;
+
+        Ok(height)
+    }
+
+    pub async fn full_scan(
+        &self,
+        mut request: FullScanRequest<KeychainKind>,
+        tx_graph: &TxGraph<ConfirmationBlockTime>,
+    ) -> Result<FullScanResponse<KeychainKind>, Error> {
+        let client = self.clone();
+        let tx_graph = tx_graph.clone();
+        let stop_gap = self.options.stop_gap as u32;
+        let birthday = self.options.start_height;
+
+        crate::unblock::run_blocking(move || -> Result<_, CbfError> {
+            let mut spks = WatchedScripts::default();
+            for keychain in request.keychains() {
+                spks.derive(&mut request, keychain, stop_gap + 1);
+            }
+            spks.track_outputs_from_graph(&tx_graph);
+
+            // full scans look at the whole history since the wallet's birthday, not just the
+            // blocks after the wallet's tip, so rescans find older transactions
+            let mut peer = client.connect()?;
+            let (tx_update, chain_update) =
+                peer.with_headers_from_genesis(client.genesis_checkpoint(), |peer, chain| {
+                    peer.scan(chain, birthday, &mut spks, |spks| {
+                        spks.derive_ahead(&mut request, stop_gap)
+                    })
+                })?;
+
+            Ok(FullScanResponse {
+                tx_update,
+                chain_update: Some(chain_update),
+                last_active_indices: spks.last_active,
+            })
+        })
+        .await
+        .map_err(Error::CbfScan)
+    }
+
+    pub async fn sync(
+        &self,
+        mut request: SyncRequest<(KeychainKind, u32)>,
+        tx_graph: &TxGraph<ConfirmationBlockTime>,
+    ) -> Result<SyncResponse, Error> {
+        let client = self.clone();
+        let tx_graph = tx_graph.clone();
+
+        crate::unblock::run_blocking(move || -> Result<_, CbfError> {
+            let mut spks = WatchedScripts::default();
+            for spk in request.iter_spks() {
+                spks.scripts.insert(spk, None);
+            }
+            spks.track_outputs_from_graph(&tx_graph);
+
+            let chain_tip = request
+                .chain_tip()
+                .unwrap_or_else(|| client.genesis_checkpoint());
+
+            let mut peer = client.connect()?;
+            let chain = peer.sync_headers(&chain_tip)?;
+            let (tx_update, chain_update) = peer.scan(&chain, 0, &mut spks, |_| {})?;
+
+            Ok(SyncResponse {
+                tx_update,
+                chain_update: Some(chain_update),
+            })
+        })
+        .await
+        .map_err(Error::CbfScan)
+    }
+
+    /// Checks the filter of every block from the start height, the first check downloads them
+    /// and the ones after reuse them, slow on mainnet without a start height
+    pub async fn check_address_for_txn(&self, address: Address) -> Result<bool, Error> {
+        let client = self.clone();
+        let start_height = self.options.start_height;
+
+        crate::unblock::run_blocking(move || -> Result<_, CbfError> {
+            let script = address.script_pubkey();
+
+            let mut peer = client.connect()?;
+            peer.with_headers_from_genesis(client.genesis_checkpoint(), |peer, chain| {
+                let scripts = std::slice::from_ref(&script);
+                for height in peer.matching_blocks(chain, start_height, scripts)? {
+                    let hash = chain.hash_at(height).expect("height is in the chain");
+                    let block = peer.block(hash)?;
+
+                    // filters have false positives, make sure the block pays the address
+                    let pays_address = block
+                        .txdata
+                        .iter()
+                        .flat_map(|tx| &tx.output)
+                        .any(|output| output.script_pubkey == script);
+
+                    if pays_address {
+                        return Ok(true);
+                    }
+                }
+
+                Ok(false)
+            })
+        })
+        .await
+        .map_err(Error::CbfAddress)
+    }
+
+    pub async fn broadcast_transaction(&self, txn: Transaction) -> Result<Txid, Error> {
+        let client = self.clone();
+        crate::unblock::run_blocking(move || -> Result<_, CbfError> {
+            let txid = txn.compute_txid();
+
+            let mut peer = client.connect()?;
+            peer.send(NetworkMessage::Tx(txn))?;
+
+            // messages are handled in order, so the transaction was processed once the peer pongs
+            peer.ping()?;
+
+            Ok(txid)
+        })
+        .await
+        .map_err(Error::CbfBroadcast)
+    }
+
+    fn connect(&self) -> Result<Peer, CbfError> {
+        Peer::connect(&self.host, self.port, self.network, self.session.clone())
+    }
+
+    fn genesis_checkpoint(&self) -> CheckPoint {
+        let network: bitcoin::Network = self.network.into();
+        let genesis_hash = bitcoin::constants::genesis_block(network).block_hash();
+
+        CheckPoint::new(BlockId {
+            height: 0,
+            hash: genesis_hash,
+        })
+    }
+
+    fn default_options() -> NodeClientOptions {
+        NodeClientOptions {
+            // filters and blocks are fetched in the batches the protocol allows
+            batch_size: 1,
+            stop_gap: STOP_GAP,
+            start_height: 0,
+        }
+    }
+}
+
+/// A connection to a peer that serves compact block filters, after the version handshake
+struct Peer {
+    reader: bitcoin::io::FromStd<BufReader<TcpStream>>,
+    writer: TcpStream,
+    magic: Magic,
+    /// The peer's height when we connected
+    start_height: i32,
+    session: Arc<Mutex<Session>>,
+}
+
+impl Peer {
+    fn connect(
+        host: &str,
+        port: u16,
+        network: Network,
+        session: Arc<Mutex<Session>>,
+    ) -> Result<Self, CbfError> {
+        let stream = match ProxySettings::current() {
+            Some(proxy) => proxy.connect(host, port)?,
+            None => connect_direct(host, port)?,
+        };
+
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let network: bitcoin::Network = network.into();
+        let mut peer = Self {
+            reader: bitcoin::io::FromStd::new(BufReader::new(stream.try_clone()?)),
+            writer: stream,
+            magic: network.magic(),
+            start_height: 0,
+            session,
+        };
+
+        peer.handshake()?;
+        Ok(peer)
+    }
+
+    fn handshake(&mut self) -> Result<(), CbfError> {
+        // we don't accept connections, so there is no address to share
+        let address = p2p::Address::new(&SocketAddr::from(([0, 0, 0, 0], 0)), ServiceFlags::NONE);
+        let timestamp = UNIX_EPOCH.elapsed().map_or(0, |now| now.as_secs() as i64);
+
+        let mut version = VersionMessage::new(
+            ServiceFlags::NONE,
+            timestamp,
+            address.clone(),
+            address,
+            rand::random(),
+            USER_AGENT.to_string(),
+            0,
+        );
+        version.version = PROTOCOL_VERSION;
+
+        self.send(NetworkMessage::Version(version))?;
+
+        let (mut has_version, mut has_verack) = (false, false);
+        while !(has_version && has_verack) {
+            match self.receive()? {
+                NetworkMessage::Version(version) => {
+                    if !version.services.has(ServiceFlags::COMPACT_FILTERS) {
+                        return Err(CbfError::NoCompactFilters);
+                    }
+
+                    self.start_height = version.start_height;
+                    self.send(NetworkMessage::Verack)?;
+                    has_version = true;
+                }
+                NetworkMessage::Verack => has_verack = true,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the wallet's transactions in the blocks of `chain` from `start_height`
+    ///
+    /// Filters are checked again for the scripts `derive_more` adds, because they could have
+    /// been used in blocks that were already checked, until no new scripts are added. The
+    /// filters are kept in the session, so each pass after the first only checks them again
+    fn scan(
+        &mut self,
+        chain: &HeaderChain,
+        start_height: u32,
+        spks: &mut WatchedScripts,
+        mut derive_more: impl FnMut(&mut WatchedScripts),
+    ) -> Result<(TxUpdate<ConfirmationBlockTime>, CheckPoint), CbfError> {
+        let mut tx_update = TxUpdate::default();
+        let mut scanned = HashSet::new();
+        let mut matched = BTreeSet::new();
+
+        loop {
+            let new_scripts: Vec<ScriptBuf> = spks
+                .scripts
+                .keys()
+                .filter(|script| !scanned.contains(*script))
+                .cloned()
+                .collect();
+
+            if new_scripts.is_empty() {
+                break;
+            }
+
+            for height in self.matching_blocks(chain, start_height, &new_scripts)? {
+                let hash = chain.hash_at(height).expect("height is in the chain");
+                let block = self.block(hash)?;
+
+                apply_block(&mut tx_update, &block, BlockId { height, hash }, spks);
+                matched.insert(height);
+            }
+
+            scanned.extend(new_scripts);
+            derive_more(spks);
+        }
+
+        // a block can match again in a later pass, keep each transaction once
+        let mut txids = HashSet::new();
+        tx_update.txs.retain(|tx| txids.insert(tx.compute_txid()));
+
+        // the matched blocks anchor the transactions, so they have to be in the chain update
+        let mut heights = matched;
+        heights.insert(chain.tip().height);
+        heights.remove(&chain.base.height());
+
+        let block_ids = heights.into_iter().map(|height| BlockId {
+            height,
+            hash: chain.hash_at(height).expect("height is in the chain"),
+        });
+
+        let chain_update = chain
+            .base
+            .clone()
+            .extend(block_ids)
+            .expect("heights are increasing and above the base");
+
+        debug!("cbf scan reached height {}", chain_update.height());
+
+        Ok((tx_update, chain_update))
+    }
+
+    /// Headers from the newest checkpoint the peer agrees with, to the peer's tip
+    fn sync_headers(&mut self, chain_tip: &CheckPoint) -> Result<HeaderChain, CbfError> {
+        let mut chain = HeaderChain::new(chain_tip.clone());
+        self.extend_headers(&mut chain)?;
+        Ok(chain)
+    }
+
+    /// Run `scan` with the headers from genesis to the peer's tip, the headers are kept in the
+    /// session so the next call only downloads the new ones
+    fn with_headers_from_genesis<T>(
+        &mut self,
+        genesis: CheckPoint,
+        scan: impl FnOnce(&mut Self, &HeaderChain) -> Result<T, CbfError>,
+    ) -> Result<T, CbfError> {
+        let cached = self.session.lock().headers.take();
+        let mut chain = cached.unwrap_or_else(|| HeaderChain::new(genesis));
+        self.extend_headers(&mut chain)?;
+
+        let result = scan(self, &chain);
+        self.session.lock().headers = Some(chain);
+
+        result
+    }
+
+    /// Add the peer's headers after our tip, dropping our blocks the peer no longer has
+    fn extend_headers(&mut self, chain: &mut HeaderChain) -> Result<(), CbfError> {
+        let mut headers = self.get_headers(chain.locator())?;
+
+        // the peer has nothing newer than our tip
+        let Some(first) = headers.first() else {
+            return Ok(());
+        };
+
+        chain.truncate_to(first.prev_blockhash)?;
+        loop {
+            for header in &headers {
+                chain.push(header)?;
+            }
+
+            if headers.len() < MAX_HEADERS_PER_REQUEST {
+                return Ok(());
+            }
+
+            headers = self.get_headers(vec![chain.tip().hash])?;
+        }
+    }
+
+    fn get_headers(&mut self, locator: Vec<BlockHash>) -> Result<Vec<Header>, CbfError> {
+        let request = GetHeadersMessage::new(locator, BlockHash::all_zeros());
+        self.send(NetworkMessage::GetHeaders(request))?;
+
+        loop {
+            if let NetworkMessage::Headers(headers) = self.receive()? {
+                return Ok(headers);
+            }
+        }
+    }
+
+    /// Heights of the blocks from `start_height`, and after the chain's base, whose filters
+    /// match any of the scripts
+    fn matching_blocks(
+        &mut self,
+        chain: &HeaderChain,
+        start_height: u32,
+        scripts: &[ScriptBuf],
+    ) -> Result<Vec<u32>, CbfError> {
+        let mut matches = vec![];
+        let tip = chain.tip().height;
+        let mut start = start_height.max(chain.base.height() + 1);
+        let mut checkpoints = None;
+
+        while start <= tip {
+            let stop = tip.min(start + MAX_FILTERS_PER_REQUEST - 1);
+
+            let filters = match self.cached_filters(chain, start, stop) {
+                Some(filters) => filters,
+                None => {
+                    if checkpoints.is_none() {
+                        checkpoints = Some(self.filter_checkpoints(chain)?);
+                    }
+
+                    let checkpoints = checkpoints.as_deref().unwrap_or_default();
+                    self.download_filters(chain, start, stop, checkpoints)?
+                }
+            };
+
+            for (height, filter) in (start..=stop).zip(&filters) {
+                let block_hash = chain.hash_at(height).expect("height is in the chain");
+
+                let scripts = scripts.iter().map(|script| script.as_bytes());
+                let is_match = BlockFilter::new(filter)
+                    .match_any(&block_hash, scripts)
+                    .map_err(|_| CbfError::InvalidFilter(block_hash))?;
+
+                if is_match {
+                    matches.push(height);
+                }
+            }
+
+            start = stop + 1;
+        }
+
+        debug!("{} of the filters up to {tip} matched", matches.len());
+        Ok(matches)
+    }
+
+    /// The filters for `start..=stop` from the session, if it has all of them
+    fn cached_filters(&self, chain: &HeaderChain, start: u32, stop: u32) -> Option<Vec<Vec<u8>>> {
+        let session = self.session.lock();
+
+        (start..=stop)
+            .map(|height| {
+                let block_hash = chain.hash_at(height).expect("height is in the chain");
+                session.filters.get(&block_hash).cloned()
+            })
+            .collect()
+    }
+
+    /// Filters for `start..=stop`, each one checked against the filter headers
+    fn download_filters(
+        &mut self,
+        chain: &HeaderChain,
+        start: u32,
+        stop: u32,
+        checkpoints: &[FilterHeader],
+    ) -> Result<Vec<Vec<u8>>, CbfError> {
+        let stop = BlockId {
+            height: stop,
+            hash: chain.hash_at(stop).expect("stop is in the chain"),
+        };
+
+        let request = GetCFHeaders {
+            filter_type: BASIC_FILTER,
+            start_height: start,
+            stop_hash: stop.hash,
+        };
+
+        self.send(NetworkMessage::GetCFHeaders(request))?;
+        let headers = loop {
+            if let NetworkMessage::CFHeaders(headers) = self.receive()? {
+                break headers;
+            }
+        };
+
+        check_filter_headers(&headers, start, stop, checkpoints)?;
+
+        let request = GetCFilters {
+            filter_type: BASIC_FILTER,
+            start_height: start,
+            stop_hash: stop.hash,
+        };
+
+        self.send(NetworkMessage::GetCFilters(request))?;
+
+        // one message per block, in order
+        let mut filters = Vec::with_capacity(headers.filter_hashes.len());
+        for (height, filter_hash) in (start..=stop.height).zip(&headers.filter_hashes) {
+            let filter = loop {
+                if let NetworkMessage::CFilter(filter) = self.receive()? {
+                    break filter;
+                }
+            };
+
+            let block_hash = chain.hash_at(height).expect("height is in the chain");
+            if filter.block_hash != block_hash {
+                return Err(CbfError::InvalidFilter(filter.block_hash));
+            }
+
+            if FilterHash::hash(&filter.filter) != *filter_hash {
+                return Err(CbfError::InvalidFilter(block_hash));
+            }
+
+            self.session.lock().cache_filter(block_hash, &filter.filter);
+            filters.push(filter.filter);
+        }
+
+        Ok(filters)
+    }
+
+    /// The peer's filter header for every 1000th block up to the chain's tip
+    fn filter_checkpoints(&mut self, chain: &HeaderChain) -> Result<Vec<FilterHeader>, CbfError> {
+        let tip = chain.tip();
+        let request = GetCFCheckpt {
+            filter_type: BASIC_FILTER,
+            stop_hash: tip.hash,
+        };
+
+        self.send(NetworkMessage::GetCFCheckpt(request))?;
+        let checkpoint = loop {
+            if let NetworkMessage::CFCheckpt(checkpoint) = self.receive()? {
+                break checkpoint;
+            }
+        };
+
+        let expected = tip.height / FILTER_CHECKPOINT_INTERVAL;
+        if checkpoint.stop_hash != tip.hash || checkpoint.filter_headers.len() != expected as usize
+        {
+            return Err(CbfError::InvalidFilterHeaders);
+        }
+
+        Ok(checkpoint.filter_headers)
+    }
+
+    fn block(&mut self, hash: BlockHash) -> Result<Block, CbfError> {
+        self.send(NetworkMessage::GetData(vec![Inventory::WitnessBlock(hash)]))?;
+
+        loop {
+            match self.receive()? {
+                NetworkMessage::Block(block) if block.block_hash() == hash => {
+                    if !block.check_merkle_root() || !block.check_witness_commitment() {
+                        return Err(CbfError::InvalidBlock(hash));
+                    }
+
+                    return Ok(block);
+                }
+                NetworkMessage::NotFound(_) => return Err(CbfError::BlockNotFound(hash)),
+                _ => {}
+            }
+        }
+    }
+
+    fn ping(&mut self) -> Result<(), CbfError> {
+        let nonce = rand::random();
+        self.send(NetworkMessage::Ping(nonce))?;
+
+        loop {
+            if let NetworkMessage::Pong(pong) = self.receive()? {
+                if pong == nonce {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, message: NetworkMessage) -> Result<(), CbfError> {
+        let message = RawNetworkMessage::new(self.magic, message);
+        self.writer.write_all(&encode::serialize(&message))?;
+        Ok(())
+    }
+
+    /// Next message from the peer, pings are answered here
+    fn receive(&mut self) -> Result<NetworkMessage, CbfError> {
+        loop {
+            let message = RawNetworkMessage::consensus_decode(&mut self.reader)?;
+            if *message.magic() != self.magic {
+                return Err(CbfError::WrongNetwork);
+            }
+
+            match message.into_payload() {
+                NetworkMessage::Ping(nonce) => self.send(NetworkMessage::Pong(nonce))?,
+                message => return Ok(message),
+            }
+        }
+    }
+}
+
+/// Block hashes from a checkpoint the peer agrees with to the peer's tip
+#[derive(Debug)]
+struct HeaderChain {
+    base: CheckPoint,
+    /// Hashes of the blocks after `base`, in order
+    hashes: Vec<BlockHash>,
+}
+
+impl HeaderChain {
+    fn new(base: CheckPoint) -> Self {
+        Self {
+            base,
+            hashes: vec![],
+        }
+    }
+
+    fn tip(&self) -> BlockId {
+        BlockId {
+            height: self.base.height() + self.hashes.len() as u32,
+            hash: self.hashes.last().copied().unwrap_or(self.base.hash()),
+        }
+    }
+
+    fn hash_at(&self, height: u32) -> Option<BlockHash> {
+        if height == self.base.height() {
+            return Some(self.base.hash());
+        }
+
+        let index = height.checked_sub(self.base.height() + 1)?;
+        self.hashes.get(index as usize).copied()
+    }
+
+    /// Our newest blocks first, then exponentially further apart, ending with the oldest
+    fn locator(&self) -> Vec<BlockHash> {
+        let newest = self.hashes.iter().rev().copied();
+        locator(newest.chain(self.base.iter().map(|checkpoint| checkpoint.hash())))
+    }
+
+    /// Drop the blocks after `hash`, where the peer's chain continues from
+    fn truncate_to(&mut self, hash: BlockHash) -> Result<(), CbfError> {
+        let position = self.hashes.iter().rposition(|block| *block == hash);
+        if let Some(index) = position {
+            self.hashes.truncate(index + 1);
+            return Ok(());
+        }
+
+        self.base = self
+            .base
+            .iter()
+            .find(|checkpoint| checkpoint.hash() == hash)
+            .ok_or(CbfError::InvalidHeaders)?;
+
+        self.hashes.clear();
+        Ok(())
+    }
+
+    /// Headers must connect to the tip and have valid proof of work, the target isn't checked
+    /// against the difficulty adjustments, the peer is trusted to be on the best chain
+    fn push(&mut self, header: &Header) -> Result<(), CbfError> {
+        if header.prev_blockhash != self.tip().hash {
+            return Err(CbfError::InvalidHeaders);
+        }
+
+        let hash = header
+            .validate_pow(header.target())
+            .map_err(|_| CbfError::InvalidHeaders)?;
+
+        self.hashes.push(hash);
+        Ok(())
+    }
+}
+
+/// The first ten of the hashes, newest first, then exponentially further apart, ending with
+/// the oldest
+fn locator(hashes: impl Iterator<Item = BlockHash>) -> Vec<BlockHash> {
+    let mut locator = vec![];
+    let mut oldest = None;
+    let (mut next, mut step) = (0, 1);
+
+    for (index, hash) in hashes.enumerate() {
+        oldest = Some(hash);
+        if index != next || locator.len() >= MAX_LOCATOR_HASHES - 1 {
+            continue;
+        }
+
+        locator.push(hash);
+        if locator.len() >= 10 {
+            step *= 2;
+        }
+
+        next += step;
+    }
+
+    // always end with the oldest block, usually genesis
+    if let Some(oldest) = oldest {
+        if locator.last() != Some(&oldest) {
+            locator.push(oldest);
+        }
+    }
+
+    locator
+}
+
+/// The filter headers must chain from the previous header to the stop block, and match the
+/// peer's checkpoints at every 1000th block
+fn check_filter_headers(
+    headers: &CFHeaders,
+    start_height: u32,
+    stop: BlockId,
+    checkpoints: &[FilterHeader],
+) -> Result<(), CbfError> {
+    let count = stop.height + 1 - start_height;
+    if headers.stop_hash != stop.hash || headers.filter_hashes.len() != count as usize {
+        return Err(CbfError::InvalidFilterHeaders);
+    }
+
+    let matches_checkpoint = |height: u32, header: &FilterHeader| {
+        if height == 0 || height % FILTER_CHECKPOINT_INTERVAL != 0 {
+            return true;
+        }
+
+        let index = height / FILTER_CHECKPOINT_INTERVAL - 1;
+        checkpoints.get(index as usize) == Some(header)
+    };
+
+    let mut header = headers.previous_filter_header;
+    if !matches_checkpoint(start_height - 1, &header) {
+        return Err(CbfError::InvalidFilterHeaders);
+    }
+
+    for (height, filter_hash) in (start_height..).zip(&headers.filter_hashes) {
+        header = filter_hash.filter_header(&header);
+        if !matches_checkpoint(height, &header) {
+            return Err(CbfError::InvalidFilterHeaders);
+        }
+    }
+
+    Ok(())
+}
+
+/// Peer `host` and `port` from the node url, the port defaults to the network's p2p port
+pub fn parse_peer_address(url: &str, network: Network) -> Result<(String, u16), Error> {
+    let url = url.trim();
+    let url = if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("p2p://{url}"))
+    }
+    .map_err(|error| Error::InvalidCbfPeer(error.to_string()))?;
+
+    let Some(host) = url.host_str().filter(|host| !host.is_empty()) else {
+        return Err(Error::InvalidCbfPeer("no host".to_string()));
+    };
+
+    let port = url.port().unwrap_or_else(|| default_p2p_port(network));
+    Ok((host.to_string(), port))
+}
+
+fn default_p2p_port(network: Network) -> u16 {
+    match network {
+        Network::Bitcoin => 8333,
+        Network::Testnet => 18333,
+        Network::Signet => 38333,
+        Network::Regtest => 18444,
+    }
+}
+
+fn connect_direct(host: &str, port: u16) -> io::Result<TcpStream> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "peer host did not resolve");
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash as _;
+
+    use super::*;
+
+    fn block_hash(height: u32) -> BlockHash {
+        BlockHash::hash(&height.to_le_bytes())
+    }
+
+    #[test]
+    fn test_parse_peer_address() {
+        let parse = |url: &str| parse_peer_address(url, Network::Regtest).unwrap();
+
+        assert_eq!(parse("127.0.0.1"), ("127.0.0.1".to_string(), 18444));
+        assert_eq!(parse("localhost:18445"), ("localhost".to_string(), 18445));
+        assert_eq!(parse("p2p://[::1]:8333"), ("[::1]".to_string(), 8333));
+        assert!(parse_peer_address("", Network::Regtest).is_err());
+    }
+
+    #[test]
+    fn test_locator() {
+        let genesis = CheckPoint::new(BlockId {
+            height: 0,
+            hash: block_hash(0),
+        });
+
+        let block_ids = (1..=5000).map(|height| BlockId {
+            height,
+            hash: block_hash(height),
+        });
+
+        let chain_tip = genesis.extend(block_ids).unwrap();
+        let locator = HeaderChain::new(chain_tip).locator();
+
+        assert!(locator.len() <= MAX_LOCATOR_HASHES);
+        let newest: Vec<BlockHash> = (4991..=5000).rev().map(block_hash).collect();
+        assert_eq!(locator[..10].to_vec(), newest);
+        assert_eq!(locator.last(), Some(&block_hash(0)));
+
+        let genesis_only = CheckPoint::new(BlockId {
+            height: 0,
+            hash: block_hash(0),
+        });
+
+        let locator = HeaderChain::new(genesis_only).locator();
+        assert_eq!(locator, vec![block_hash(0)]);
+    }
+
+    #[test]
+    fn test_header_chain_truncate() {
+        let genesis = CheckPoint::new(BlockId {
+            height: 0,
+            hash: block_hash(0),
+        });
+
+        let mut chain = HeaderChain::new(genesis);
+        chain.hashes = (1..=20).map(block_hash).collect();
+
+        chain.truncate_to(block_hash(15)).unwrap();
+        assert_eq!(chain.tip().height, 15);
+        assert_eq!(chain.locator()[0], block_hash(15));
+
+        chain.truncate_to(block_hash(0)).unwrap();
+        assert_eq!(chain.tip().height, 0);
+        assert!(chain.truncate_to(block_hash(21)).is_err());
+    }
+
+    #[test]
+    fn test_check_filter_headers() {
+        let filter_hashes: Vec<FilterHash> = (0..=2500u32)
+            .map(|height| FilterHash::hash(&height.to_le_bytes()))
+            .collect();
+
+        let mut filter_headers = vec![FilterHeader::all_zeros()];
+        for filter_hash in &filter_hashes[1..] {
+            let previous = *filter_headers.last().unwrap();
+            filter_headers.push(filter_hash.filter_header(&previous));
+        }
+
+        let checkpoints = [filter_headers[1000], filter_headers[2000]];
+        let headers = |start: u32, stop: u32| CFHeaders {
+            filter_type: BASIC_FILTER,
+            stop_hash: block_hash(stop),
+            previous_filter_header: filter_headers[start as usize - 1],
+            filter_hashes: filter_hashes[start as usize..=stop as usize].to_vec(),
+        };
+
+        let stop = |height: u32| BlockId {
+            height,
+            hash: block_hash(height),
+        };
+
+        let check = |headers: &CFHeaders, start: u32, stop: BlockId| {
+            check_filter_headers(headers, start, stop, &checkpoints)
+        };
+
+        assert!(check(&headers(1, 1000), 1, stop(1000)).is_ok());
+        assert!(check(&headers(1001, 2500), 1001, stop(2500)).is_ok());
+
+        // a filter the peer changed no longer chains to its checkpoint
+        let mut changed = headers(1500, 2500);
+        changed.filter_hashes[10] = FilterHash::hash(b"changed");
+        assert!(check(&changed, 1500, stop(2500)).is_err());
+
+        let mut wrong_previous = headers(1001, 1500);
+        wrong_previous.previous_filter_header = filter_headers[999];
+        assert!(check(&wrong_previous, 1001, stop(1500)).is_err());
+
+        assert!(check(&headers(1001, 1500), 1001, stop(1501)).is_err());
+    }
+
+    #[test]
+    fn test_header_chain_heights() {
+        let base = CheckPoint::new(BlockId {
+            height: 100,
+            hash: block_hash(100),
+        });
+
+        let mut chain = HeaderChain::new(base);
+        chain.hashes = vec![block_hash(101), block_hash(102)];
+
+        assert_eq!(chain.tip().height, 102);
+        assert_eq!(chain.hash_at(100), Some(block_hash(100)));
+        assert_eq!(chain.hash_at(102), Some(block_hash(102)));
+        assert_eq!(chain.hash_at(99), None);
+        assert_eq!(chain.hash_at(103), None);
+    }
+
+    /// Run against a local regtest bitcoind started with `-blockfilterindex -peerblockfilters`, ex:
+    /// `COVE_CBF_PEER="127.0.0.1:18444" cargo test -- --ignored cbf`
+    #[tokio::test]
+    #[ignore]
+    async fn test_cbf_sync_headers_and_filters() {
+        let url = std::env::var("COVE_CBF_PEER").expect("COVE_CBF_PEER not set");
+        let node = Node::new_cbf("regtest".to_string(), url, Network::Regtest);
+
+        let client = CbfClient::new_from_node(&node).unwrap();
+        let height = client.get_height().await.unwrap();
+
+        crate::unblock::run_blocking(move || {
+            let mut peer = client.connect().unwrap();
+            let chain = peer.sync_headers(&client.genesis_checkpoint()).unwrap();
+            assert!(chain.tip().height as usize >= height);
+
+            // downloads and checks every filter, nothing matches an empty script list
+            assert!(peer.matching_blocks(&chain, 0, &[]).unwrap().is_empty());
+        })
+        .await;
+    }
+}
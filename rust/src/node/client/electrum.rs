@@ -1,9 +1,14 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use bdk_chain::{bitcoin::Address, ConfirmationBlockTime, TxGraph};
+use bdk_chain::{
+    bitcoin::{Address, ScriptBuf},
+    ConfirmationBlockTime, TxGraph,
+};
 use bdk_core::spk_client::{FullScanRequest, FullScanResponse, SyncRequest, SyncResponse};
 use bdk_electrum::{
-    electrum_client::{Client, ElectrumApi as _},
+    electrum_client::{
+        Client, ConfigBuilder, ElectrumApi as _, Error as ElectrumError, Socks5Config,
+    },
     BdkElectrumClient,
 };
 use bdk_wallet::KeychainKind;
@@ -12,7 +17,7 @@ use tap::TapFallible as _;
 use tracing::debug;
 
 use super::{Error, NodeClientOptions, ELECTRUM_BATCH_SIZE, STOP_GAP};
-use crate::node::Node;
+use crate::{node::Node, proxy::ProxySettings};
 
 type ElectrumClientInner = BdkElectrumClient<Client>;
 
@@ -22,6 +27,29 @@ pub struct ElectrumClient {
     options: NodeClientOptions,
 }
 
+/// Script and block header subscriptions on the client's connection, the server pushes a
+/// notification when a script's history changes or a new block is found
+pub struct ElectrumSubscriptions {
+    client: Arc<ElectrumClientInner>,
+    scripts: HashSet<ScriptBuf>,
+    headers_subscribed: bool,
+}
+
+/// What changed since the last poll
+#[derive(Debug, Default)]
+pub struct SubscriptionEvents {
+    /// Subscribed scripts with new activity
+    pub scripts: Vec<ScriptBuf>,
+    /// Height of the newest block, if there was a new block
+    pub new_height: Option<usize>,
+}
+
+impl SubscriptionEvents {
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty() && self.new_height.is_none()
+    }
+}
+
 impl ElectrumClient {
     pub fn new_with_options(client: Arc<ElectrumClientInner>, options: NodeClientOptions) -> Self {
         Self { client, options }
@@ -40,7 +68,21 @@ impl ElectrumClient {
         options: NodeClientOptions,
     ) -> Result<Self, Error> {
         let url = node.url.strip_suffix('/').unwrap_or(&node.url);
-        let inner_client = Client::new(url).map_err(Error::CreateElectrumClient)?;
+
+        let mut config = ConfigBuilder::new();
+        if let Some(proxy) = ProxySettings::current() {
+            let socks5 = match proxy.credentials() {
+                Some((username, password)) => {
+                    Socks5Config::with_credentials(proxy.address(), username, password)
+                }
+                None => Socks5Config::new(proxy.address()),
+            };
+
+            config = config.socks5(Some(socks5));
+        }
+
+        let inner_client =
+            Client::from_config(url, config.build()).map_err(Error::CreateElectrumClient)?;
         let bdk_client = BdkElectrumClient::new(inner_client);
         let client = Arc::new(bdk_client);
 
@@ -140,11 +182,107 @@ impl ElectrumClient {
         Ok(tx_id)
     }
 
+    /// If both clients share the same connection to the server
+    pub fn is_same_connection(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.client, &other.client)
+    }
+
+    pub fn subscriptions(&self) -> ElectrumSubscriptions {
+        ElectrumSubscriptions {
+            client: self.client.clone(),
+            scripts: HashSet::new(),
+            headers_subscribed: false,
+        }
+    }
+
     fn default_options() -> NodeClientOptions {
         NodeClientOptions {
             batch_size: ELECTRUM_BATCH_SIZE,
             stop_gap: STOP_GAP,
+            start_height: 0,
+        }
+    }
+}
+
+impl ElectrumSubscriptions {
+    /// Subscribe to new blocks and the scripts that aren't subscribed yet, the server sends a
+    /// notification for every change after this
+    pub async fn subscribe(&mut self, scripts: Vec<ScriptBuf>) -> Result<(), Error> {
+        let subscribe_headers = !self.headers_subscribed;
+        let new_scripts: Vec<ScriptBuf> = scripts
+            .into_iter()
+            .filter(|script| !self.scripts.contains(script))
+            .collect();
+
+        if !subscribe_headers && new_scripts.is_empty() {
+            return Ok(());
         }
+
+        let client = self.client.clone();
+        let subscribe = move || -> Result<_, ElectrumError> {
+            let client = &client.inner;
+
+            if subscribe_headers {
+                client.block_headers_subscribe()?;
+            }
+
+            if !new_scripts.is_empty() {
+                client
+                    .batch_script_subscribe(new_scripts.iter().map(|script| script.as_script()))?;
+            }
+
+            Ok(new_scripts)
+        };
+
+        let new_scripts = crate::unblock::run_blocking(subscribe)
+            .await
+            .map_err(Error::ElectrumSubscribe)?;
+
+        self.headers_subscribed = true;
+        self.scripts.extend(new_scripts);
+
+        Ok(())
+    }
+
+    /// Collect the notifications the server sent since the last poll
+    ///
+    /// The electrum client only reads notifications off the connection while it waits for a
+    /// response, so pushes are emulated by polling, a ping reads them into the client's queues
+    /// and they are popped from there
+    pub async fn poll(&mut self) -> Result<SubscriptionEvents, Error> {
+        let client = self.client.clone();
+        let scripts = std::mem::take(&mut self.scripts);
+
+        let poll = move || -> Result<_, ElectrumError> {
+            let client = &client.inner;
+            client.ping()?;
+
+            let mut events = SubscriptionEvents::default();
+            for script in &scripts {
+                let mut has_activity = false;
+                while client.script_pop(script)?.is_some() {
+                    has_activity = true;
+                }
+
+                if has_activity {
+                    events.scripts.push(script.clone());
+                }
+            }
+
+            while let Some(header) = client.block_headers_pop()? {
+                events.new_height = Some(header.height);
+            }
+
+            Ok((events, scripts))
+        };
+
+        let (events, scripts) = crate::unblock::run_blocking(poll)
+            .await
+            .map_err(Error::ElectrumSubscribe)?;
+
+        self.scripts = scripts;
+
+        Ok(events)
     }
 }
 
@@ -13,7 +13,7 @@ use bitcoin::Txid;
 use tap::TapFallible as _;
 use tracing::debug;
 
-use crate::node::Node;
+use crate::{node::Node, proxy::ProxySettings};
 
 use super::{Error, NodeClientOptions, ESPLORA_BATCH_SIZE, STOP_GAP};
 
@@ -30,12 +30,13 @@ impl EsploraClient {
             options: NodeClientOptions {
                 batch_size: ESPLORA_BATCH_SIZE,
                 stop_gap: STOP_GAP,
+                start_height: 0,
             },
         }
     }
 
     pub fn new_from_node(node: &Node) -> Result<Self, Error> {
-        let client = esplora_client::Builder::new(&node.url)
+        let client = Self::builder(node)
             .build_async()
             .map_err(Error::CreateEsploraClient)?
             .into();
@@ -47,7 +48,7 @@ impl EsploraClient {
         node: &Node,
         options: NodeClientOptions,
     ) -> Result<Self, Error> {
-        let client = esplora_client::Builder::new(&node.url)
+        let client = Self::builder(node)
             .build_async()
             .map_err(Error::CreateEsploraClient)?
             .into();
@@ -59,6 +60,15 @@ impl EsploraClient {
         Self { client, options }
     }
 
+    fn builder(node: &Node) -> esplora_client::Builder {
+        let builder = esplora_client::Builder::new(&node.url);
+
+        match ProxySettings::current() {
+            Some(proxy) => builder.proxy(&proxy.url()),
+            None => builder,
+        }
+    }
+
     pub async fn get_height(&self) -> Result<u32, Error> {
         self.client
             .get_height()
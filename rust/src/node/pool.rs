@@ -0,0 +1,417 @@
+//! Ranked nodes for a network, calls retry with backoff and fail over to the next healthy node
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use futures::future::join_all;
+use tracing::{debug, warn};
+use tryhard::RetryPolicy;
+
+use crate::{database::Database, network::Network, wallet::metadata::ScanSettings};
+
+use super::{
+    client::{self, NodeClient, NodeClientOptions},
+    Node,
+};
+
+/// How often every node in the pool is checked for health and latency
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// A node that takes longer than this to answer is unhealthy
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Retries on the same node, before failing over to the next one
+const RETRIES: u32 = 2;
+
+/// Delay before the first retry, doubled for each one after
+const RETRY_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct NodeHealth {
+    pub node: Node,
+    /// `None` until the node is checked or used
+    pub is_healthy: Option<bool>,
+    /// Round trip of the last health check
+    pub latency_ms: Option<u32>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NodePoolError {
+    #[error("no nodes configured for this network")]
+    NoNodes,
+
+    #[error("all nodes failed, last error: {0}")]
+    AllNodesFailed(client::Error),
+
+    #[error("{0}")]
+    BroadcastRejected(client::Error),
+}
+
+type Error = NodePoolError;
+
+/// The selected node first, then the backup nodes in the user's order
+#[derive(Debug, Clone, Default)]
+pub struct NodePool {
+    nodes: Vec<NodeHealth>,
+    client: Option<NodeClient>,
+    /// Index of the node `client` is connected to
+    current: Option<usize>,
+    last_health_check: Option<Instant>,
+    /// Connected to a different node since this was last taken
+    switched_to: Option<Node>,
+}
+
+impl NodeHealth {
+    fn unchecked(node: Node) -> Self {
+        Self {
+            node,
+            is_healthy: None,
+            latency_ms: None,
+            last_error: None,
+        }
+    }
+
+    async fn check(node: Node) -> Self {
+        let start = Instant::now();
+        let result = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, node.check_url()).await;
+        let latency_ms = start.elapsed().as_millis() as u32;
+
+        let last_error = match result {
+            Ok(Ok(())) => None,
+            Ok(Err(error)) => Some(error.to_string()),
+            Err(_) => Some(format!(
+                "timed out after {}s",
+                HEALTH_CHECK_TIMEOUT.as_secs()
+            )),
+        };
+
+        Self {
+            node,
+            is_healthy: Some(last_error.is_none()),
+            latency_ms: last_error.is_none().then_some(latency_ms),
+            last_error,
+        }
+    }
+}
+
+impl NodePool {
+    pub fn load(network: Network) -> Self {
+        let mut pool = Self::default();
+        pool.reload(network);
+        pool
+    }
+
+    /// Pick up changes to the selected or backup nodes, keeping the health of known nodes
+    pub fn reload(&mut self, network: Network) {
+        let config = &Database::global().global_config;
+
+        let nodes = std::iter::once(config.selected_node())
+            .chain(config.backup_nodes(network))
+            .filter(|node| node.network == network)
+            .collect();
+
+        self.set_nodes(nodes);
+    }
+
+    fn set_nodes(&mut self, nodes: Vec<Node>) {
+        let current_node = self.current_node().cloned();
+
+        let mut ranked: Vec<NodeHealth> = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let is_duplicate = ranked
+                .iter()
+                .any(|health| health.node.url == node.url && health.node.api_type == node.api_type);
+
+            if is_duplicate {
+                continue;
+            }
+
+            let health = self
+                .nodes
+                .iter()
+                .find(|health| health.node == node)
+                .cloned()
+                .unwrap_or_else(|| NodeHealth::unchecked(node));
+
+            ranked.push(health);
+        }
+
+        self.nodes = ranked;
+        self.current = current_node.and_then(|node| self.position(&node));
+
+        // the client is for a node that was removed
+        if self.current.is_none() {
+            self.client = None;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn client(&self) -> Option<NodeClient> {
+        self.client.clone()
+    }
+
+    pub fn current_node(&self) -> Option<&Node> {
+        self.current.map(|index| &self.nodes[index].node)
+    }
+
+    pub fn health(&self) -> Vec<NodeHealth> {
+        self.nodes.clone()
+    }
+
+    pub fn nodes(&self) -> Vec<Node> {
+        self.nodes
+            .iter()
+            .map(|health| health.node.clone())
+            .collect()
+    }
+
+    /// The node the pool switched to, if it changed since the last call
+    pub fn take_switch(&mut self) -> Option<Node> {
+        self.switched_to.take()
+    }
+
+    pub fn needs_health_check(&self) -> bool {
+        match self.last_health_check {
+            Some(last_check) => last_check.elapsed() >= HEALTH_CHECK_INTERVAL,
+            None => true,
+        }
+    }
+
+    /// Checks all the nodes concurrently, meant to be run in a background task, apply the results
+    /// with [`Self::apply_health`]
+    pub async fn check_health(nodes: Vec<Node>) -> Vec<NodeHealth> {
+        join_all(nodes.into_iter().map(NodeHealth::check)).await
+    }
+
+    pub fn apply_health(&mut self, health: Vec<NodeHealth>) {
+        self.last_health_check = Some(Instant::now());
+
+        for checked in health {
+            if let Some(index) = self.position(&checked.node) {
+                self.nodes[index] = checked;
+            }
+        }
+    }
+
+    /// Connect to the highest ranked node that is up, trying nodes known to be down last
+    pub async fn connect(&mut self, settings: ScanSettings) -> Result<NodeClient, Error> {
+        let mut last_error = None;
+
+        for index in self.connection_order() {
+            match self.connect_to(index, settings).await {
+                Ok(client) => return Ok(client),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(last_error.map_or(Error::NoNodes, Error::AllNodesFailed))
+    }
+
+    /// Run `call` on the current node, retrying with backoff, then on every other node in order
+    /// until one succeeds, a rejected broadcast is returned right away, the node is working
+    pub async fn call<T, F, Fut>(&mut self, settings: ScanSettings, call: F) -> Result<T, Error>
+    where
+        F: Fn(NodeClient) -> Fut,
+        Fut: Future<Output = Result<T, client::Error>>,
+    {
+        let mut order = self.connection_order();
+        if let Some(current) = self.current {
+            order.retain(|index| *index != current);
+            order.insert(0, current);
+        }
+
+        let mut last_error = None;
+        for index in order {
+            let connected = self.client.clone().filter(|_| self.current == Some(index));
+            let client = match connected {
+                Some(client) => client,
+                None => match self.connect_to(index, settings).await {
+                    Ok(client) => client,
+                    Err(error) => {
+                        last_error = Some(error);
+                        continue;
+                    }
+                },
+            };
+
+            match with_retry(|| call(client.clone())).await {
+                Ok(value) => return Ok(value),
+                Err(error) if error.is_broadcast_rejected() => {
+                    return Err(Error::BroadcastRejected(error));
+                }
+                Err(error) => {
+                    self.mark_failed(index, &error);
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        Err(last_error.map_or(Error::NoNodes, Error::AllNodesFailed))
+    }
+
+    /// The current node failed outside of [`Self::call`], ex: in a background scan
+    pub fn mark_current_failed(&mut self, error: &client::Error) {
+        if let Some(current) = self.current {
+            self.mark_failed(current, error);
+        }
+    }
+
+    async fn connect_to(
+        &mut self,
+        index: usize,
+        settings: ScanSettings,
+    ) -> Result<NodeClient, client::Error> {
+        let node = self.nodes[index].node.clone();
+        let options = NodeClientOptions::new(node.api_type, settings);
+
+        let node_ref = &node;
+        let client = with_retry(|| async move {
+            let client = NodeClient::new_with_options(node_ref, options).await?;
+            client.check_url().await?;
+            Ok(client)
+        })
+        .await
+        .inspect_err(|error| self.mark_failed(index, error))?;
+
+        // the selected node is the one expected when nothing was connected yet
+        if index != self.current.unwrap_or(0) {
+            debug!("switched to node {}", node.name);
+            self.switched_to = Some(node);
+        }
+
+        let health = &mut self.nodes[index];
+        health.is_healthy = Some(true);
+        health.last_error = None;
+
+        self.client = Some(client.clone());
+        self.current = Some(index);
+
+        Ok(client)
+    }
+
+    fn mark_failed(&mut self, index: usize, error: &client::Error) {
+        let health = &mut self.nodes[index];
+        warn!("node {} failed: {error}", health.node.name);
+
+        health.is_healthy = Some(false);
+        health.last_error = Some(error.to_string());
+    }
+
+    /// Rank order, with nodes known to be down moved to the end
+    fn connection_order(&self) -> Vec<usize> {
+        let (mut order, down): (Vec<usize>, Vec<usize>) =
+            (0..self.nodes.len()).partition(|index| self.nodes[*index].is_healthy != Some(false));
+
+        order.extend(down);
+        order
+    }
+
+    fn position(&self, node: &Node) -> Option<usize> {
+        self.nodes.iter().position(|health| &health.node == node)
+    }
+}
+
+async fn with_retry<T, F, Fut>(call: F) -> Result<T, client::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, client::Error>>,
+{
+    tryhard::retry_fn(call)
+        .retries(RETRIES)
+        .custom_backoff(|attempt, error: &client::Error| {
+            if error.is_broadcast_rejected() {
+                return RetryPolicy::Break;
+            }
+
+            RetryPolicy::Delay(RETRY_DELAY.saturating_mul(1 << attempt.saturating_sub(1)))
+        })
+        .max_delay(Duration::from_secs(2))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use bdk_electrum::electrum_client;
+
+    use super::*;
+
+    fn node(name: &str) -> Node {
+        let url = format!("ssl://{name}:50002");
+        Node::new_electrum(name.to_string(), url, Network::Bitcoin)
+    }
+
+    fn pool(names: &[&str]) -> NodePool {
+        let mut pool = NodePool::default();
+        pool.set_nodes(names.iter().map(|name| node(name)).collect());
+        pool
+    }
+
+    #[test]
+    fn test_connection_order_skips_down_nodes() {
+        let mut pool = pool(&["a", "b", "c"]);
+        assert_eq!(pool.connection_order(), vec![0, 1, 2]);
+
+        let mut down = NodeHealth::unchecked(node("a"));
+        down.is_healthy = Some(false);
+        pool.apply_health(vec![down]);
+
+        assert_eq!(pool.connection_order(), vec![1, 2, 0]);
+        assert!(!pool.needs_health_check());
+    }
+
+    #[test]
+    fn test_reload_keeps_health_and_removes_duplicates() {
+        let mut pool = pool(&["a", "b"]);
+
+        let mut healthy = NodeHealth::unchecked(node("b"));
+        healthy.is_healthy = Some(true);
+        healthy.latency_ms = Some(40);
+        pool.apply_health(vec![healthy.clone()]);
+
+        pool.set_nodes(vec![node("b"), node("c"), node("b")]);
+
+        assert_eq!(pool.nodes(), vec![node("b"), node("c")]);
+        assert_eq!(pool.health()[0], healthy);
+        assert_eq!(pool.health()[1].is_healthy, None);
+    }
+
+    async fn broadcast_attempts(error: fn() -> electrum_client::Error) -> u32 {
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), client::Error> = with_retry(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async move { Err(client::Error::ElectrumBroadcast(error())) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        attempts.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_rejected_broadcast_is_not_retried() {
+        let rejected = || electrum_client::Error::Protocol("min relay fee not met".into());
+        assert_eq!(broadcast_attempts(rejected).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_connection_error_is_retried() {
+        let disconnected = || {
+            let error = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+            electrum_client::Error::IOError(error)
+        };
+
+        assert_eq!(broadcast_attempts(disconnected).await, RETRIES + 1);
+    }
+}
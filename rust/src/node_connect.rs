@@ -1,7 +1,12 @@
 use tracing::error;
 use url::Url;
 
-use crate::{database::Database, network::Network, node::Node};
+use crate::{
+    database::Database,
+    network::Network,
+    node::{client::cbf::parse_peer_address, ApiType, Node},
+    proxy::{is_onion_host, ProxySettings},
+};
 use macros::impl_default_for;
 
 pub const BITCOIN_ESPLORA: [(&str, &str); 2] = [
@@ -28,6 +33,9 @@ pub const TESTNET_ELECTRUM: [(&str, &str); 1] =
 
 pub const SIGNET_ESPLORA: [(&str, &str); 1] = [("mutinynet", "https://mutinynet.com/api")];
 
+/// There are no preset regtest nodes, this is the default for a local electrs instance
+pub const REGTEST_ELECTRUM: (&str, &str) = ("localhost", "tcp://127.0.0.1:60401");
+
 #[derive(Debug, Clone, uniffi::Object)]
 pub struct NodeSelector {
     network: Network,
@@ -137,19 +145,34 @@ impl NodeSelector {
     }
 
     #[uniffi::method]
-    /// Use the url and name of the custom node to set it as the selected node
+    /// Use the url and type of the custom node to set it as the selected node
     pub fn parse_custom_node(
         &self,
         url: String,
-        name: String,
+        api_type: ApiType,
         entered_name: String,
     ) -> Result<Node, Error> {
-        let node_type = name.to_ascii_lowercase();
+        match api_type {
+            ApiType::Rpc => return self.parse_custom_rpc_node(url, entered_name),
+            ApiType::Cbf => return self.parse_custom_cbf_node(url, entered_name),
+            ApiType::Electrum | ApiType::Esplora => {}
+        }
 
-        let url =
-            parse_node_url(&url).map_err(|error| Error::ParseNodeUrlError(error.to_string()))?;
+        let url = match api_type {
+            ApiType::Esplora => parse_esplora_node_url(&url),
+            _ => parse_node_url(&url),
+        }
+        .map_err(|error| Error::ParseNodeUrlError(error.to_string()))?;
 
-        if !url.domain().unwrap_or_default().contains('.') {
+        // onion services can only be reached through tor
+        if url.host_str().is_some_and(is_onion_host) && ProxySettings::current().is_none() {
+            return Err(Error::ParseNodeUrlError(
+                "onion addresses need a Tor proxy, add one in network settings".to_string(),
+            ));
+        }
+
+        // local regtest nodes are usually reached by ip address or `localhost`
+        if self.network != Network::Regtest && !url.domain().unwrap_or_default().contains('.') {
             return Err(Error::ParseNodeUrlError(
                 "invalid url, no domain".to_string(),
             ));
@@ -158,18 +181,14 @@ impl NodeSelector {
         let url_string = url.to_string();
 
         let name = if entered_name.is_empty() {
-            url.domain().unwrap_or(url_string.as_str()).to_string()
+            url.host_str().unwrap_or(url_string.as_str()).to_string()
         } else {
             entered_name
         };
 
-        let node = if node_type.contains("electrum") {
-            Node::new_electrum(name, url_string, self.network)
-        } else if node_type.contains("esplora") {
-            Node::new_esplora(name, url_string, self.network)
-        } else {
-            error!("invalid node type: {node_type}");
-            Node::default(self.network)
+        let node = match api_type {
+            ApiType::Electrum => Node::new_electrum(name, url_string, self.network),
+            _ => Node::new_esplora(name, url_string, self.network),
         };
 
         Ok(node)
@@ -190,9 +209,85 @@ impl NodeSelector {
 
         Ok(())
     }
+
+    #[uniffi::method]
+    /// Nodes wallets fail over to, in order, when the selected node is down
+    pub fn backup_nodes(&self) -> Vec<Node> {
+        Database::global().global_config.backup_nodes(self.network)
+    }
+
+    #[uniffi::method]
+    pub fn set_backup_nodes(&self, nodes: Vec<Node>) -> Result<(), Error> {
+        if let Some(node) = nodes.iter().find(|node| node.network != self.network) {
+            let error = format!("node {} is for {}", node.name, node.network);
+            return Err(Error::SetSelectedNodeError(error));
+        }
+
+        Database::global()
+            .global_config
+            .set_backup_nodes(self.network, &nodes)
+            .map_err(|error| Error::SetSelectedNodeError(error.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl NodeSelector {
+    /// Bitcoin Core nodes are usually on a local network, so allow ip addresses and bare hosts
+    fn parse_custom_rpc_node(&self, url: String, entered_name: String) -> Result<Node, Error> {
+        let url = parse_rpc_node_url(&url, self.network)
+            .map_err(|error| Error::ParseNodeUrlError(error.to_string()))?;
+
+        let Some(host) = url.host_str() else {
+            return Err(Error::ParseNodeUrlError("invalid url, no host".to_string()));
+        };
+
+        // the rpc client connects directly, it can't go through the proxy
+        if is_onion_host(host) {
+            return Err(Error::ParseNodeUrlError(
+                "onion addresses are only supported for electrum and esplora nodes".to_string(),
+            ));
+        }
+
+        if ProxySettings::current().is_some() {
+            return Err(Error::ParseNodeUrlError(
+                "bitcoin core rpc nodes can't connect through a proxy, turn off the proxy to use one"
+                    .to_string(),
+            ));
+        }
+
+        let name = if entered_name.is_empty() {
+            host.to_string()
+        } else {
+            entered_name
+        };
+
+        Ok(Node::new_rpc(name, url.to_string(), self.network))
+    }
+
+    /// Compact block filter peers are bitcoin nodes, usually a local one, so allow ip addresses
+    /// and bare hosts, ex: `192.168.1.10:8333`
+    fn parse_custom_cbf_node(&self, url: String, entered_name: String) -> Result<Node, Error> {
+        let (host, port) = parse_peer_address(&url, self.network)
+            .map_err(|error| Error::ParseNodeUrlError(error.to_string()))?;
+
+        if is_onion_host(&host) && ProxySettings::current().is_none() {
+            return Err(Error::ParseNodeUrlError(
+                "onion addresses need a Tor proxy, add one in network settings".to_string(),
+            ));
+        }
+
+        let name = if entered_name.is_empty() {
+            host.clone()
+        } else {
+            entered_name
+        };
+
+        Ok(Node::new_cbf(name, format!("{host}:{port}"), self.network))
+    }
 }
 
-fn node_list(network: Network) -> Vec<Node> {
+pub fn node_list(network: Network) -> Vec<Node> {
     match network {
         Network::Bitcoin => {
             let mut nodes = BITCOIN_ELECTRUM
@@ -228,6 +323,9 @@ fn node_list(network: Network) -> Vec<Node> {
             .iter()
             .map(|(name, url)| Node::new_esplora(name.to_string(), url.to_string(), network))
             .collect::<Vec<Node>>(),
+
+        // regtest only supports custom nodes
+        Network::Regtest => vec![],
     }
 }
 
@@ -263,6 +361,41 @@ fn parse_node_url(url: &str) -> Result<Url, url::ParseError> {
     Ok(url)
 }
 
+/// Esplora is a http api, so its urls keep their http(s) scheme and default to https
+fn parse_esplora_node_url(url: &str) -> Result<Url, url::ParseError> {
+    if url.contains("://") {
+        Url::parse(url)
+    } else {
+        Url::parse(&format!("https://{url}"))
+    }
+}
+
+/// Bitcoin Core rpc urls keep their http(s) scheme and default to the network's rpc port
+fn parse_rpc_node_url(url: &str, network: Network) -> Result<Url, url::ParseError> {
+    let mut url = if url.contains("://") {
+        Url::parse(url)?
+    } else {
+        Url::parse(&format!("http://{url}"))?
+    };
+
+    // urls without a host, ex: `file:///x`, can't have a port
+    if url.port().is_none() {
+        url.set_port(Some(default_rpc_port(network)))
+            .map_err(|_| url::ParseError::EmptyHost)?;
+    }
+
+    Ok(url)
+}
+
+fn default_rpc_port(network: Network) -> u16 {
+    match network {
+        Network::Bitcoin => 8332,
+        Network::Testnet => 18332,
+        Network::Signet => 38332,
+        Network::Regtest => 18443,
+    }
+}
+
 #[uniffi::export]
 fn node_selection_to_node(node: NodeSelection) -> Node {
     node.into()
@@ -276,6 +409,7 @@ fn default_node_selection() -> NodeSelection {
         Network::Bitcoin => BITCOIN_ESPLORA[0],
         Network::Testnet => TESTNET_ESPLORA[0],
         Network::Signet => SIGNET_ESPLORA[0],
+        Network::Regtest => return NodeSelection::Custom(Node::default(network)),
     };
 
     NodeSelection::Preset(Node::new_esplora(
@@ -284,3 +418,44 @@ fn default_node_selection() -> NodeSelection {
         network,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONION: &str = "explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion";
+
+    #[test]
+    fn test_parse_onion_node_url() {
+        let url = parse_node_url(&format!("{ONION}:50001")).unwrap();
+        assert_eq!(url.to_string(), format!("tcp://{ONION}:50001/"));
+
+        let url = parse_node_url(&format!("ssl://{ONION}")).unwrap();
+        assert_eq!(url.to_string(), format!("ssl://{ONION}:50002"));
+        assert!(url.host_str().is_some_and(is_onion_host));
+    }
+
+    #[test]
+    fn test_parse_esplora_node_url() {
+        let url = parse_esplora_node_url("http://127.0.0.1:3002").unwrap();
+        assert_eq!(url.to_string(), "http://127.0.0.1:3002/");
+
+        let url = parse_esplora_node_url("https://mempool.space/api").unwrap();
+        assert_eq!(url.to_string(), "https://mempool.space/api");
+
+        let url = parse_esplora_node_url("blockstream.info/api").unwrap();
+        assert_eq!(url.to_string(), "https://blockstream.info/api");
+    }
+
+    #[test]
+    fn test_parse_rpc_node_url() {
+        let url = parse_rpc_node_url("192.168.1.10", Network::Regtest).unwrap();
+        assert_eq!(url.to_string(), "http://192.168.1.10:18443/");
+
+        let url = parse_rpc_node_url("https://node.local:8332", Network::Bitcoin).unwrap();
+        assert_eq!(url.to_string(), "https://node.local:8332/");
+
+        // parses as a url, but has no host to connect to
+        assert!(parse_rpc_node_url("file:///x", Network::Bitcoin).is_err());
+    }
+}
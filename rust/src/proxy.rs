@@ -0,0 +1,296 @@
+//! SOCKS5 proxy, ex: Tor, used for every connection to nodes and to the price and fee servers
+
+use std::{
+    io::{self, Read as _, Write as _},
+    net::TcpStream,
+};
+
+use crate::database::Database;
+
+#[derive(
+    Debug, Clone, Hash, Eq, PartialEq, uniffi::Record, serde::Serialize, serde::Deserialize,
+)]
+pub struct ProxySettings {
+    /// ex: 127.0.0.1 for a local Tor daemon or Orbot
+    pub host: String,
+    /// ex: 9050 for Tor, 9150 for Tor Browser
+    pub port: u16,
+    /// Use new proxy credentials for each connection, so Tor builds a separate circuit for each
+    pub stream_isolation: bool,
+}
+
+impl ProxySettings {
+    /// The proxy from the settings, `None` connects directly
+    pub fn current() -> Option<Self> {
+        Database::global().global_config.proxy()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let host = self.host.trim();
+
+        if host.is_empty() {
+            return Err("host is empty".to_string());
+        }
+
+        if host.contains(['/', ' ', '@']) {
+            let error = format!("host should only be the ip or name, ex: 127.0.0.1, got {host}");
+            return Err(error);
+        }
+
+        if self.port == 0 {
+            return Err("port must be between 1 and 65535".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// `host:port`, with brackets around ipv6 hosts
+    pub fn address(&self) -> String {
+        let host = self.host.trim();
+
+        if host.contains(':') && !host.starts_with('[') {
+            return format!("[{host}]:{}", self.port);
+        }
+
+        format!("{host}:{}", self.port)
+    }
+
+    /// Username and password for a new connection, only set with stream isolation, Tor uses
+    /// a different circuit for each unique pair
+    pub fn credentials(&self) -> Option<(String, String)> {
+        if !self.stream_isolation {
+            return None;
+        }
+
+        let id: u64 = rand::random();
+        Some((format!("sbw-{id:016x}"), "sbw".to_string()))
+    }
+
+    /// `socks5h` so host names, including `.onion` addresses, are resolved by the proxy
+    pub fn url(&self) -> String {
+        match self.credentials() {
+            Some((username, password)) => {
+                format!("socks5h://{username}:{password}@{}", self.address())
+            }
+            None => format!("socks5h://{}", self.address()),
+        }
+    }
+
+    pub fn http_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        reqwest::Client::builder()
+            .proxy(reqwest::Proxy::all(self.url())?)
+            .build()
+    }
+
+    /// Blocking tcp connection to `host:port` through the proxy, used for p2p connections
+    ///
+    /// The host is always sent as a name, so the proxy resolves it and `.onion` hosts work
+    pub fn connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.len() > u8::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "host name too long",
+            ));
+        }
+
+        let mut stream = TcpStream::connect(self.address())?;
+        let credentials = self.credentials();
+
+        // greeting, with the one auth method we want to use
+        let method = if credentials.is_some() { 2 } else { 0 };
+        stream.write_all(&[5, 1, method])?;
+
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply)?;
+        if reply != [5, method] {
+            return Err(io::Error::other("proxy does not support the auth method"));
+        }
+
+        if let Some((username, password)) = credentials {
+            let mut auth = vec![1, username.len() as u8];
+            auth.extend(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend(password.as_bytes());
+
+            stream.write_all(&auth)?;
+            stream.read_exact(&mut reply)?;
+            if reply[1] != 0 {
+                return Err(io::Error::other("proxy rejected the credentials"));
+            }
+        }
+
+        // connect command, with the host as a domain name
+        let mut request = vec![5, 1, 0, 3, host.len() as u8];
+        request.extend(host.as_bytes());
+        request.extend(port.to_be_bytes());
+        stream.write_all(&request)?;
+
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header)?;
+        if header[1] != 0 {
+            let error = format!(
+                "proxy unable to connect to {host}:{port}, code {}",
+                header[1]
+            );
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, error));
+        }
+
+        // skip the address the proxy bound to, not needed
+        let bound_len = match header[3] {
+            1 => 4,
+            4 => 16,
+            3 => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len)?;
+                len[0] as usize
+            }
+            _ => return Err(io::Error::other("invalid proxy reply")),
+        };
+
+        let mut bound = vec![0u8; bound_len + 2];
+        stream.read_exact(&mut bound)?;
+
+        Ok(stream)
+    }
+}
+
+/// Http client through the proxy, when one is set, a new client for each call so each request
+/// gets its own circuit with stream isolation
+///
+/// Fails instead of falling back to a direct connection, which would leak the user's ip address
+pub fn http_client() -> Result<Option<reqwest::Client>, reqwest::Error> {
+    let Some(proxy) = ProxySettings::current() else { return Ok(None) };
+    Ok(Some(proxy.http_client()?))
+}
+
+/// `.onion` hosts can only be reached through a Tor proxy
+pub fn is_onion_host(host: &str) -> bool {
+    host.trim_end_matches('.')
+        .to_ascii_lowercase()
+        .ends_with(".onion")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read as _, Write as _},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn proxy(host: &str, port: u16, stream_isolation: bool) -> ProxySettings {
+        ProxySettings {
+            host: host.to_string(),
+            port,
+            stream_isolation,
+        }
+    }
+
+    #[test]
+    fn test_proxy_url() {
+        assert_eq!(
+            proxy("127.0.0.1", 9050, false).url(),
+            "socks5h://127.0.0.1:9050"
+        );
+        assert_eq!(proxy("::1", 9050, false).url(), "socks5h://[::1]:9050");
+
+        let isolated = proxy("localhost", 9150, true);
+        let (first, second) = (isolated.url(), isolated.url());
+
+        assert!(first.starts_with("socks5h://sbw-"));
+        assert!(first.ends_with("@localhost:9150"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_validate_proxy() {
+        assert!(proxy("127.0.0.1", 9050, true).validate().is_ok());
+        assert!(proxy("", 9050, false).validate().is_err());
+        assert!(proxy("socks5://127.0.0.1", 9050, false).validate().is_err());
+        assert!(proxy("127.0.0.1", 0, false).validate().is_err());
+    }
+
+    #[test]
+    fn test_onion_host() {
+        let host = "explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion";
+
+        assert!(is_onion_host(host));
+        assert!(is_onion_host("EXAMPLE.ONION."));
+        assert!(!is_onion_host("onion.example.com"));
+    }
+
+    /// Minimal SOCKS5 server, accepts one connection and returns the host it was asked for
+    fn socks5_server() -> (u16, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 512];
+
+            // greeting: version, number of methods, methods
+            stream.read_exact(&mut buf[..2]).unwrap();
+            let methods = buf[1] as usize;
+            stream.read_exact(&mut buf[..methods]).unwrap();
+
+            if buf[..methods].contains(&2) {
+                // username and password
+                stream.write_all(&[5, 2]).unwrap();
+                stream.read_exact(&mut buf[..2]).unwrap();
+                let username = buf[1] as usize;
+                stream.read_exact(&mut buf[..username + 1]).unwrap();
+                let password = buf[username] as usize;
+                stream.read_exact(&mut buf[..password]).unwrap();
+                stream.write_all(&[1, 0]).unwrap();
+            } else {
+                stream.write_all(&[5, 0]).unwrap();
+            }
+
+            // connect request with a domain name: version, command, reserved, type 3, length
+            stream.read_exact(&mut buf[..5]).unwrap();
+            assert_eq!(buf[3], 3, "host should be resolved by the proxy");
+
+            let length = buf[4] as usize;
+            stream.read_exact(&mut buf[..length + 2]).unwrap();
+            let host = String::from_utf8(buf[..length].to_vec()).unwrap();
+
+            // connection refused, the test only needs the requested host
+            stream.write_all(&[5, 5, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+            host
+        });
+
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_http_client_uses_proxy() {
+        for stream_isolation in [false, true] {
+            let (port, server) = socks5_server();
+            let client = proxy("127.0.0.1", port, stream_isolation)
+                .http_client()
+                .unwrap();
+
+            let onion = "http://explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion/";
+            assert!(client.get(onion).send().await.is_err());
+
+            let host = server.join().unwrap();
+            assert!(is_onion_host(&host));
+        }
+    }
+
+    #[test]
+    fn test_connect_uses_proxy() {
+        let onion = "explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion";
+
+        for stream_isolation in [false, true] {
+            let (port, server) = socks5_server();
+            let socks = proxy("127.0.0.1", port, stream_isolation);
+
+            let error = socks.connect(onion, 8333).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+            assert_eq!(server.join().unwrap(), onion);
+        }
+    }
+}
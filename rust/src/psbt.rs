@@ -1,8 +1,12 @@
-use crate::transaction::{Amount, BdkAmount, TxId};
-use bdk_wallet::psbt::PsbtUtils as _;
-use bitcoin::TxOut;
+use crate::{
+    database::Database,
+    transaction::{ffi::BitcoinTransaction, Amount, BdkAmount, OutPoint, TxId},
+    wallet::Address,
+};
+use bdk_wallet::{miniscript::psbt::PsbtExt as _, psbt::PsbtUtils as _, KeychainKind};
+use bitcoin::{secp256k1::Secp256k1, Address as BdkAddress, Network, Script, TxOut};
 use derive_more::{AsRef, Deref, From, Into};
-use std::fmt::Debug;
+use std::{fmt::Debug, str::FromStr as _, sync::Arc};
 
 pub type BdkPsbt = bdk_wallet::bitcoin::Psbt;
 
@@ -32,10 +36,50 @@ pub enum PsbtError {
     #[error("Fee overflow.")]
     FeeOverflow,
 
+    #[error("Unable to parse PSBT: {0}")]
+    ParseError(String),
+
+    #[error("PSBTs are for different transactions: {0}")]
+    CombineError(String),
+
+    #[error("PSBT is not fully signed: {0}")]
+    NotFinalized(String),
+
     #[error("Other PSBT error {0}")]
     Other(String),
 }
 
+/// An input of the psbt, the amount and address are only known if the psbt includes the utxo
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct PsbtInput {
+    pub outpoint: Arc<OutPoint>,
+    pub amount: Option<Arc<Amount>>,
+    pub address: Option<Arc<Address>>,
+    /// Spends an output of the loaded wallet
+    pub is_mine: bool,
+    /// Has at least one signature, or is already finalized
+    pub is_signed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct PsbtOutput {
+    pub amount: Arc<Amount>,
+    /// `None` for outputs without an address, ex: `OP_RETURN`
+    pub address: Option<Arc<Address>>,
+    pub is_mine: bool,
+    pub is_change: bool,
+}
+
+/// The psbt's inputs and outputs, matched against the loaded wallet
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct PsbtDetails {
+    pub tx_id: Arc<TxId>,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    /// `None` if the psbt is missing some of the utxos
+    pub fee: Option<Arc<Amount>>,
+}
+
 type Error = PsbtError;
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -47,6 +91,60 @@ impl Psbt {
         Ok(psbt.into())
     }
 
+    /// Base64 or hex encoded psbt
+    #[uniffi::constructor(name = "tryFromString")]
+    pub fn _try_from_str(psbt: String) -> Result<Self> {
+        Self::try_from_str(&psbt)
+    }
+
+    pub fn to_base64(&self) -> String {
+        self.0.to_string()
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.0.serialize()
+    }
+
+    /// Every input has its final script, the transaction can be extracted
+    pub fn is_finalized(&self) -> bool {
+        self.0
+            .inputs
+            .iter()
+            .all(|input| input.final_script_witness.is_some() || input.final_script_sig.is_some())
+    }
+
+    /// Inputs and outputs, without matching them against a wallet
+    pub fn details(&self) -> PsbtDetails {
+        let network = Database::global().global_config.selected_network();
+        self.details_for_wallet(None, network.into())
+    }
+
+    /// Merge the signatures from another copy of the same psbt
+    pub fn combine(&self, other: Arc<Psbt>) -> Result<Self> {
+        let mut psbt = self.0.clone();
+        let other = Arc::unwrap_or_clone(other).0;
+
+        psbt.combine(other)
+            .map_err(|error| PsbtError::CombineError(error.to_string()))?;
+
+        Ok(psbt.into())
+    }
+
+    /// Finalize a copy of the psbt and extract the signed transaction
+    pub fn extract_transaction(&self) -> Result<BitcoinTransaction> {
+        let secp = Secp256k1::verification_only();
+        let psbt = self.0.clone().finalize(&secp).map_err(|(_, errors)| {
+            let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+            PsbtError::NotFinalized(errors.join(", "))
+        })?;
+
+        let transaction = psbt
+            .extract_tx()
+            .map_err(|error| PsbtError::Other(error.to_string()))?;
+
+        Ok(transaction.into())
+    }
+
     /// The virtual size of the transaction.
     pub fn weight(&self) -> u64 {
         self.0.unsigned_tx.vsize() as u64
@@ -86,6 +184,95 @@ impl Psbt {
 }
 
 impl Psbt {
+    /// Parse a base64 or hex encoded psbt
+    pub fn try_from_str(psbt: &str) -> Result<Self> {
+        let psbt = psbt.trim();
+
+        if let Ok(data) = hex::decode(psbt) {
+            return Self::try_from_data(&data);
+        }
+
+        let psbt =
+            BdkPsbt::from_str(psbt).map_err(|error| PsbtError::ParseError(error.to_string()))?;
+
+        Ok(psbt.into())
+    }
+
+    /// Parse a binary psbt
+    pub fn try_from_data(data: &[u8]) -> Result<Self> {
+        let psbt =
+            BdkPsbt::deserialize(data).map_err(|error| PsbtError::ParseError(error.to_string()))?;
+
+        Ok(psbt.into())
+    }
+
+    /// Inputs and outputs, `wallet` is used to mark which ones are ours
+    pub fn details_for_wallet(
+        &self,
+        wallet: Option<&bdk_wallet::Wallet>,
+        network: Network,
+    ) -> PsbtDetails {
+        let address = |script: &Script| {
+            BdkAddress::from_script(script, network)
+                .ok()
+                .map(|address| Arc::new(Address::new(address)))
+        };
+
+        let is_mine =
+            |script: &Script| wallet.is_some_and(|wallet| wallet.is_mine(script.to_owned()));
+
+        let inputs = self
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(self.0.inputs.iter())
+            .enumerate()
+            .map(|(index, (txin, input))| {
+                let utxo = self.0.get_utxo_for(index);
+                let is_signed = !input.partial_sigs.is_empty()
+                    || input.tap_key_sig.is_some()
+                    || !input.tap_script_sigs.is_empty()
+                    || input.final_script_witness.is_some()
+                    || input.final_script_sig.is_some();
+
+                PsbtInput {
+                    outpoint: Arc::new(txin.previous_output.into()),
+                    amount: utxo.as_ref().map(|utxo| Arc::new(utxo.value.into())),
+                    address: utxo.as_ref().and_then(|utxo| address(&utxo.script_pubkey)),
+                    is_mine: utxo
+                        .as_ref()
+                        .is_some_and(|utxo| is_mine(&utxo.script_pubkey)),
+                    is_signed,
+                }
+            })
+            .collect();
+
+        let outputs = self
+            .unsigned_tx
+            .output
+            .iter()
+            .map(|output| {
+                let keychain = wallet
+                    .and_then(|wallet| wallet.derivation_of_spk(output.script_pubkey.clone()))
+                    .map(|(keychain, _index)| keychain);
+
+                PsbtOutput {
+                    amount: Arc::new(output.value.into()),
+                    address: address(&output.script_pubkey),
+                    is_mine: keychain.is_some(),
+                    is_change: keychain == Some(KeychainKind::Internal),
+                }
+            })
+            .collect();
+
+        PsbtDetails {
+            tx_id: Arc::new(self.tx_id()),
+            inputs,
+            outputs,
+            fee: self.fee().ok().map(Arc::new),
+        }
+    }
+
     /// Get all UTXOs
     pub fn utxos(&self) -> Option<Vec<TxOut>> {
         let tx = &self.unsigned_tx;
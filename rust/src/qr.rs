@@ -13,6 +13,7 @@ use crate::{
     mnemonic::{ParseMnemonic as _, WordAccess as _},
     multi_format::StringOrData,
     seed_qr::{SeedQr, SeedQrError},
+    ur::{UrError, UrJoinResult, UrJoiner},
 };
 
 #[derive(uniffi::Object)]
@@ -21,10 +22,15 @@ pub enum MultiQr {
     SeedQr(SeedQr),
     Single(String),
     Bbqr(Header, Arc<Mutex<ContinuousJoiner>>),
+    Ur(Arc<Mutex<UrJoiner>>),
 }
 
+/// Result of adding a part to a BBQr or an animated UR
 #[derive(Debug, uniffi::Object)]
-pub struct BbqrJoinResult(ContinuousJoinResult);
+pub enum BbqrJoinResult {
+    Bbqr(ContinuousJoinResult),
+    Ur(UrJoinResult),
+}
 
 #[derive(Debug, uniffi::Object)]
 pub struct BbqrJoined(Joined);
@@ -51,6 +57,9 @@ pub enum MultiQrError {
     #[error("Cannot add binary data to BBQR")]
     CannotAddBinaryDataToBbqr,
 
+    #[error("Cannot add binary data to UR")]
+    CannotAddBinaryDataToUr,
+
     #[error("BBQr did not container seed words, found: {0}")]
     BbqrDidNotContainSeedWords(String),
 
@@ -59,6 +68,9 @@ pub enum MultiQrError {
 
     #[error("Invalid plain text seed QR")]
     InvalidPlainTextQr(String),
+
+    #[error(transparent)]
+    InvalidUr(#[from] UrError),
 }
 
 #[derive(Debug, Clone, uniffi::Enum)]
@@ -67,6 +79,8 @@ pub enum MultiQrScanResult {
     Single(String),
     CompletedBBqr(Arc<BbqrJoined>),
     InProgressBBqr(u32),
+    CompletedUr(StringOrData),
+    InProgressUr(u32),
 }
 
 #[uniffi::export]
@@ -93,6 +107,12 @@ impl MultiQr {
             return Self::Bbqr(header, continuous_joiner);
         }
 
+        // try to parse one part of an animated UR
+        if crate::ur::is_multi_part(&qr) {
+            let joiner = UrJoiner::try_new(&qr).expect("already checked that it is multi part");
+            return Self::Ur(Arc::new(Mutex::new(joiner)));
+        }
+
         // try to parse standard seed qr
         if let Ok(seed_qr) = SeedQr::try_from_str(&qr) {
             return Self::SeedQr(seed_qr);
@@ -133,12 +153,20 @@ impl MultiQr {
                     ContinuousJoinResult::NotStarted => panic!("not started, not possible"),
                 }
             }
+            (Self::Ur(joiner), R::String(qr)) => match joiner.lock().add_part(&qr)? {
+                UrJoinResult::Complete(result) => MultiQrScanResult::CompletedUr(result),
+                UrJoinResult::InProgress { parts_left } => {
+                    MultiQrScanResult::InProgressUr(parts_left)
+                }
+            },
 
             // errors
             (Self::Bbqr(_, _), StringOrData::Data(_vec)) => {
                 return Err(MultiQrError::CannotAddBinaryDataToBbqr)
             }
 
+            (Self::Ur(_), R::Data(_)) => return Err(MultiQrError::CannotAddBinaryDataToUr),
+
             (Self::Single(_), R::Data(_)) => return Err(MultiQrError::CannotAddPartToSingleQr),
         };
 
@@ -159,6 +187,20 @@ impl MultiQr {
                 Some(grouped)
             }
 
+            // ex: a single part crypto-seed UR
+            MultiQrScanResult::Single(qr) if crate::ur::is_ur(&qr) => {
+                let StringOrData::String(words) = crate::ur::decode(&qr)? else {
+                    return Err(MultiQrError::InvalidPlainTextQr(qr));
+                };
+
+                let words = words
+                    .parse_mnemonic()
+                    .map_err(|_| MultiQrError::InvalidPlainTextQr(qr))?
+                    .grouped_plain_words_of(groups_of as usize);
+
+                Some(words)
+            }
+
             MultiQrScanResult::Single(qr) => {
                 let bip39 =
                     Mnemonic::parse_in(Language::English, &qr).or_else(|_| qr.parse_mnemonic());
@@ -175,7 +217,21 @@ impl MultiQr {
                 Some(words)
             }
 
-            MultiQrScanResult::InProgressBBqr(_) => None,
+            MultiQrScanResult::CompletedUr(StringOrData::String(words)) => {
+                let words = words
+                    .parse_mnemonic()
+                    .map_err(|e| MultiQrError::ParseError(e.to_string()))?
+                    .grouped_plain_words_of(groups_of as usize);
+
+                Some(words)
+            }
+
+            MultiQrScanResult::CompletedUr(StringOrData::Data(_)) => {
+                let error = "UR did not contain seed words".to_string();
+                return Err(MultiQrError::BbqrDidNotContainSeedWords(error));
+            }
+
+            MultiQrScanResult::InProgressBBqr(_) | MultiQrScanResult::InProgressUr(_) => None,
         };
 
         Ok(words)
@@ -191,6 +247,17 @@ impl MultiQr {
         matches!(self, MultiQr::Bbqr(_, _))
     }
 
+    #[uniffi::method]
+    pub fn is_ur(&self) -> bool {
+        matches!(self, MultiQr::Ur(_))
+    }
+
+    /// BBQr or animated UR, the parts need to be added with `add_part` until complete
+    #[uniffi::method]
+    pub fn is_multi_part(&self) -> bool {
+        self.is_bbqr() || self.is_ur()
+    }
+
     #[uniffi::method]
     pub fn add_part(&self, qr: String) -> Result<BbqrJoinResult, MultiQrError> {
         match self {
@@ -200,7 +267,12 @@ impl MultiQr {
                     .add_part(qr)
                     .map_err(|e| MultiQrError::ParseError(e.to_string()))?;
 
-                Ok(BbqrJoinResult(join_result))
+                Ok(BbqrJoinResult::Bbqr(join_result))
+            }
+
+            MultiQr::Ur(joiner) => {
+                let join_result = joiner.lock().add_part(&qr)?;
+                Ok(BbqrJoinResult::Ur(join_result))
             }
 
             // error
@@ -213,6 +285,7 @@ impl MultiQr {
     pub fn total_parts(&self) -> u32 {
         match self {
             MultiQr::Bbqr(header, _) => header.num_parts as u32,
+            MultiQr::Ur(joiner) => joiner.lock().total_parts(),
             MultiQr::SeedQr(_) => 1,
             MultiQr::Single(_) => 1,
         }
@@ -222,26 +295,39 @@ impl MultiQr {
 #[uniffi::export]
 impl BbqrJoinResult {
     pub fn is_complete(&self) -> bool {
-        matches!(self.0, ContinuousJoinResult::Complete(_))
+        matches!(
+            self,
+            Self::Bbqr(ContinuousJoinResult::Complete(_)) | Self::Ur(UrJoinResult::Complete(_))
+        )
     }
 
     pub fn final_result(&self) -> Result<String, MultiQrError> {
-        match &self.0 {
-            ContinuousJoinResult::Complete(result) => {
-                let data = result.data.clone();
-                let string = String::from_utf8(data).map_err(|_| MultiQrError::InvalidUtf8)?;
-                Ok(string)
+        match self.final_result_string_or_data()? {
+            StringOrData::String(string) => Ok(string),
+            StringOrData::Data(_) => Err(MultiQrError::InvalidUtf8),
+        }
+    }
+
+    /// The joined data as text when it is UTF-8, binary otherwise ex: a BBQr of type 'P' (psbt)
+    pub fn final_result_string_or_data(&self) -> Result<StringOrData, MultiQrError> {
+        match self {
+            Self::Bbqr(ContinuousJoinResult::Complete(result)) => {
+                Ok(StringOrData::new(result.data.clone()))
+            }
+            Self::Ur(UrJoinResult::Complete(result)) => Ok(result.clone()),
+            Self::Bbqr(_) | Self::Ur(UrJoinResult::InProgress { .. }) => {
+                Err(MultiQrError::NotYetAvailable)
             }
-            ContinuousJoinResult::InProgress { .. } => Err(MultiQrError::NotYetAvailable),
-            ContinuousJoinResult::NotStarted => Err(MultiQrError::NotYetAvailable),
         }
     }
 
     pub fn parts_left(&self) -> u32 {
-        match self.0 {
-            ContinuousJoinResult::Complete(_) => 0,
-            ContinuousJoinResult::InProgress { parts_left } => parts_left as u32,
-            ContinuousJoinResult::NotStarted => panic!("not started, not possible"),
+        match self {
+            Self::Bbqr(ContinuousJoinResult::Complete(_)) => 0,
+            Self::Bbqr(ContinuousJoinResult::InProgress { parts_left }) => *parts_left as u32,
+            Self::Bbqr(ContinuousJoinResult::NotStarted) => panic!("not started, not possible"),
+            Self::Ur(UrJoinResult::Complete(_)) => 0,
+            Self::Ur(UrJoinResult::InProgress { parts_left }) => *parts_left,
         }
     }
 }
@@ -5,7 +5,11 @@ use crate::{
     database::Database,
     mnemonic::NumberOfBip39Words,
     transaction::{ffi::BitcoinTransaction, Amount, TransactionDetails},
-    wallet::{confirm::ConfirmDetails, metadata::WalletId, Address},
+    wallet::{
+        confirm::{AddressAndAmount, ConfirmDetails},
+        metadata::WalletId,
+        Address,
+    },
 };
 
 use derive_more::From;
@@ -66,6 +70,14 @@ pub enum SendRoute {
         id: WalletId,
         address: Option<Arc<Address>>,
         amount: Option<Arc<Amount>>,
+        /// From a BIP21 payment request, saved as the recipient address' label
+        label: Option<String>,
+        /// From a BIP21 payment request, shown to the user before sending
+        message: Option<String>,
+    },
+    SetRecipients {
+        id: WalletId,
+        recipients: Vec<AddressAndAmount>,
     },
     HardwareExport {
         id: WalletId,
@@ -250,22 +262,32 @@ impl RouteFactory {
         reset_to.load_and_reset_after(time)
     }
 
-    #[uniffi::method(default(address = None, amount = None))]
+    #[uniffi::method(default(address = None, amount = None, label = None, message = None))]
     pub fn send_set_amount(
         &self,
         id: WalletId,
         address: Option<Arc<Address>>,
         amount: Option<Arc<Amount>>,
+        label: Option<String>,
+        message: Option<String>,
     ) -> Route {
         let send = SendRoute::SetAmount {
             id,
             address,
             amount,
+            label,
+            message,
         };
 
         Route::Send(send)
     }
 
+    /// Send to multiple recipients in one transaction
+    pub fn send_set_recipients(&self, id: WalletId, recipients: Vec<AddressAndAmount>) -> Route {
+        let send = SendRoute::SetRecipients { id, recipients };
+        Route::Send(send)
+    }
+
     #[uniffi::method(default(signed_transaction = None))]
     pub fn send_confirm(
         &self,
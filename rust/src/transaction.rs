@@ -20,7 +20,11 @@ use bdk_wallet::bitcoin::{
 };
 use rand::Rng as _;
 
-use crate::{database::Database, fiat::FiatAmount, wallet::Wallet};
+use crate::{
+    database::Database,
+    fiat::FiatAmount,
+    wallet::{label::Labels, Wallet},
+};
 
 pub type Amount = amount::Amount;
 pub type SentAndReceived = sent_and_received::SentAndReceived;
@@ -61,6 +65,7 @@ pub struct ConfirmedTransaction {
     pub confirmed_at: jiff::Timestamp,
     pub sent_and_received: SentAndReceived,
     pub fiat: Option<FiatAmount>,
+    pub user_label: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, uniffi::Object)]
@@ -69,6 +74,11 @@ pub struct UnconfirmedTransaction {
     pub sent_and_received: SentAndReceived,
     pub last_seen: u64,
     pub fiat: Option<FiatAmount>,
+    /// Transaction opted in to replace-by-fee (BIP125)
+    pub signals_rbf: bool,
+    /// Fee is known and one of our outputs is unspent, so a child can pay for it (CPFP)
+    pub can_cpfp: bool,
+    pub user_label: Option<String>,
 }
 
 #[derive(
@@ -126,8 +136,10 @@ impl Transaction {
     pub fn new(
         wallet: &Wallet,
         tx: CanonicalTx<Arc<BdkTransaction>, ConfirmationBlockTime>,
+        labels: &Labels,
     ) -> Self {
         let txid = tx.tx_node.txid.into();
+        let user_label = labels.tx_label(&tx.tx_node.txid);
         let fiat_currency = Database::global()
             .global_config
             .fiat_currency()
@@ -138,11 +150,19 @@ impl Transaction {
 
         match tx.chain_position {
             BdkChainPosition::Unconfirmed { last_seen } => {
+                let can_cpfp = wallet.calculate_fee(&tx.tx_node.tx).is_ok()
+                    && wallet
+                        .list_unspent()
+                        .any(|utxo| utxo.outpoint.txid == tx.tx_node.txid);
+
                 let unconfirmed = UnconfirmedTransaction {
                     txid,
                     sent_and_received,
                     last_seen: last_seen.unwrap_or_default(),
                     fiat,
+                    signals_rbf: tx.tx_node.tx.is_explicitly_rbf(),
+                    can_cpfp,
+                    user_label,
                 };
 
                 Self::Unconfirmed(Arc::new(unconfirmed))
@@ -160,6 +180,7 @@ impl Transaction {
                     confirmed_at,
                     sent_and_received,
                     fiat,
+                    user_label,
                 };
 
                 Self::Confirmed(Arc::new(confirmed))
@@ -203,6 +224,15 @@ impl From<BdkOutPoint> for OutPoint {
     }
 }
 
+impl From<&OutPoint> for BdkOutPoint {
+    fn from(out_point: &OutPoint) -> Self {
+        Self {
+            txid: out_point.txid.0,
+            vout: out_point.vout,
+        }
+    }
+}
+
 impl From<BdkTxid> for TxId {
     fn from(txid: BdkTxid) -> Self {
         Self(txid)
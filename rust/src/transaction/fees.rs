@@ -50,6 +50,28 @@ impl FeeRate {
     }
 }
 
+// MARK: CPFP
+
+/// Fee a child transaction has to pay so the parent + child package reaches `target`
+///
+/// The child always pays at least the target rate for its own size, even if the parent
+/// already pays more than the target
+pub fn cpfp_child_fee(
+    parent_fee: bitcoin_units::Amount,
+    parent_vsize: u64,
+    child_vsize: u64,
+    target: BdkFeeRate,
+) -> Option<bitcoin_units::Amount> {
+    let package_fee = target.fee_vb(parent_vsize.checked_add(child_vsize)?)?;
+    let child_only_fee = target.fee_vb(child_vsize)?;
+
+    let child_fee = package_fee
+        .checked_sub(parent_fee)
+        .unwrap_or(bitcoin_units::Amount::ZERO);
+
+    Some(child_fee.max(child_only_fee))
+}
+
 // MARK: FeeRateOptions
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, uniffi::Object)]
@@ -423,3 +445,27 @@ fn fee_rate_options_with_total_fee_is_equal(
 ) -> bool {
     lhs == rhs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin_units::Amount as BdkAmount;
+
+    #[test]
+    fn test_cpfp_child_pays_for_parent() {
+        let target = BdkFeeRate::from_sat_per_vb(10).unwrap();
+
+        // parent 200 vb at 1 sat/vb, child 110 vb, package needs 3100 sats
+        let child_fee = cpfp_child_fee(BdkAmount::from_sat(200), 200, 110, target).unwrap();
+        assert_eq!(child_fee, BdkAmount::from_sat(2900));
+    }
+
+    #[test]
+    fn test_cpfp_child_pays_at_least_target() {
+        let target = BdkFeeRate::from_sat_per_vb(10).unwrap();
+
+        // parent already pays well over the target
+        let child_fee = cpfp_child_fee(BdkAmount::from_sat(50_000), 200, 110, target).unwrap();
+        assert_eq!(child_fee, BdkAmount::from_sat(1100));
+    }
+}
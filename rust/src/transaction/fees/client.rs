@@ -35,6 +35,12 @@ impl FeeClient {
         }
     }
 
+    /// Goes through the proxy when one is set
+    fn client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        let client = crate::proxy::http_client()?.unwrap_or_else(|| self.client.clone());
+        Ok(client)
+    }
+
     /// Get fees from the memory cache if it exists and is less than 60 seconds old
     /// otherwise get the new fees from the server
     pub async fn get_fees(&self) -> Result<FeeResponse, reqwest::Error> {
@@ -53,7 +59,7 @@ impl FeeClient {
 
     /// Always gets new fees from the server
     async fn get_new_fees(&self) -> Result<FeeResponse, reqwest::Error> {
-        let response = self.client.get(&self.url).send().await?;
+        let response = self.client()?.get(&self.url).send().await?;
         let fees: FeeResponse = response.json().await?;
         Ok(fees)
     }
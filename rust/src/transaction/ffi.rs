@@ -131,6 +131,30 @@ impl TxId {
     }
 }
 
+#[uniffi::export]
+impl OutPoint {
+    #[uniffi::method]
+    pub fn txid(&self) -> TxId {
+        self.txid
+    }
+
+    #[uniffi::method]
+    pub fn vout(&self) -> u32 {
+        self.vout
+    }
+
+    /// `txid:vout`
+    #[uniffi::method]
+    pub fn as_string(&self) -> String {
+        BdkOutPoint::from(self).to_string()
+    }
+
+    #[uniffi::method]
+    pub fn is_equal(&self, other: Arc<OutPoint>) -> bool {
+        self == other.as_ref()
+    }
+}
+
 #[uniffi::export]
 impl ConfirmedTransaction {
     #[uniffi::method]
@@ -143,9 +167,17 @@ impl ConfirmedTransaction {
         self.block_height
     }
 
+    /// The user's label, or a generated one when the user hasn't set one
     #[uniffi::method]
     pub fn label(&self) -> String {
-        self.sent_and_received.label()
+        self.user_label
+            .clone()
+            .unwrap_or_else(|| self.sent_and_received.label())
+    }
+
+    #[uniffi::method]
+    pub fn user_label(&self) -> Option<String> {
+        self.user_label.clone()
     }
 
     #[uniffi::method]
@@ -201,13 +233,31 @@ impl UnconfirmedTransaction {
         self.last_seen
     }
 
+    /// Only transactions we sent, that signal RBF, can be replaced with a higher fee
+    #[uniffi::method]
+    pub fn can_bump_fee(&self) -> bool {
+        self.signals_rbf && self.sent_and_received.direction() == TransactionDirection::Outgoing
+    }
+
+    /// Incoming transactions can be sped up by spending their output with a higher fee (CPFP),
+    /// as long as we know their fee and still have an output of theirs to spend
+    #[uniffi::method]
+    pub fn can_speed_up_incoming(&self) -> bool {
+        self.can_cpfp && self.sent_and_received.direction() == TransactionDirection::Incoming
+    }
+
     #[uniffi::method]
     pub fn sent_and_received(&self) -> SentAndReceived {
         self.sent_and_received
     }
 
+    /// The user's label, or a generated one when the user hasn't set one
     #[uniffi::method]
     pub fn label(&self) -> String {
+        if let Some(user_label) = &self.user_label {
+            return user_label.clone();
+        }
+
         match &self.sent_and_received.direction {
             TransactionDirection::Incoming => "Receiving",
             TransactionDirection::Outgoing => "Sending",
@@ -215,6 +265,11 @@ impl UnconfirmedTransaction {
         .to_string()
     }
 
+    #[uniffi::method]
+    pub fn user_label(&self) -> Option<String> {
+        self.user_label.clone()
+    }
+
     #[uniffi::method]
     pub fn fiat_amount(&self) -> Option<FiatAmount> {
         self.fiat
@@ -254,6 +309,7 @@ fn transaction_preview_confirmed_new() -> Transaction {
         confirmed_at: jiff::Timestamp::now(),
         sent_and_received: SentAndReceived::preview_new(),
         fiat: Some(FiatAmount::preview_new()),
+        user_label: None,
     };
 
     Transaction::Confirmed(Arc::new(txn))
@@ -319,5 +375,8 @@ fn transaction_preview_unconfirmed_new() -> Transaction {
         sent_and_received: SentAndReceived::preview_new(),
         last_seen,
         fiat: None,
+        signals_rbf: true,
+        can_cpfp: true,
+        user_label: None,
     }))
 }
@@ -45,6 +45,7 @@ pub struct TransactionDetails {
     pub fee: Option<Amount>,
     pub fee_rate: Option<FeeRate>,
     pub pending_or_confirmed: PendingOrConfirmed,
+    pub signals_rbf: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Enum)]
@@ -76,6 +77,7 @@ impl TransactionDetails {
             fee,
             pending_or_confirmed,
             fee_rate,
+            signals_rbf: tx_details.is_explicitly_rbf(),
         };
 
         Ok(me)
@@ -224,6 +226,12 @@ impl TransactionDetails {
         self.pending_or_confirmed.is_confirmed()
     }
 
+    /// Pending transactions we sent, that signal RBF, can be replaced with a higher fee
+    #[uniffi::method]
+    pub fn can_bump_fee(&self) -> bool {
+        self.signals_rbf && self.is_sent() && !self.is_confirmed()
+    }
+
     #[uniffi::method]
     pub fn confirmation_date_time(&self) -> Option<String> {
         let confirm_time = match &self.pending_or_confirmed {
@@ -295,6 +303,7 @@ impl TransactionDetails {
                 block_number: 840_000,
                 confirmation_time: 1677721600,
             }),
+            signals_rbf: true,
         }
     }
     #[uniffi::constructor(name = "preview_confirmed_received")]
@@ -13,6 +13,9 @@ pub struct UnsignedTransaction {
     pub tx_id: TxId,
     pub confirm_details: ConfirmDetails,
     pub created_at: u64,
+    /// The user's label for the transaction, looked up from the wallet's labels when listed
+    #[serde(skip)]
+    pub user_label: Option<String>,
 }
 
 #[uniffi::export]
@@ -21,8 +24,15 @@ impl UnsignedTransaction {
         self.tx_id
     }
 
+    /// The user's label, or a generated one when the user hasn't set one
     pub fn label(&self) -> String {
-        "Sending".to_string()
+        self.user_label
+            .clone()
+            .unwrap_or_else(|| "Sending".to_string())
+    }
+
+    pub fn user_label(&self) -> Option<String> {
+        self.user_label.clone()
     }
 
     pub fn details(&self) -> ConfirmDetails {
@@ -45,6 +55,7 @@ impl From<UnsignedTransactionRecord> for UnsignedTransaction {
             tx_id: record.tx_id,
             confirm_details: record.confirm_details,
             created_at: record.created_at,
+            user_label: None,
         }
     }
 }
@@ -70,6 +81,7 @@ impl UnsignedTransaction {
             tx_id: TxId::preview_new(),
             confirm_details: ConfirmDetails::preview_new(38588),
             created_at: jiff::Timestamp::now().as_second() as u64,
+            user_label: None,
         }
     }
 }
@@ -0,0 +1,675 @@
+//! Uniform Resources (BCR-2020-005), the animated QR format used by Keystone, Passport, Jade and
+//! SeedSigner. Decoded payloads are converted into the formats the rest of the app already
+//! parses, ex: a crypto-account becomes an output descriptor and a crypto-psbt the psbt bytes.
+
+mod cbor;
+
+use std::collections::HashSet;
+
+use bitcoin::{
+    bip32::{ChainCode, ChildNumber, Fingerprint, Xpub},
+    secp256k1::PublicKey,
+    NetworkKind,
+};
+
+use crate::multi_format::StringOrData;
+use cbor::Value;
+
+/// Max bytes of the message in each part of an animated UR
+const MAX_FRAGMENT_LENGTH: usize = 200;
+
+// registry tags, see BCR-2020-006
+const TAG_HDKEY: u64 = 303;
+const TAG_OUTPUT: u64 = 308;
+
+// script expression tags, see BCR-2020-010
+const TAG_SH: u64 = 400;
+const TAG_WSH: u64 = 401;
+const TAG_PKH: u64 = 403;
+const TAG_WPKH: u64 = 404;
+const TAG_MULTI: u64 = 406;
+const TAG_SORTED_MULTI: u64 = 407;
+const TAG_TR: u64 = 409;
+const TAG_COSIGNER: u64 = 410;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrType {
+    CryptoPsbt,
+    CryptoAccount,
+    CryptoOutput,
+    CryptoHdkey,
+    CryptoSeed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum UrError {
+    #[error("Not a valid UR: {0}")]
+    InvalidUr(String),
+
+    #[error("Unsupported UR type: {0}, only crypto-psbt, crypto-account, crypto-output, crypto-hdkey and crypto-seed are supported")]
+    UnsupportedType(String),
+
+    #[error("Unable to decode UR: {0}")]
+    DecodeError(String),
+
+    #[error("Unable to encode UR: {0}")]
+    EncodeError(String),
+
+    #[error("Invalid CBOR in UR: {0}")]
+    InvalidCbor(String),
+
+    #[error("Missing {0} in UR")]
+    MissingField(String),
+
+    #[error("Unsupported UR contents: {0}")]
+    UnsupportedContents(String),
+
+    #[error("Part is for a different UR, expected {expected}, found {found}")]
+    DifferentUr { expected: String, found: String },
+}
+
+type Error = UrError;
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Result of adding a part to an animated UR
+#[derive(Debug, Clone)]
+pub enum UrJoinResult {
+    InProgress { parts_left: u32 },
+    Complete(StringOrData),
+}
+
+/// Collects the parts of an animated UR, the parts can be scanned in any order
+pub struct UrJoiner {
+    ur_type: UrType,
+    total_parts: u32,
+    received: HashSet<u32>,
+    decoder: ur::Decoder,
+}
+
+/// A single scanned QR, `ur:<type>/<payload>` or `ur:<type>/<seq>-<total>/<payload>`
+struct Part {
+    ur: String,
+    ur_type: UrType,
+    sequence: Option<(u32, u32)>,
+}
+
+pub fn is_ur(qr: &str) -> bool {
+    qr.trim()
+        .get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+}
+
+/// Check if the QR is one part of an animated UR, these need to be joined with a [`UrJoiner`]
+pub fn is_multi_part(qr: &str) -> bool {
+    Part::try_from_str(qr).is_ok_and(|part| part.sequence.is_some())
+}
+
+/// Decode a single part UR
+pub fn decode(qr: &str) -> Result<StringOrData> {
+    let part = Part::try_from_str(qr)?;
+    if part.sequence.is_some() {
+        let error = "this is one part of an animated UR, scan all the parts".to_string();
+        return Err(UrError::InvalidUr(error));
+    }
+
+    let (_kind, message) =
+        ur::decode(&part.ur).map_err(|error| UrError::DecodeError(error.to_string()))?;
+
+    decode_message(part.ur_type, &message)
+}
+
+/// Split a psbt into the parts of an animated crypto-psbt UR
+pub fn encode_psbt(psbt: &[u8]) -> Result<Vec<String>> {
+    let message = cbor::encode_bytes(psbt);
+    let mut encoder = ur::Encoder::new(&message, MAX_FRAGMENT_LENGTH, "crypto-psbt")
+        .map_err(|error| UrError::EncodeError(error.to_string()))?;
+
+    // uppercase fits in the denser alphanumeric QR mode
+    (0..encoder.fragment_count())
+        .map(|_| {
+            let part = encoder
+                .next_part()
+                .map_err(|error| UrError::EncodeError(error.to_string()))?;
+
+            Ok(part.to_ascii_uppercase())
+        })
+        .collect()
+}
+
+impl UrType {
+    fn try_from_str(ur_type: &str) -> Result<Self> {
+        match ur_type {
+            "crypto-psbt" | "psbt" => Ok(Self::CryptoPsbt),
+            "crypto-account" => Ok(Self::CryptoAccount),
+            "crypto-output" => Ok(Self::CryptoOutput),
+            "crypto-hdkey" => Ok(Self::CryptoHdkey),
+            "crypto-seed" => Ok(Self::CryptoSeed),
+            other => Err(UrError::UnsupportedType(other.to_string())),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::CryptoPsbt => "crypto-psbt",
+            Self::CryptoAccount => "crypto-account",
+            Self::CryptoOutput => "crypto-output",
+            Self::CryptoHdkey => "crypto-hdkey",
+            Self::CryptoSeed => "crypto-seed",
+        }
+    }
+}
+
+impl Part {
+    fn try_from_str(qr: &str) -> Result<Self> {
+        let ur = qr.trim().to_ascii_lowercase();
+        let Some(rest) = ur.strip_prefix("ur:") else {
+            return Err(UrError::InvalidUr("missing ur: prefix".to_string()));
+        };
+
+        let components = rest.split('/').collect::<Vec<&str>>();
+        let (ur_type, sequence) = match components.as_slice() {
+            [ur_type, _payload] => (UrType::try_from_str(ur_type)?, None),
+            [ur_type, sequence, _payload] => {
+                let ur_type = UrType::try_from_str(ur_type)?;
+                (ur_type, Some(parse_sequence(sequence)?))
+            }
+            _ => {
+                return Err(UrError::InvalidUr(
+                    "unexpected number of components".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            ur,
+            ur_type,
+            sequence,
+        })
+    }
+}
+
+fn parse_sequence(sequence: &str) -> Result<(u32, u32)> {
+    let invalid = || UrError::InvalidUr(format!("invalid sequence: {sequence}"));
+
+    let (index, total) = sequence.split_once('-').ok_or_else(invalid)?;
+    let index = index.parse::<u32>().map_err(|_| invalid())?;
+    let total = total.parse::<u32>().map_err(|_| invalid())?;
+
+    if index == 0 || total == 0 {
+        return Err(invalid());
+    }
+
+    Ok((index, total))
+}
+
+impl UrJoiner {
+    pub fn try_new(qr: &str) -> Result<Self> {
+        let part = Part::try_from_str(qr)?;
+        let Some((_, total_parts)) = part.sequence else {
+            return Err(UrError::InvalidUr("not an animated UR".to_string()));
+        };
+
+        Ok(Self {
+            ur_type: part.ur_type,
+            total_parts,
+            received: HashSet::new(),
+            decoder: ur::Decoder::default(),
+        })
+    }
+
+    pub fn total_parts(&self) -> u32 {
+        self.total_parts
+    }
+
+    pub fn add_part(&mut self, qr: &str) -> Result<UrJoinResult> {
+        let part = Part::try_from_str(qr)?;
+        let Some((index, total_parts)) = part.sequence else {
+            return Err(UrError::InvalidUr("not an animated UR".to_string()));
+        };
+
+        if part.ur_type != self.ur_type || total_parts != self.total_parts {
+            return Err(UrError::DifferentUr {
+                expected: format!("{} in {} parts", self.ur_type.as_str(), self.total_parts),
+                found: format!("{} in {total_parts} parts", part.ur_type.as_str()),
+            });
+        }
+
+        self.decoder
+            .receive(&part.ur)
+            .map_err(|error| UrError::DecodeError(error.to_string()))?;
+
+        // parts after the total are mixes of several fragments, only count the original ones
+        if index <= self.total_parts {
+            self.received.insert(index);
+        }
+
+        if !self.decoder.complete() {
+            let parts_left = self
+                .total_parts
+                .saturating_sub(self.received.len() as u32)
+                .max(1);
+
+            return Ok(UrJoinResult::InProgress { parts_left });
+        }
+
+        let message = self
+            .decoder
+            .message()
+            .map_err(|error| UrError::DecodeError(error.to_string()))?
+            .ok_or_else(|| UrError::DecodeError("complete but no message".to_string()))?;
+
+        let result = decode_message(self.ur_type, &message)?;
+        Ok(UrJoinResult::Complete(result))
+    }
+}
+
+fn decode_message(ur_type: UrType, message: &[u8]) -> Result<StringOrData> {
+    let value = Value::decode(message).map_err(|error| UrError::InvalidCbor(error.to_string()))?;
+
+    let result = match ur_type {
+        UrType::CryptoPsbt => {
+            let psbt = value.as_bytes().ok_or_else(|| missing("psbt"))?;
+            StringOrData::Data(psbt.to_vec())
+        }
+
+        UrType::CryptoSeed => {
+            let entropy = value
+                .get(1)
+                .and_then(Value::as_bytes)
+                .ok_or_else(|| missing("seed payload"))?;
+
+            let mnemonic = bip39::Mnemonic::from_entropy(entropy)
+                .map_err(|error| UrError::UnsupportedContents(error.to_string()))?;
+
+            StringOrData::String(mnemonic.to_string())
+        }
+
+        UrType::CryptoHdkey => StringOrData::String(descriptor_from_hdkey(&value)?),
+        UrType::CryptoOutput => StringOrData::String(descriptor_from_output(&value, None)?),
+        UrType::CryptoAccount => StringOrData::String(descriptor_from_account(&value)?),
+    };
+
+    Ok(result)
+}
+
+/// Accounts contain an output for each script type, prefer native segwit, then taproot
+fn descriptor_from_account(account: &Value) -> Result<String> {
+    let master_fingerprint = account.get(1).and_then(Value::as_u64).map(|fp| fp as u32);
+    let outputs = account
+        .get(2)
+        .and_then(Value::as_array)
+        .ok_or_else(|| missing("account outputs"))?;
+
+    let priority = |descriptor: &String| {
+        ["wpkh(", "tr(", "sh(wpkh(", "pkh("]
+            .iter()
+            .position(|prefix| descriptor.starts_with(prefix))
+            .unwrap_or(usize::MAX)
+    };
+
+    outputs
+        .iter()
+        .filter_map(|output| descriptor_from_output(output, master_fingerprint).ok())
+        .min_by_key(priority)
+        .ok_or_else(|| UrError::UnsupportedContents("no single sig outputs in account".into()))
+}
+
+fn descriptor_from_output(output: &Value, master_fingerprint: Option<u32>) -> Result<String> {
+    // embedded outputs are tagged, top level outputs start at the script expression
+    let output = match output {
+        Value::Tag(TAG_OUTPUT, output) => output.as_ref(),
+        output => output,
+    };
+
+    let key = |inner: &Value| key_expression(inner, master_fingerprint);
+
+    let descriptor = match output {
+        Value::Tag(TAG_WPKH, hdkey) => format!("wpkh({})", key(hdkey)?),
+        Value::Tag(TAG_TR, hdkey) => format!("tr({})", key(hdkey)?),
+        Value::Tag(TAG_PKH, hdkey) => format!("pkh({})", key(hdkey)?),
+        Value::Tag(TAG_SH, inner) => match inner.as_ref() {
+            Value::Tag(TAG_WPKH, hdkey) => format!("sh(wpkh({}))", key(hdkey)?),
+            _ => return Err(UrError::UnsupportedContents("sh output".to_string())),
+        },
+        Value::Tag(TAG_WSH | TAG_MULTI | TAG_SORTED_MULTI | TAG_COSIGNER, _) => {
+            let error = "multisig outputs, import the descriptor instead".to_string();
+            return Err(UrError::UnsupportedContents(error));
+        }
+        other => {
+            let error = format!("output with tag {:?}", other.tag());
+            return Err(UrError::UnsupportedContents(error));
+        }
+    };
+
+    Ok(descriptor)
+}
+
+/// A standalone key, the script type comes from the purpose in its origin path
+fn descriptor_from_hdkey(hdkey: &Value) -> Result<String> {
+    let key = key_expression(hdkey, None)?;
+
+    let purpose = hdkey
+        .get(6)
+        .map(keypath)
+        .transpose()?
+        .and_then(|path| path.first().copied());
+
+    let descriptor = match purpose {
+        Some(ChildNumber::Hardened { index: 44 }) => format!("pkh({key})"),
+        Some(ChildNumber::Hardened { index: 49 }) => format!("sh(wpkh({key}))"),
+        Some(ChildNumber::Hardened { index: 86 }) => format!("tr({key})"),
+        _ => format!("wpkh({key})"),
+    };
+
+    Ok(descriptor)
+}
+
+/// `[fingerprint/origin]xpub/<0;1>/*` from a crypto-hdkey
+fn key_expression(hdkey: &Value, master_fingerprint: Option<u32>) -> Result<String> {
+    if hdkey.tag().is_some_and(|tag| tag != TAG_HDKEY) {
+        return Err(UrError::UnsupportedContents(
+            "key is not a crypto-hdkey".to_string(),
+        ));
+    }
+
+    if hdkey.get(2).and_then(Value::as_bool) == Some(true) {
+        return Err(UrError::UnsupportedContents("private keys".to_string()));
+    }
+
+    let key_data = hdkey
+        .get(3)
+        .and_then(Value::as_bytes)
+        .ok_or_else(|| missing("key data"))?;
+
+    let chain_code = hdkey
+        .get(4)
+        .and_then(Value::as_bytes)
+        .ok_or_else(|| missing("chain code"))?;
+
+    let origin = hdkey.get(6);
+    let path = origin.map(keypath).transpose()?.unwrap_or_default();
+
+    let fingerprint = origin
+        .and_then(|origin| origin.get(2))
+        .and_then(Value::as_u64)
+        .map(|fp| fp as u32)
+        .or(master_fingerprint)
+        .ok_or_else(|| missing("source fingerprint"))?;
+
+    // use-info, network 1 is testnet
+    let network = match hdkey
+        .get(5)
+        .and_then(|info| info.get(2))
+        .and_then(Value::as_u64)
+    {
+        Some(1) => NetworkKind::Test,
+        _ => NetworkKind::Main,
+    };
+
+    let parent_fingerprint = hdkey
+        .get(8)
+        .and_then(Value::as_u64)
+        .map(|fp| Fingerprint::from((fp as u32).to_be_bytes()))
+        .unwrap_or_default();
+
+    let xpub = Xpub {
+        network,
+        depth: path.len() as u8,
+        parent_fingerprint,
+        child_number: path
+            .last()
+            .copied()
+            .unwrap_or(ChildNumber::Normal { index: 0 }),
+        public_key: PublicKey::from_slice(key_data)
+            .map_err(|error| UrError::UnsupportedContents(error.to_string()))?,
+        chain_code: ChainCode::try_from(chain_code)
+            .map_err(|_| UrError::UnsupportedContents("invalid chain code".to_string()))?,
+    };
+
+    let origin = path
+        .iter()
+        .map(|child| match child {
+            ChildNumber::Hardened { index } => format!("/{index}h"),
+            ChildNumber::Normal { index } => format!("/{index}"),
+        })
+        .collect::<String>();
+
+    Ok(format!("[{fingerprint:08x}{origin}]{xpub}/<0;1>/*"))
+}
+
+/// The components of a crypto-keypath, pairs of index and hardened
+fn keypath(keypath: &Value) -> Result<Vec<ChildNumber>> {
+    let components = keypath
+        .get(1)
+        .and_then(Value::as_array)
+        .ok_or_else(|| missing("keypath components"))?;
+
+    components
+        .chunks(2)
+        .map(|component| {
+            let [index, hardened] = component else {
+                return Err(UrError::UnsupportedContents(
+                    "keypath component".to_string(),
+                ));
+            };
+
+            let index = index
+                .as_u64()
+                .and_then(|index| u32::try_from(index).ok())
+                .ok_or_else(|| UrError::UnsupportedContents("keypath wildcards".to_string()))?;
+
+            let child = if hardened.as_bool().unwrap_or(false) {
+                ChildNumber::from_hardened_idx(index)
+            } else {
+                ChildNumber::from_normal_idx(index)
+            };
+
+            child.map_err(|error| UrError::UnsupportedContents(error.to_string()))
+        })
+        .collect()
+}
+
+fn missing(field: &str) -> UrError {
+    UrError::MissingField(field.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BCR-2020-015 example, "shield group erode awake lock sausage cash glare wave crew flame glove"
+    const BCR_2020_015_ACCOUNT: &str = concat!(
+        "ur:crypto-account/oeadcyemrewytyaolttaadmutaaddloxaxhdclaxwmfmdeiamecsdsemgtvsjzcncygrko",
+        "wtrontzschgezokstswkkscfmklrtauteyaahdcxiehfonurdppfyntapejpproypegrdawkgmaewejlsfdtsrfy",
+        "bdehcaflmtrlbdhpamtaaddyoeadlncsdwykaeykaeykaocyemrewytyaycynlytsnyltaadmhtaadmwtaaddlox",
+        "axhdclaostvelfemdyynwydwyaievosrgmambklovabdgypdglldvespsthysadamhpmjeinaahdcxntdllnaaey",
+        "koytdacygegwhgjsiyonpywmcmrpwphsvodsrerozsbyaxluzcoxdpamtaaddyoeadlncsehykaeykaeykaocyem",
+        "rewytyaycypdbskeuytaadmwtaaddloxaxhdclaxzcfxeegdrpmogrgwkbzctlttweadkiengrwlhtprremouolu",
+        "utqdpfbncedkynfhaahdcxjpwevdeogthttkmeswzcolcpsaahcfnshkhtehytclmnteatmoteadtlwynnftloam",
+        "taaddyoeadlncsghykaeykaeykaocyemrewytyaycybthlvytstaadmhtaadnytaaddloxaxhdclaxhhsnhdrpft",
+        "dwuocntilydibehnecmovdfekpjkclcslasbhkpawsaddmcmmnahnyaahdcxlotedtndfymyltclhlmtpfsadscn",
+        "htztaolbnnkistaedegwfmmedreetnwmcycnamtaaddyoeadlfcsdpykaocyemrewytyaycyemrewytytaadmhta",
+        "admetaadnytaaddloxaxhdclaxdwkswmztpytnswtsecnblfbayajkdldeclqzzolrsnhljedsgminetytbnahat",
+        "byaahdcxkkguwsvyimjkvwteytwztyswvendtpmncpasfrrylprnhtkblndrgrmkoyjtbkrpamtaaddyoeadlocs",
+        "dyykaeykaeykadykaocyemrewytyaycyhkrpnddrtaadmetaadnytaaddloxaxhdclaohnhffmvsbndslrfgclpf",
+        "jejyatbdpebacnzokotofxntaoemvskpaowmryfnotfgaahdcxdlnbvecentssfsssgylnhkrstoytecrdlyadre",
+        "kirfaybglahltalsrfcaeerobwamtaaddyoeadlocsdyykaeykaeykaoykaocyemrewytyaycyhkrpnddrtaadnl",
+        "taaddloxaxhdclaorkrhkeytwsoykorletwstbwycagtbsotmeptjkesgwrfcmveskvdmngujzttgtdpaahdcxgr",
+        "fgmuvyylmwcxjtttechplslgoegagaptdniatidmhdmebdwfryfsnsdkcplyvaamtaaddyoeadlncshfykaeykae",
+        "ykaocyemrewytyaycytostatbntyoycwrk",
+    );
+
+    // the taproot output of the BCR-2020-015 account as a crypto-output
+    const BCR_2020_015_TR_OUTPUT: &str = concat!(
+        "ur:crypto-output/taadnltaaddloxaxhdclaorkrhkeytwsoykorletwstbwycagtbsotmeptjkesgwrfcmves",
+        "kvdmngujzttgtdpaahdcxgrfgmuvyylmwcxjtttechplslgoegagaptdniatidmhdmebdwfryfsnsdkcplyvaamt",
+        "aaddyoeadlncshfykaeykaeykaocyemrewytyaycytostatbnlfuejsvs",
+    );
+
+    // the nested segwit output of the BCR-2020-015 account as a crypto-output
+    const BCR_2020_015_SH_WPKH_OUTPUT: &str = concat!(
+        "ur:crypto-output/taadmhtaadmwtaaddloxaxhdclaostvelfemdyynwydwyaievosrgmambklovabdgypdgll",
+        "dvespsthysadamhpmjeinaahdcxntdllnaaeykoytdacygegwhgjsiyonpywmcmrpwphsvodsrerozsbyaxluzco",
+        "xdpamtaaddyoeadlncsehykaeykaeykaocyemrewytyaycypdbskeuyjteocmzt",
+    );
+
+    // the native segwit key of the BCR-2020-015 account as a crypto-hdkey
+    const BCR_2020_015_WPKH_HDKEY: &str = concat!(
+        "ur:crypto-hdkey/oxaxhdclaxzcfxeegdrpmogrgwkbzctlttweadkiengrwlhtprremouoluutqdpfbncedkyn",
+        "fhaahdcxjpwevdeogthttkmeswzcolcpsaahcfnshkhtehytclmnteatmoteadtlwynnftloamtaaddyoeadlncs",
+        "ghykaeykaeykaocyemrewytyaycybthlvytsmumkmykn",
+    );
+
+    // BCR-2020-007 example 2, a testnet key without a source fingerprint
+    const BCR_2020_007_HDKEY: &str = concat!(
+        "ur:crypto-hdkey/onaxhdclaojlvoechgferkdpqdiabdrflawshlhdmdcemtfnlrctghchbdolvwsednvdztbg",
+        "olaahdcxtottgostdkhfdahdlykkecbbweskrymwflvdylgerkloswtbrpfdbsticmwylklpahtaadehoyaoadam",
+        "taaddyoyadlecsdwykadykadykaewkadwkaycywlcscewfihbdaehn",
+    );
+
+    // BCR-2020-010 example 1, pkh of a bare crypto-eckey
+    const BCR_2020_010_PKH_ECKEY: &str = concat!(
+        "ur:crypto-output/taadmutaadeyoyaxhdclaoswaalbmwfpwekijndyfefzjtmdrtketphhktmngrlkwsfnosp",
+        "ypsasrhhhjonnvwtsqzwljy",
+    );
+
+    const XPUB_44: &str = "xpub6CnQkivUEH9bSbWVWfDLCtigKKgnSWGaVSRyCbN2QNBJzuvHT1vUQpgSpY1NiVvoeNEuVwk748Cn9G3NtbQB1aGGsEL7aYEnjVWgjj9tefu";
+    const XPUB_49: &str = "xpub6CtR1iF4dZPkEyXDwVf3HE74tSwXNMcHtBzX4gwz2UnPhJ54Jz5unHx2syYCCDkvVUmsmoYTmcaHXe1wJppvct4GMMaN5XAbRk7yGScRSte";
+    const XPUB_84: &str = "xpub6BkU445MSEBXbPjD3g2c2ch6mn8yy1SXXQUM7EwjgYiq6Wt1NDwDZ45npqWcV8uQC5oi2gHuVukoCoZZyT4HKq8EpotPMqGqxdZRuapCQ23";
+    const XPUB_86: &str = "xpub6DAvL2L5bgGSpDygSQUDpjwE47saoMk2rSRtYhN7Dma7HvnFLTXNrcSC1AmEN8G2SCD958bUwgc6Bew4sAFa2kqYynF8Rmu6P5jMt2FDPtm";
+
+    fn descriptor(qr: &str) -> String {
+        match decode(qr).unwrap() {
+            StringOrData::String(descriptor) => descriptor,
+            StringOrData::Data(_) => panic!("expected a descriptor"),
+        }
+    }
+
+    #[test]
+    fn test_bcr_crypto_account_prefers_native_segwit() {
+        assert_eq!(
+            descriptor(BCR_2020_015_ACCOUNT),
+            format!("wpkh([37b5eed4/84h/0h/0h]{XPUB_84}/<0;1>/*)")
+        );
+    }
+
+    #[test]
+    fn test_bcr_crypto_account_outputs() {
+        let (_kind, message) = ur::decode(BCR_2020_015_ACCOUNT).unwrap();
+        let account = Value::decode(&message).unwrap();
+        let outputs = account.get(2).and_then(Value::as_array).unwrap();
+        assert_eq!(outputs.len(), 7);
+
+        let descriptors = outputs
+            .iter()
+            .map(|output| descriptor_from_output(output, Some(0x37b5eed4)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            descriptors[0],
+            Ok(format!("pkh([37b5eed4/44h/0h/0h]{XPUB_44}/<0;1>/*)"))
+        );
+        assert_eq!(
+            descriptors[1],
+            Ok(format!("sh(wpkh([37b5eed4/49h/0h/0h]{XPUB_49}/<0;1>/*))"))
+        );
+        assert_eq!(
+            descriptors[2],
+            Ok(format!("wpkh([37b5eed4/84h/0h/0h]{XPUB_84}/<0;1>/*)"))
+        );
+        assert_eq!(
+            descriptors[6],
+            Ok(format!("tr([37b5eed4/86h/0h/0h]{XPUB_86}/<0;1>/*)"))
+        );
+
+        // sh(cosigner), sh(wsh(cosigner)) and wsh(cosigner) are multisig
+        for multisig in &descriptors[3..6] {
+            assert!(matches!(multisig, Err(UrError::UnsupportedContents(_))));
+        }
+    }
+
+    #[test]
+    fn test_bcr_crypto_output() {
+        assert_eq!(
+            descriptor(BCR_2020_015_TR_OUTPUT),
+            format!("tr([37b5eed4/86h/0h/0h]{XPUB_86}/<0;1>/*)")
+        );
+        assert_eq!(
+            descriptor(BCR_2020_015_SH_WPKH_OUTPUT),
+            format!("sh(wpkh([37b5eed4/49h/0h/0h]{XPUB_49}/<0;1>/*))")
+        );
+
+        // bare keys have no xpub to derive addresses from
+        assert!(matches!(
+            decode(BCR_2020_010_PKH_ECKEY),
+            Err(UrError::UnsupportedContents(_))
+        ));
+    }
+
+    #[test]
+    fn test_bcr_crypto_hdkey() {
+        assert_eq!(
+            descriptor(BCR_2020_015_WPKH_HDKEY),
+            format!("wpkh([37b5eed4/84h/0h/0h]{XPUB_84}/<0;1>/*)")
+        );
+
+        // hardware wallets can't sign for keys without a source fingerprint
+        assert_eq!(
+            decode(BCR_2020_007_HDKEY).unwrap_err(),
+            UrError::MissingField("source fingerprint".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cbor_bytes_roundtrip() {
+        let data = vec![7u8; 300];
+        let encoded = cbor::encode_bytes(&data);
+
+        assert_eq!(&encoded[..3], &[0x59, 0x01, 0x2c]);
+        assert_eq!(Value::decode(&encoded).unwrap(), Value::Bytes(data));
+    }
+
+    #[test]
+    fn test_crypto_seed_to_mnemonic() {
+        // {1: h'00000000000000000000000000000000'}
+        let mut message = vec![0xa1, 0x01, 0x50];
+        message.extend_from_slice(&[0; 16]);
+
+        let words = match decode_message(UrType::CryptoSeed, &message).unwrap() {
+            StringOrData::String(words) => words,
+            StringOrData::Data(_) => panic!("expected words"),
+        };
+
+        assert!(words.starts_with("abandon abandon"));
+        assert!(words.ends_with("about"));
+    }
+
+    #[test]
+    fn test_animated_psbt_roundtrip() {
+        let psbt = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+        let parts = encode_psbt(&psbt).unwrap();
+        assert!(parts.len() > 1);
+        assert!(is_multi_part(&parts[0]));
+
+        let mut joiner = UrJoiner::try_new(&parts[0]).unwrap();
+
+        // scanned out of order
+        let result = parts
+            .iter()
+            .rev()
+            .map(|part| joiner.add_part(part).unwrap())
+            .find(|result| matches!(result, UrJoinResult::Complete(_)));
+
+        match result {
+            Some(UrJoinResult::Complete(StringOrData::Data(data))) => assert_eq!(data, psbt),
+            other => panic!("expected the psbt, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        assert_eq!(parse_sequence("3-10").unwrap(), (3, 10));
+        assert!(parse_sequence("0-10").is_err());
+        assert!(parse_sequence("abc").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Minimal CBOR reader and writer for the UR registry types, only definite lengths are supported
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Unsigned(u64),
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Tag(u64, Box<Value>),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CborError {
+    #[error("unexpected end of data")]
+    UnexpectedEnd,
+
+    #[error("unsupported item, major type {0}, additional info {1}")]
+    Unsupported(u8, u8),
+
+    #[error("text is not valid utf-8")]
+    InvalidText,
+
+    #[error("nested too deeply")]
+    TooDeep,
+
+    #[error("trailing data after the item")]
+    TrailingData,
+}
+
+const MAX_DEPTH: usize = 16;
+
+impl Value {
+    pub fn decode(data: &[u8]) -> Result<Self, CborError> {
+        let mut reader = Reader { data, position: 0 };
+        let value = reader.read_value(0)?;
+
+        if reader.position != data.len() {
+            return Err(CborError::TrailingData);
+        }
+
+        Ok(value)
+    }
+
+    /// The value without any tags around it
+    pub fn untagged(&self) -> &Value {
+        match self {
+            Value::Tag(_, value) => value.untagged(),
+            value => value,
+        }
+    }
+
+    pub fn tag(&self) -> Option<u64> {
+        match self {
+            Value::Tag(tag, _) => Some(*tag),
+            _ => None,
+        }
+    }
+
+    /// Lookup an integer key in a map, all the UR registry types use integer keys
+    pub fn get(&self, key: u64) -> Option<&Value> {
+        let Value::Map(entries) = self.untagged() else {
+            return None;
+        };
+
+        entries
+            .iter()
+            .find(|(entry_key, _)| entry_key == &Value::Unsigned(key))
+            .map(|(_, value)| value)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.untagged() {
+            Value::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self.untagged() {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.untagged() {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self.untagged() {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Encode a byte string, ex: the psbt in a crypto-psbt
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len() + 9);
+    encode_header(2, bytes.len() as u64, &mut encoded);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn encode_header(major: u8, argument: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+
+    match argument {
+        0..=23 => out.push(major | argument as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CborError> {
+        let data = self.data;
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(CborError::UnexpectedEnd)?;
+
+        let bytes = data
+            .get(self.position..end)
+            .ok_or(CborError::UnexpectedEnd)?;
+
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_argument(&mut self, major: u8, info: u8) -> Result<u64, CborError> {
+        let argument = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().expect("took 2 bytes")) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().expect("took 4 bytes")) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().expect("took 8 bytes")),
+            _ => return Err(CborError::Unsupported(major, info)),
+        };
+
+        Ok(argument)
+    }
+
+    fn read_len(&mut self, major: u8, info: u8) -> Result<usize, CborError> {
+        let len = self.read_argument(major, info)?;
+
+        // every item is at least one byte, catches bogus lengths before allocating
+        let len = usize::try_from(len).map_err(|_| CborError::UnexpectedEnd)?;
+        if len > self.data.len() - self.position {
+            return Err(CborError::UnexpectedEnd);
+        }
+
+        Ok(len)
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value, CborError> {
+        if depth > MAX_DEPTH {
+            return Err(CborError::TooDeep);
+        }
+
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        let value = match major {
+            0 => Value::Unsigned(self.read_argument(major, info)?),
+            1 => Value::Negative(self.read_argument(major, info)?),
+            2 => {
+                let len = self.read_len(major, info)?;
+                Value::Bytes(self.take(len)?.to_vec())
+            }
+            3 => {
+                let len = self.read_len(major, info)?;
+                let text =
+                    std::str::from_utf8(self.take(len)?).map_err(|_| CborError::InvalidText)?;
+
+                Value::Text(text.to_string())
+            }
+            4 => {
+                let len = self.read_len(major, info)?;
+                let values = (0..len)
+                    .map(|_| self.read_value(depth + 1))
+                    .collect::<Result<_, _>>()?;
+
+                Value::Array(values)
+            }
+            5 => {
+                let len = self.read_len(major, info)?;
+                let entries = (0..len)
+                    .map(|_| Ok((self.read_value(depth + 1)?, self.read_value(depth + 1)?)))
+                    .collect::<Result<_, CborError>>()?;
+
+                Value::Map(entries)
+            }
+            6 => {
+                let tag = self.read_argument(major, info)?;
+                Value::Tag(tag, Box::new(self.read_value(depth + 1)?))
+            }
+            _ => match info {
+                20 => Value::Bool(false),
+                21 => Value::Bool(true),
+                22 => Value::Null,
+                _ => return Err(CborError::Unsupported(major, info)),
+            },
+        };
+
+        Ok(value)
+    }
+}
@@ -1,9 +1,16 @@
 pub mod address;
+pub mod address_list;
 pub mod balance;
+pub mod bip21;
 pub mod confirm;
+pub mod descriptor;
 pub mod ffi;
 pub mod fingerprint;
+pub mod label;
 pub mod metadata;
+pub mod multisig;
+pub mod safety_check;
+pub mod utxo;
 
 use std::{
     ops::{Deref, DerefMut},
@@ -18,7 +25,7 @@ use crate::{
     keychain::{Keychain, KeychainError},
     keys::Descriptors,
     mnemonic::MnemonicExt as _,
-    multi_format::MultiFormatError,
+    multi_format::{MultiFormatError, StringOrData},
     network::Network,
     xpub::{self, XpubError},
 };
@@ -26,11 +33,13 @@ use balance::Balance;
 use bdk_file_store::Store;
 use bdk_wallet::{
     bitcoin::bip32::Fingerprint as BdkFingerprint, descriptor::ExtendedDescriptor,
-    keys::DescriptorPublicKey, KeychainKind,
+    keys::DescriptorPublicKey, miniscript::ForEachKey as _, KeychainKind,
 };
 use bip39::Mnemonic;
+use descriptor::DescriptorError;
 use fingerprint::Fingerprint;
-use metadata::{DiscoveryState, WalletId, WalletMetadata};
+use metadata::{DiscoveryState, ScanSettings, WalletId, WalletMetadata};
+use multisig::{MultisigError, MultisigInfo};
 use pubport::formats::Format;
 use tracing::{debug, error, warn};
 
@@ -72,6 +81,12 @@ pub enum WalletError {
 
     #[error(transparent)]
     MultiFormatError(#[from] MultiFormatError),
+
+    #[error(transparent)]
+    MultisigError(#[from] MultisigError),
+
+    #[error(transparent)]
+    DescriptorError(#[from] DescriptorError),
 }
 
 #[derive(Debug, uniffi::Object)]
@@ -105,6 +120,7 @@ pub enum WalletAddressType {
     NativeSegwit,
     WrappedSegwit,
     Legacy,
+    Taproot,
 }
 
 impl Wallet {
@@ -119,17 +135,18 @@ impl Wallet {
 
         let create_wallet = || -> Result<Self, WalletError> {
             // create bdk wallet filestore, set id to metadata id
-            let me = Self::try_new_persisted_from_mnemonic_segwit(
+            let me = Self::try_new_persisted_from_mnemonic(
                 metadata.clone(),
                 mnemonic.clone(),
-                passphrase,
+                passphrase.clone(),
+                metadata.address_type,
             )?;
 
-            // save mnemonic for private key
+            // save mnemonic for private key, the passphrase is never saved
             keychain.save_wallet_key(&me.id, mnemonic.clone())?;
 
             // save public key in keychain too
-            let xpub = mnemonic.xpub(me.network.into());
+            let xpub = mnemonic.xpub_with_passphrase(me.network.into(), passphrase.as_deref());
             keychain.save_wallet_xpub(&me.id, xpub)?;
 
             // save wallet_metadata to database
@@ -201,6 +218,13 @@ impl Wallet {
     /// Create a new watch-only wallet from the given xpub
     pub fn try_new_persisted_from_xpub(xpub: String) -> Result<Self, WalletError> {
         let xpub = xpub.trim();
+
+        // ex: a descriptor with a custom derivation path, or a multipath `/<0;1>/*` descriptor,
+        // checked first so the full descriptor is kept, not just the xpub pubport finds in it
+        if descriptor::is_descriptor(xpub) {
+            return Self::try_new_persisted_from_descriptor(xpub);
+        }
+
         let hardware_export = pubport::Format::try_new_from_str(xpub)
             .map_err(Into::into)
             .map_err(WalletError::ParseXpubError);
@@ -209,9 +233,14 @@ impl Wallet {
             return Self::try_new_persisted_from_pubport(hardware_export);
         }
 
-        let xpub = xpub.trim();
-        if xpub.starts_with("UR:") || xpub.starts_with("ur:") {
-            return Err(MultiFormatError::UrFormatNotSupported.into());
+        // ex: a crypto-account UR scanned from the hardware wallet
+        if crate::ur::is_ur(xpub) {
+            let decoded = crate::ur::decode(xpub).map_err(MultiFormatError::from)?;
+            let StringOrData::String(descriptor) = decoded else {
+                return Err(MultiFormatError::UnrecognizedFormat.into());
+            };
+
+            return Self::try_new_persisted_from_xpub(descriptor);
         }
 
         // already returned if its a valid xpub
@@ -249,6 +278,11 @@ impl Wallet {
             Format::Electrum(descriptors) => descriptors,
         };
 
+        // ex: taproot descriptors exported from the hardware wallet
+        if let Some(address_type) = WalletAddressType::from_descriptor(&descriptors.external) {
+            metadata.address_type = address_type;
+        }
+
         let fingerprint = descriptors.fingerprint();
 
         // make sure its not already imported
@@ -314,6 +348,157 @@ impl Wallet {
         })
     }
 
+    /// Create a new watch-only wallet from any public output descriptor
+    ///
+    /// Custom descriptors can share a fingerprint with other wallets from the same device,
+    /// so unlike the other imports, only the same receive descriptor is a duplicate
+    pub fn try_new_persisted_from_descriptor(input: &str) -> Result<Self, WalletError> {
+        let keychain = Keychain::global();
+        let database = Database::global();
+        let network = database.global_config.selected_network();
+        let mode = database.global_config.wallet_mode();
+
+        let descriptors = descriptor::parse_watch_only(input)?;
+        let external = &descriptors.external.extended_descriptor;
+
+        // make sure its not already imported, from a descriptor or any other way
+        let external_descriptor = external.to_string();
+        let existing = database
+            .wallets
+            .get_all(network, mode)?
+            .into_iter()
+            .find(|wallet| {
+                Self::try_load_persisted(wallet.id.clone()).is_ok_and(|other| {
+                    let other = other.bdk.public_descriptor(KeychainKind::External);
+                    other.to_string() == external_descriptor
+                })
+            });
+
+        if let Some(existing) = existing {
+            return Err(WalletError::WalletAlreadyExists(existing.id));
+        }
+
+        let id = WalletId::new();
+        let mut metadata = WalletMetadata::new_with_id(id.clone(), "", None);
+        metadata.master_fingerprint = None;
+
+        if let Some(address_type) = WalletAddressType::from_descriptor(external) {
+            metadata.address_type = address_type;
+        }
+
+        // the scanner only knows the standard address types
+        metadata.discovery_state = DiscoveryState::Single;
+
+        // single key descriptors, multisig descriptors have one xpub per cosigner
+        let mut xpubs = vec![];
+        external.for_each_key(|key| {
+            if let DescriptorPublicKey::XPub(xpub) = key {
+                xpubs.push((key.master_fingerprint(), xpub.xkey));
+            }
+
+            true
+        });
+
+        let xpub = match xpubs.as_slice() {
+            [(fingerprint, xpub)] => {
+                metadata.master_fingerprint = Some(Arc::new((*fingerprint).into()));
+                Some(*xpub)
+            }
+            _ => None,
+        };
+
+        metadata.name = match metadata.master_fingerprint.as_deref() {
+            Some(fingerprint) => format!("Imported {}", fingerprint.as_uppercase()),
+            None => "Imported Descriptor".to_string(),
+        };
+
+        let mut db = Store::<bdk_wallet::ChangeSet>::open_or_create_new(
+            id.to_string().as_bytes(),
+            data_path(&id),
+        )
+        .map_err(|error| WalletError::PersistError(error.to_string()))?;
+
+        let wallet = descriptors
+            .into_create_params()
+            .network(network.into())
+            .create_wallet(&mut db)
+            .map_err(|error| WalletError::BdkError(error.to_string()))?;
+
+        if let Some(xpub) = xpub {
+            keychain.save_wallet_xpub(&id, xpub)?;
+        }
+
+        // save wallet_metadata to database
+        database.wallets.create_wallet(metadata.clone())?;
+
+        Ok(Self {
+            id,
+            metadata,
+            network,
+            bdk: wallet,
+            db,
+        })
+    }
+
+    /// Create a new watch-only multisig wallet, signatures come from the cosigners' devices
+    pub fn try_new_persisted_from_multisig(info: MultisigInfo) -> Result<Self, WalletError> {
+        let database = Database::global();
+        let network = database.global_config.selected_network();
+        let mode = database.global_config.wallet_mode();
+
+        let descriptors = info.descriptors()?;
+        let external = descriptors.external.extended_descriptor.to_string();
+
+        // make sure its not already imported
+        let existing = database
+            .wallets
+            .get_all(network, mode)?
+            .into_iter()
+            .find(|wallet| {
+                let Some(other) = wallet.multisig.as_ref() else {
+                    return false;
+                };
+
+                other
+                    .descriptor(KeychainKind::External)
+                    .is_ok_and(|descriptor| descriptor.to_string() == external)
+            });
+
+        if let Some(existing) = existing {
+            return Err(WalletError::WalletAlreadyExists(existing.id));
+        }
+
+        let id = WalletId::new();
+        let name = format!("Multisig {}-of-{}", info.threshold, info.cosigners.len());
+
+        let mut metadata = WalletMetadata::new_with_id(id.clone(), name, None);
+        metadata.master_fingerprint = None;
+        metadata.multisig = Some(info);
+
+        let mut db = Store::<bdk_wallet::ChangeSet>::open_or_create_new(
+            id.to_string().as_bytes(),
+            data_path(&id),
+        )
+        .map_err(|error| WalletError::PersistError(error.to_string()))?;
+
+        let wallet = descriptors
+            .into_create_params()
+            .network(network.into())
+            .create_wallet(&mut db)
+            .map_err(|error| WalletError::BdkError(error.to_string()))?;
+
+        // save wallet_metadata to database
+        database.wallets.create_wallet(metadata.clone())?;
+
+        Ok(Self {
+            id,
+            metadata,
+            network,
+            bdk: wallet,
+            db,
+        })
+    }
+
     /// The user imported a hww and wants to switch from native segwit to a different address type
     pub fn switch_descriptor_to_new_address_type(
         &mut self,
@@ -412,7 +597,9 @@ impl Wallet {
         )
         .map_err(|error| WalletError::PersistError(error.to_string()))?;
 
-        let descriptors = mnemonic.into_descriptors(passphrase, network, address_type);
+        let account = metadata.account_index;
+        let descriptors =
+            mnemonic.into_descriptors_for_account(passphrase, network, address_type, account);
 
         let wallet = descriptors
             .into_create_params()
@@ -523,6 +710,21 @@ impl Wallet {
 
         Ok(())
     }
+
+    /// The wallet's scan settings, restored and imported wallets have no birthday, so their
+    /// full scans start where the wallet's script type was first usable instead of genesis
+    pub fn scan_settings(&self) -> ScanSettings {
+        let mut settings = self.metadata.scan_settings;
+        if settings.birthday_height.is_none() {
+            let desc_type = self
+                .bdk
+                .public_descriptor(KeychainKind::External)
+                .desc_type();
+            settings.birthday_height = Some(self.network.first_usable_height(desc_type));
+        }
+
+        settings
+    }
 }
 
 #[uniffi::export]
@@ -570,6 +772,23 @@ impl WalletAddressType {
             WalletAddressType::NativeSegwit => 0,
             WalletAddressType::WrappedSegwit => 1,
             WalletAddressType::Legacy => 2,
+            WalletAddressType::Taproot => 3,
+        }
+    }
+
+    /// The address type of a single-sig descriptor, `None` for anything else ex: multisig
+    pub fn from_descriptor(descriptor: &ExtendedDescriptor) -> Option<Self> {
+        use bdk_wallet::miniscript::descriptor::ShInner;
+        use bdk_wallet::miniscript::Descriptor;
+
+        match descriptor {
+            Descriptor::Wpkh(_) => Some(Self::NativeSegwit),
+            Descriptor::Sh(sh) if matches!(sh.as_inner(), ShInner::Wpkh(_)) => {
+                Some(Self::WrappedSegwit)
+            }
+            Descriptor::Pkh(_) => Some(Self::Legacy),
+            Descriptor::Tr(_) => Some(Self::Taproot),
+            _ => None,
         }
     }
 }
@@ -588,6 +807,18 @@ fn data_path(wallet_id: &WalletId) -> PathBuf {
     ROOT_DATA_DIR.join(db)
 }
 
+#[cfg(test)]
+impl Wallet {
+    /// A hot wallet with its own id, so tests running at the same time don't share a store
+    pub fn new_for_test() -> Self {
+        let mnemonic = Mnemonic::from_str("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+        let mut metadata = WalletMetadata::preview_new();
+        metadata.id = WalletId::preview_new_random();
+
+        Self::try_new_persisted_from_mnemonic_segwit(metadata, mnemonic, None).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
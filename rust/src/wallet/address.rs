@@ -1,7 +1,9 @@
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::str::FromStr as _;
 use std::sync::Arc;
 
+use bdk_chain::bitcoin::address::NetworkChecked;
 use bdk_chain::bitcoin::address::NetworkUnchecked;
 use bdk_chain::bitcoin::params::Params;
 use bdk_chain::bitcoin::Address as BdkAddress;
@@ -10,10 +12,13 @@ use bdk_chain::ConfirmationBlockTime;
 use bdk_wallet::{bitcoin::Transaction as BdkTransaction, AddressInfo as BdkAddressInfo};
 use serde::Deserialize;
 
+use crate::database::Database;
 use crate::network::Network;
 use crate::transaction::Amount;
 use crate::transaction::TransactionDirection;
 
+use super::bip21::{Bip21, Bip21Error};
+
 #[derive(
     Debug,
     Clone,
@@ -47,6 +52,8 @@ pub struct AddressWithNetwork {
     pub address: Address,
     pub network: Network,
     pub amount: Option<Amount>,
+    pub label: Option<String>,
+    pub message: Option<String>,
 }
 
 type Error = AddressError;
@@ -71,6 +78,9 @@ pub enum AddressError {
 
     #[error("empty address")]
     EmptyAddress,
+
+    #[error("invalid payment request: {0}")]
+    InvalidPaymentRequest(#[from] Bip21Error),
 }
 
 impl Clone for AddressInfo {
@@ -127,65 +137,54 @@ impl Address {
 }
 
 impl AddressWithNetwork {
+    /// Parse a plain address or a BIP21 payment URI, detecting the network from the address
     pub fn try_new(str: &str) -> Result<Self, Error> {
-        let str = str.trim();
-        let str = str.trim_start_matches("bitcoin:");
-
-        let (address_str, amount) = extract_amount(str);
+        Self::try_new_for_network(str, Network::Bitcoin)
+    }
 
-        let address: BdkAddress<NetworkUnchecked> =
-            address_str.parse().map_err(|_| Error::InvalidAddress)?;
-
-        let network = Network::Bitcoin;
-        if let Ok(address) = address.clone().require_network(network.into()) {
-            return Ok(Self {
-                address: address.into(),
-                network,
-                amount,
-            });
+    /// Like [`Self::try_new`], but an address that is valid on the `selected` network is
+    /// parsed for it, base58 test addresses are the same on testnet, signet and regtest
+    pub fn try_new_for_network(str: &str, selected: Network) -> Result<Self, Error> {
+        let Bip21 {
+            address,
+            amount,
+            label,
+            message,
+        } = Bip21::parse(str)?;
+
+        if address.is_empty() {
+            return Err(Error::EmptyAddress);
         }
 
-        let network = Network::Testnet;
-        if let Ok(address) = address.require_network(network.into()) {
-            return Ok(Self {
-                address: address.into(),
-                network,
-                amount,
-            });
+        let address: BdkAddress<NetworkUnchecked> =
+            address.parse().map_err(|_| Error::InvalidAddress)?;
+
+        let amount = amount.map(Amount::from);
+
+        // base58 testnet addresses are also valid on regtest, only `bcrt1` addresses get there
+        // unless regtest is the selected network
+        let networks = [
+            selected,
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Regtest,
+        ];
+        for network in networks {
+            if let Ok(address) = address.clone().require_network(network.into()) {
+                return Ok(Self {
+                    address: address.into(),
+                    network,
+                    amount,
+                    label,
+                    message,
+                });
+            }
         }
 
         Err(Error::UnsupportedNetwork)
     }
 }
 
-fn extract_amount(full_qr: &str) -> (&str, Option<Amount>) {
-    let Some(pos) = full_qr.find("?amount=") else { return (full_qr, None) };
-
-    let address = &full_qr[..pos];
-
-    let number_start = pos + 8;
-    let mut number_end = number_start;
-    for char in full_qr[number_start..].chars() {
-        if char.is_ascii_digit() || char == '.' {
-            number_end += 1;
-        } else {
-            break;
-        }
-    }
-
-    let number = &full_qr[number_start..number_end];
-    let Ok(amount_float) = number.parse::<f64>() else { return (address, None) };
-    let Ok(amount) = Amount::from_btc(amount_float) else { return (address, None) };
-
-    (address, Some(amount))
-}
-
-use std::str::FromStr as _;
-
-use bdk_chain::bitcoin::address::NetworkChecked;
-
-use crate::database::Database;
-
 #[uniffi::export]
 fn address_is_equal(lhs: Arc<Address>, rhs: Arc<Address>) -> bool {
     lhs == rhs
@@ -195,7 +194,8 @@ fn address_is_equal(lhs: Arc<Address>, rhs: Arc<Address>) -> bool {
 impl AddressWithNetwork {
     #[uniffi::constructor(name = "new")]
     pub fn new(address: String) -> Result<Self, Error> {
-        Self::try_new(&address)
+        let network = Database::global().global_config.selected_network();
+        Self::try_new_for_network(&address, network)
     }
 
     fn address(&self) -> Address {
@@ -209,6 +209,14 @@ impl AddressWithNetwork {
     fn amount(&self) -> Option<Arc<Amount>> {
         self.amount.map(Arc::new)
     }
+
+    fn label(&self) -> Option<String> {
+        self.label.clone()
+    }
+
+    fn message(&self) -> Option<String> {
+        self.message.clone()
+    }
 }
 
 #[uniffi::export]
@@ -252,6 +260,21 @@ impl AddressInfo {
     fn index(&self) -> u32 {
         self.index
     }
+
+    /// BIP21 `bitcoin:` URI for receiving to this address, for sharing and QR codes
+    #[uniffi::method(default(amount = None, label = None, message = None))]
+    fn payment_uri(
+        &self,
+        amount: Option<Arc<Amount>>,
+        label: Option<String>,
+        message: Option<String>,
+    ) -> String {
+        Bip21::new(self.address.to_string())
+            .amount(amount.map(|amount| amount.0))
+            .label(label)
+            .message(message)
+            .to_uri()
+    }
 }
 
 #[uniffi::export]
@@ -290,38 +313,6 @@ impl<'de> Deserialize<'de> for Address {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_extract_amount_no_amount() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, None);
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.001";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.001).unwrap()));
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount_and_spaces() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.001  ";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.001).unwrap()));
-    }
-
-    #[test]
-    fn test_extract_amount_with_amount_and_other_query_params() {
-        let a = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f?amount=0.002&foo=bar";
-        let (a, amount) = extract_amount(a);
-        assert_eq!(a, "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f");
-        assert_eq!(amount, Some(Amount::from_btc(0.002).unwrap()));
-    }
-
     #[test]
     fn test_address_with_network() {
         let assert = |address_with_network: AddressWithNetwork, amount: Option<Amount>| {
@@ -368,4 +359,61 @@ mod tests {
             Some(Amount::from_btc(0.002).unwrap()),
         );
     }
+
+    #[test]
+    fn test_address_with_network_regtest() {
+        let address_with_network = AddressWithNetwork::try_new(
+            "bitcoin:bcrt1q6rz28mcfaxtmd6v789l9rrlrusdprr9pz3cppk?amount=0.5",
+        )
+        .unwrap();
+
+        assert_eq!(address_with_network.network, Network::Regtest);
+        assert_eq!(
+            address_with_network.amount,
+            Some(Amount::from_btc(0.5).unwrap())
+        );
+
+        // base58 test addresses are valid on testnet and regtest
+        let base58 = "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn";
+        let address_with_network = AddressWithNetwork::try_new(base58).unwrap();
+        assert_eq!(address_with_network.network, Network::Testnet);
+
+        let address_with_network =
+            AddressWithNetwork::try_new_for_network(base58, Network::Regtest).unwrap();
+        assert_eq!(address_with_network.network, Network::Regtest);
+
+        // a mainnet address stays on mainnet
+        let address_with_network = AddressWithNetwork::try_new_for_network(
+            "bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm",
+            Network::Regtest,
+        )
+        .unwrap();
+        assert_eq!(address_with_network.network, Network::Bitcoin);
+    }
+
+    #[test]
+    fn test_address_with_network_payment_request() {
+        let address_with_network = AddressWithNetwork::try_new(
+            "bitcoin:bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm?label=Alice&message=Invoice%20%2342&amount=0.01",
+        )
+        .unwrap();
+
+        assert_eq!(
+            address_with_network.amount,
+            Some(Amount::from_btc(0.01).unwrap())
+        );
+        assert_eq!(address_with_network.label.as_deref(), Some("Alice"));
+        assert_eq!(address_with_network.message.as_deref(), Some("Invoice #42"));
+
+        let address_with_network = AddressWithNetwork::try_new(
+            "bitcoin:bc1q00000002ltfnxz6lt9g655akfz0lm6k9wva2rm?req-pop=callback",
+        );
+
+        assert!(matches!(
+            address_with_network,
+            Err(Error::InvalidPaymentRequest(
+                Bip21Error::UnsupportedRequiredParameter(_)
+            ))
+        ));
+    }
 }
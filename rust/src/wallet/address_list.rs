@@ -0,0 +1,272 @@
+//! Every address the wallet has handed out, with its usage, for auditing the gap limit
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use bdk_wallet::{
+    bitcoin::{bip32::DerivationPath, Amount as BdkAmount},
+    miniscript::ForEachKey as _,
+    KeychainKind,
+};
+
+use crate::transaction::Amount;
+
+use super::{
+    label::{LabelType, Labels},
+    Address,
+};
+
+type Derivation = (KeychainKind, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct AddressList {
+    /// Unused addresses scanned past the last used one before a scan stops
+    pub gap_limit: u32,
+    pub external: KeychainAddresses,
+    pub change: KeychainAddresses,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct KeychainAddresses {
+    /// Every revealed address, lowest index first
+    pub addresses: Vec<AddressListEntry>,
+    pub last_used_index: Option<u32>,
+    pub last_revealed_index: Option<u32>,
+    /// Revealed addresses after the last used one
+    pub unused_gap: u32,
+    /// Revealed addresses past the gap limit, 0 when all are within it
+    pub beyond_gap_limit: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct AddressListEntry {
+    pub address: Arc<Address>,
+    pub index: u32,
+    /// Full path from the master key, ex: m/84'/0'/0'/0/5
+    pub derivation_path: Option<String>,
+    pub is_change: bool,
+    /// Received or spent in at least one transaction
+    pub is_used: bool,
+    pub tx_count: u32,
+    /// Unspent balance, including unconfirmed outputs
+    pub balance: Arc<Amount>,
+    /// Too far past the last used address, restoring from the seed won't find payments to it
+    pub is_beyond_gap_limit: bool,
+    pub label: Option<String>,
+}
+
+impl AddressList {
+    pub fn new(wallet: &bdk_wallet::Wallet, labels: &Labels, gap_limit: u32) -> Self {
+        let mut tx_counts: HashMap<Derivation, u32> = HashMap::new();
+        for tx in wallet.transactions() {
+            let tx = &tx.tx_node.tx;
+
+            let received = tx.output.iter().map(|output| output.script_pubkey.clone());
+            let spent = tx
+                .input
+                .iter()
+                .filter_map(|input| wallet.tx_graph().get_txout(input.previous_output))
+                .map(|txout| txout.script_pubkey.clone());
+
+            // count each transaction once per address, even if it has multiple outputs to it
+            let touched = received
+                .chain(spent)
+                .filter_map(|spk| wallet.derivation_of_spk(spk))
+                .collect::<HashSet<_>>();
+
+            for derivation in touched {
+                *tx_counts.entry(derivation).or_default() += 1;
+            }
+        }
+
+        let mut balances: HashMap<Derivation, BdkAmount> = HashMap::new();
+        for output in wallet.list_unspent() {
+            *balances
+                .entry((output.keychain, output.derivation_index))
+                .or_default() += output.txout.value;
+        }
+
+        let keychain_addresses = |keychain| {
+            KeychainAddresses::new(wallet, keychain, labels, gap_limit, &tx_counts, &balances)
+        };
+
+        Self {
+            gap_limit,
+            external: keychain_addresses(KeychainKind::External),
+            change: keychain_addresses(KeychainKind::Internal),
+        }
+    }
+}
+
+impl KeychainAddresses {
+    fn new(
+        wallet: &bdk_wallet::Wallet,
+        keychain: KeychainKind,
+        labels: &Labels,
+        gap_limit: u32,
+        tx_counts: &HashMap<Derivation, u32>,
+        balances: &HashMap<Derivation, BdkAmount>,
+    ) -> Self {
+        let last_revealed_index = wallet.derivation_index(keychain);
+
+        let mut addresses = last_revealed_index
+            .map(|last| 0..=last)
+            .into_iter()
+            .flatten()
+            .map(|index| {
+                let address = wallet.peek_address(keychain, index).address;
+                let tx_count = tx_counts.get(&(keychain, index)).copied().unwrap_or(0);
+                let balance = balances
+                    .get(&(keychain, index))
+                    .copied()
+                    .unwrap_or_default();
+                let label = labels.text(LabelType::Addr, &address.to_string());
+
+                AddressListEntry {
+                    address: Arc::new(address.into()),
+                    index,
+                    derivation_path: derivation_path(wallet, keychain, index)
+                        .map(|path| format!("m/{path}")),
+                    is_change: keychain == KeychainKind::Internal,
+                    is_used: tx_count > 0,
+                    tx_count,
+                    balance: Arc::new(balance.into()),
+                    is_beyond_gap_limit: false,
+                    label,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let last_used_index = addresses
+            .iter()
+            .filter(|address| address.is_used)
+            .map(|address| address.index)
+            .max();
+
+        // a scan stops after `gap_limit` unused addresses in a row
+        let first_beyond_gap = match last_used_index {
+            Some(last_used) => last_used.saturating_add(gap_limit).saturating_add(1),
+            None => gap_limit,
+        };
+
+        for address in addresses.iter_mut() {
+            address.is_beyond_gap_limit = address.index >= first_beyond_gap;
+        }
+
+        let unused_gap = match (last_revealed_index, last_used_index) {
+            (Some(last_revealed), Some(last_used)) => last_revealed - last_used,
+            (Some(last_revealed), None) => last_revealed + 1,
+            (None, _) => 0,
+        };
+
+        let beyond_gap_limit = addresses
+            .iter()
+            .filter(|address| address.is_beyond_gap_limit)
+            .count() as u32;
+
+        Self {
+            addresses,
+            last_used_index,
+            last_revealed_index,
+            unused_gap,
+            beyond_gap_limit,
+        }
+    }
+}
+
+/// Full path from the master key of the address at `index`, `None` without key origin info
+pub fn derivation_path(
+    wallet: &bdk_wallet::Wallet,
+    keychain: KeychainKind,
+    index: u32,
+) -> Option<DerivationPath> {
+    let descriptor = wallet
+        .public_descriptor(keychain)
+        .at_derivation_index(index)
+        .ok()?;
+
+    let mut path = None;
+    descriptor.for_each_key(|key| {
+        path = key.full_derivation_path();
+        true
+    });
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk_wallet::{
+        bitcoin::{absolute::LockTime, transaction::Version, OutPoint, Transaction, TxIn, TxOut},
+        keys::bip39::Mnemonic,
+    };
+
+    use super::*;
+    use crate::{
+        keys::{Descriptor, DescriptorSecretKey, Descriptors},
+        network::Network,
+    };
+
+    fn wallet() -> bdk_wallet::Wallet {
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+
+        let network = Network::Regtest;
+        let secret_key = DescriptorSecretKey::new(network, mnemonic, None);
+
+        let external = Descriptor::new_bip84(&secret_key, KeychainKind::External, network);
+        let internal = Descriptor::new_bip84(&secret_key, KeychainKind::Internal, network);
+
+        Descriptors { external, internal }
+            .into_create_params()
+            .network(network.into())
+            .create_wallet_no_persist()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_address_list_gap_limit() {
+        let mut wallet = wallet();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, 29)
+            .count();
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: BdkAmount::from_sat(50_000),
+                script_pubkey: wallet
+                    .peek_address(KeychainKind::External, 3)
+                    .script_pubkey(),
+            }],
+        };
+        wallet.apply_unconfirmed_txs([(tx, 100)]);
+
+        let list = AddressList::new(&wallet, &Labels::new(vec![]), 25);
+        let external = &list.external;
+
+        assert_eq!(external.addresses.len(), 30);
+        assert_eq!(external.last_used_index, Some(3));
+        assert_eq!(external.last_revealed_index, Some(29));
+        assert_eq!(external.unused_gap, 26);
+        assert_eq!(external.beyond_gap_limit, 1);
+        assert!(external.addresses[29].is_beyond_gap_limit);
+        assert!(!external.addresses[28].is_beyond_gap_limit);
+
+        let used = &external.addresses[3];
+        assert!(used.is_used);
+        assert_eq!(used.tx_count, 1);
+        assert_eq!(used.balance.0, BdkAmount::from_sat(50_000));
+        assert_eq!(used.derivation_path.as_deref(), Some("m/84'/1'/0'/0/3"));
+
+        assert!(list.change.addresses.is_empty());
+        assert_eq!(list.change.unused_gap, 0);
+    }
+}
@@ -1,18 +1,24 @@
+use bitcoin_units::Amount as BdkAmount;
+
 use crate::transaction::Amount;
 
 #[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    Default,
-    uniffi::Object,
-    derive_more::From,
-    derive_more::Into,
-    derive_more::Deref,
-    derive_more::AsRef,
+    Debug, Clone, PartialEq, Eq, Default, uniffi::Object, derive_more::Deref, derive_more::AsRef,
 )]
-pub struct Balance(pub bdk_wallet::Balance);
+pub struct Balance {
+    #[deref]
+    #[as_ref]
+    pub balance: bdk_wallet::Balance,
+
+    /// The part of the balance in frozen utxos, split up the same way as the full balance
+    pub frozen: bdk_wallet::Balance,
+}
+
+impl Balance {
+    pub fn with_frozen(balance: bdk_wallet::Balance, frozen: bdk_wallet::Balance) -> Self {
+        Self { balance, frozen }
+    }
+}
 
 #[uniffi::export]
 impl Balance {
@@ -21,13 +27,83 @@ impl Balance {
         Balance::default()
     }
 
+    /// Includes frozen coins
     #[uniffi::method]
     pub fn total(&self) -> Amount {
-        self.0.total().into()
+        self.balance.total().into()
     }
 
+    /// Coins that can be spent right now, frozen coins are never spendable
     #[uniffi::method]
     pub fn spendable(&self) -> Amount {
-        self.0.trusted_spendable().into()
+        let spendable = self
+            .balance
+            .trusted_spendable()
+            .checked_sub(self.frozen.trusted_spendable())
+            .unwrap_or(BdkAmount::ZERO);
+
+        spendable.into()
+    }
+
+    #[uniffi::method]
+    pub fn frozen(&self) -> Amount {
+        self.frozen.total().into()
+    }
+}
+
+impl From<bdk_wallet::Balance> for Balance {
+    fn from(balance: bdk_wallet::Balance) -> Self {
+        Self {
+            balance,
+            frozen: bdk_wallet::Balance::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bdk_balance(
+        confirmed: u64,
+        trusted_pending: u64,
+        untrusted_pending: u64,
+    ) -> bdk_wallet::Balance {
+        bdk_wallet::Balance {
+            immature: BdkAmount::ZERO,
+            trusted_pending: BdkAmount::from_sat(trusted_pending),
+            untrusted_pending: BdkAmount::from_sat(untrusted_pending),
+            confirmed: BdkAmount::from_sat(confirmed),
+        }
+    }
+
+    #[test]
+    fn test_frozen_coins_are_not_spendable() {
+        let balance = Balance::with_frozen(
+            bdk_balance(100_000, 20_000, 5_000),
+            bdk_balance(30_000, 10_000, 0),
+        );
+
+        // frozen coins are still part of the total
+        assert_eq!(balance.total().as_sats(), 125_000);
+        assert_eq!(balance.spendable().as_sats(), 80_000);
+        assert_eq!(balance.frozen().as_sats(), 40_000);
+    }
+
+    #[test]
+    fn test_untrusted_frozen_coins_only_change_frozen() {
+        let balance =
+            Balance::with_frozen(bdk_balance(100_000, 0, 5_000), bdk_balance(0, 0, 5_000));
+
+        assert_eq!(balance.spendable().as_sats(), 100_000);
+        assert_eq!(balance.frozen().as_sats(), 5_000);
+    }
+
+    #[test]
+    fn test_without_frozen_coins_everything_trusted_is_spendable() {
+        let balance = Balance::from(bdk_balance(100_000, 20_000, 5_000));
+
+        assert_eq!(balance.spendable().as_sats(), 120_000);
+        assert_eq!(balance.frozen().as_sats(), 0);
     }
 }
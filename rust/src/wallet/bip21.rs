@@ -0,0 +1,224 @@
+//! BIP21 `bitcoin:` payment URIs, ex: `bitcoin:bc1q...?amount=0.001&label=Coffee`
+
+use bdk_wallet::bitcoin::{Amount, Denomination};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+const SCHEME: &str = "bitcoin:";
+
+/// Everything except the unreserved characters from RFC 3986
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error, uniffi::Error)]
+pub enum Bip21Error {
+    #[error("invalid amount {0}, must be in BTC with at most 8 decimals")]
+    InvalidAmount(String),
+
+    #[error("parameter {0} appears more than once")]
+    DuplicateParameter(String),
+
+    #[error("unable to decode parameter {0}, invalid percent-encoding")]
+    InvalidEncoding(String),
+
+    #[error("payment request requires {0}, which is not supported")]
+    UnsupportedRequiredParameter(String),
+}
+
+type Error = Bip21Error;
+
+/// A parsed or to be generated payment URI, the address is not validated here
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bip21 {
+    pub address: String,
+    pub amount: Option<Amount>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Bip21 {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn amount(mut self, amount: Option<Amount>) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    pub fn label(mut self, label: Option<String>) -> Self {
+        self.label = label.filter(|label| !label.is_empty());
+        self
+    }
+
+    pub fn message(mut self, message: Option<String>) -> Self {
+        self.message = message.filter(|message| !message.is_empty());
+        self
+    }
+
+    /// Parse a `bitcoin:` URI, a plain address, or an address followed by a query string
+    ///
+    /// Unknown parameters are ignored, unknown `req-` parameters are an error as BIP21 requires
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+
+        let input = match input.get(..SCHEME.len()) {
+            Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &input[SCHEME.len()..],
+            _ => input,
+        };
+
+        let (address, query) = input.split_once('?').unwrap_or((input, ""));
+        let mut bip21 = Self::new(address.trim_start_matches('/'));
+
+        let params = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| param.split_once('=').unwrap_or((param, "")));
+
+        for (key, value) in params {
+            match key.to_ascii_lowercase().as_str() {
+                "amount" => {
+                    let amount = Amount::from_str_in(value, Denomination::Bitcoin)
+                        .map_err(|_| Error::InvalidAmount(value.to_string()))?;
+
+                    set_once(&mut bip21.amount, amount, key)?;
+                }
+
+                "label" => set_once(&mut bip21.label, decode(key, value)?, key)?,
+                "message" => set_once(&mut bip21.message, decode(key, value)?, key)?,
+
+                lowercase if lowercase.starts_with("req-") => {
+                    return Err(Error::UnsupportedRequiredParameter(key.to_string()))
+                }
+
+                // other optional parameters (lightning, pj, etc.) can be safely ignored
+                _ => {}
+            }
+        }
+
+        Ok(bip21)
+    }
+
+    /// `bitcoin:` URI with only the parameters that are set
+    pub fn to_uri(&self) -> String {
+        let mut params = vec![];
+
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", format_btc(amount)));
+        }
+
+        if let Some(label) = &self.label {
+            params.push(format!("label={}", utf8_percent_encode(label, QUERY_VALUE)));
+        }
+
+        if let Some(message) = &self.message {
+            params.push(format!(
+                "message={}",
+                utf8_percent_encode(message, QUERY_VALUE)
+            ));
+        }
+
+        if params.is_empty() {
+            return format!("{SCHEME}{}", self.address);
+        }
+
+        format!("{SCHEME}{}?{}", self.address, params.join("&"))
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, value: T, key: &str) -> Result<(), Error> {
+    if field.is_some() {
+        return Err(Error::DuplicateParameter(key.to_string()));
+    }
+
+    *field = Some(value);
+    Ok(())
+}
+
+fn decode(key: &str, value: &str) -> Result<String, Error> {
+    percent_decode_str(value)
+        .decode_utf8()
+        .map(|value| value.into_owned())
+        .map_err(|_| Error::InvalidEncoding(key.to_string()))
+}
+
+/// Decimal BTC without trailing zeros, ex: 0.001
+fn format_btc(amount: Amount) -> String {
+    let sats = amount.to_sat();
+    let btc = format!("{}.{:08}", sats / 100_000_000, sats % 100_000_000);
+    btc.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "bc1q0g0vn4yqyk0zjwxw0zv5pltyy9jm89vclxgsv3f";
+
+    #[test]
+    fn test_parse_all_parameters() {
+        let uri = format!(
+            "BITCOIN:{ADDRESS}?foo=bar&amount=0.002&label=Luke-Jr&message=Donation%20for%20project%20xyz"
+        );
+
+        let bip21 = Bip21::parse(&uri).unwrap();
+        assert_eq!(bip21.address, ADDRESS);
+        assert_eq!(bip21.amount, Some(Amount::from_sat(200_000)));
+        assert_eq!(bip21.label.as_deref(), Some("Luke-Jr"));
+        assert_eq!(bip21.message.as_deref(), Some("Donation for project xyz"));
+    }
+
+    #[test]
+    fn test_parse_without_scheme() {
+        let bip21 = Bip21::parse(&format!("{ADDRESS}?amount=0.001  ")).unwrap();
+        assert_eq!(bip21.address, ADDRESS);
+        assert_eq!(bip21.amount, Some(Amount::from_sat(100_000)));
+
+        let bip21 = Bip21::parse(ADDRESS).unwrap();
+        assert_eq!(bip21, Bip21::new(ADDRESS));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |query: &str| Bip21::parse(&format!("bitcoin:{ADDRESS}?{query}"));
+
+        assert_eq!(parse("amount=1,5"), Err(Error::InvalidAmount("1,5".into())));
+        assert_eq!(
+            parse("amount=0.000000001"),
+            Err(Error::InvalidAmount("0.000000001".into()))
+        );
+        assert_eq!(
+            parse("amount=1&amount=2"),
+            Err(Error::DuplicateParameter("amount".into()))
+        );
+        assert_eq!(
+            parse("req-somethingyoudontunderstand=50"),
+            Err(Error::UnsupportedRequiredParameter(
+                "req-somethingyoudontunderstand".into()
+            ))
+        );
+
+        assert!(parse("somethingyoudontunderstand=50&somethingelse=abc").is_ok());
+    }
+
+    #[test]
+    fn test_uri_roundtrip() {
+        assert_eq!(Bip21::new(ADDRESS).to_uri(), format!("bitcoin:{ADDRESS}"));
+
+        let bip21 = Bip21::new(ADDRESS)
+            .amount(Some(Amount::from_sat(150_000_000)))
+            .label(Some("Rent & Utilities".into()))
+            .message(Some("March, 50% share".into()));
+
+        let uri = bip21.to_uri();
+        let query = "amount=1.5&label=Rent%20%26%20Utilities&message=March%2C%2050%25%20share";
+        assert_eq!(uri, format!("bitcoin:{ADDRESS}?{query}"));
+
+        assert_eq!(Bip21::parse(&uri).unwrap(), bip21);
+    }
+}
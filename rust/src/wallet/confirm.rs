@@ -14,12 +14,17 @@ use super::Address;
 )]
 pub struct ConfirmDetails {
     pub spending_amount: Amount,
+    /// Total sent to all the recipients
     pub sending_amount: Amount,
     pub fee_total: Amount,
     pub fee_rate: FeeRate,
+    /// The first recipient, kept for single recipient transactions
     pub sending_to: Address,
     pub psbt: Psbt,
     pub more_details: InputOutputDetails,
+    /// Every output paying someone outside the wallet, empty for records saved before batch sends
+    #[serde(default)]
+    pub recipients: Vec<AddressAndAmount>,
 }
 
 #[derive(
@@ -91,6 +96,21 @@ impl ConfirmDetails {
         self.sending_to.clone()
     }
 
+    pub fn recipients(&self) -> Vec<AddressAndAmount> {
+        if !self.recipients.is_empty() {
+            return self.recipients.clone();
+        }
+
+        vec![AddressAndAmount {
+            address: Arc::new(self.sending_to.clone()),
+            amount: Arc::new(self.sending_amount),
+        }]
+    }
+
+    pub fn is_batch(&self) -> bool {
+        self.recipients.len() > 1
+    }
+
     pub fn inputs(&self) -> Vec<AddressAndAmount> {
         self.more_details.inputs.clone()
     }
@@ -136,6 +156,14 @@ impl ConfirmDetails {
 
         Ok(split.parts)
     }
+
+    /// Animated crypto-psbt UR, for Keystone, Passport, Jade and SeedSigner
+    pub fn psbt_to_ur(&self) -> Result<Vec<String>> {
+        let data = self.psbt.serialize();
+
+        crate::ur::encode_psbt(&data)
+            .map_err(|e| ConfirmDetailsError::QrCodeCreation(e.to_string()))
+    }
 }
 
 impl InputOutputDetails {
@@ -170,6 +198,27 @@ impl AddressAndAmount {
     }
 }
 
+#[cfg(test)]
+impl ConfirmDetails {
+    /// Details sending the first output of `psbt` on mainnet, paying `fee`
+    pub fn new_for_test(psbt: crate::psbt::BdkPsbt, fee: bitcoin::Amount) -> Self {
+        let recipient = AddressAndAmount::try_new(&psbt.unsigned_tx.output[0], Network::Bitcoin)
+            .expect("first output has an address");
+
+        let psbt: Psbt = psbt.into();
+        Self {
+            spending_amount: (recipient.amount.0 + fee).into(),
+            sending_amount: *recipient.amount,
+            fee_total: fee.into(),
+            fee_rate: BdkFeeRate::from_sat_per_vb_unchecked(1).into(),
+            sending_to: (*recipient.address).clone(),
+            more_details: InputOutputDetails::new(&psbt, Network::Bitcoin),
+            psbt,
+            recipients: vec![recipient],
+        }
+    }
+}
+
 // MARK: CONFIRM DETAILS PREVIEW
 mod ffi_preview {
     use crate::psbt::BdkPsbt;
@@ -182,6 +231,10 @@ mod ffi_preview {
         pub fn preview_new(amount: u64) -> Self {
             let psbt = psbt_preview_new();
             let more_details = InputOutputDetails::new(&psbt, Network::Bitcoin);
+            let recipients = vec![AddressAndAmount {
+                address: Arc::new(Address::preview_new()),
+                amount: Arc::new(Amount::from_sat(amount - 658)),
+            }];
 
             Self {
                 spending_amount: Amount::from_sat(amount),
@@ -191,6 +244,7 @@ mod ffi_preview {
                 sending_to: Address::preview_new(),
                 psbt,
                 more_details,
+                recipients,
             }
         }
     }
@@ -0,0 +1,319 @@
+//! Watch-only wallets from any output descriptor, including ones with custom derivation paths
+
+use bdk_wallet::{
+    bitcoin::{
+        bip32::{ChildNumber, DerivationPath},
+        secp256k1::Secp256k1,
+    },
+    descriptor::ExtendedDescriptor,
+    keys::DescriptorPublicKey,
+    miniscript::{descriptor::DescriptorType, ForEachKey as _},
+    KeychainKind,
+};
+
+use crate::keys::Descriptors;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum DescriptorError {
+    #[error("invalid descriptor: {0}")]
+    InvalidDescriptor(String),
+
+    #[error("descriptor contains private keys, only public descriptors can be imported")]
+    ContainsPrivateKeys,
+
+    #[error("descriptor has no wildcard, ex: /0/*")]
+    NotRanged,
+
+    #[error("multipath descriptors need exactly two paths, ex: /<0;1>/*, found {0}")]
+    UnsupportedMultipath(u32),
+
+    #[error("expected a receive and a change descriptor, found {0} descriptors")]
+    WrongNumberOfDescriptors(u32),
+}
+
+type Error = DescriptorError;
+
+/// The wallet's descriptors and key origins, for showing non-standard paths in settings
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct DescriptorDetails {
+    /// Receive descriptor, with checksum
+    pub external: String,
+    /// Change descriptor, with checksum
+    pub internal: String,
+    pub keys: Vec<KeyOrigin>,
+    /// Every key follows BIP44/49/84/86 for single sig, or BIP48 for multisig
+    pub is_standard: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct KeyOrigin {
+    pub fingerprint: Option<String>,
+    /// ex: m/84'/0'/0', `None` when the descriptor has no origin info for the key
+    pub derivation_path: Option<String>,
+    pub is_standard: bool,
+}
+
+/// Receive and change descriptors from one multipath descriptor `/<0;1>/*`, or one per line
+///
+/// Checksums are validated when present, lines starting with `#` are skipped
+pub fn parse_watch_only(input: &str) -> Result<Descriptors, Error> {
+    let lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+
+    let (external, internal) = match lines.as_slice() {
+        [descriptor] => {
+            let descriptor = parse_public(descriptor)?;
+            if !descriptor.is_multipath() {
+                return Err(Error::WrongNumberOfDescriptors(1));
+            }
+
+            let descriptors = descriptor
+                .into_single_descriptors()
+                .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+            let [external, internal] = <[_; 2]>::try_from(descriptors)
+                .map_err(|descriptors| Error::UnsupportedMultipath(descriptors.len() as u32))?;
+
+            (external, internal)
+        }
+
+        [external, internal] => {
+            let external = parse_public(external)?;
+            let internal = parse_public(internal)?;
+
+            if external.is_multipath() || internal.is_multipath() {
+                let error = "multipath descriptors can't be combined with other descriptors";
+                return Err(Error::InvalidDescriptor(error.to_string()));
+            }
+
+            (external, internal)
+        }
+
+        lines => return Err(Error::WrongNumberOfDescriptors(lines.len() as u32)),
+    };
+
+    if !external.has_wildcard() || !internal.has_wildcard() {
+        return Err(Error::NotRanged);
+    }
+
+    Ok(Descriptors {
+        external: external.into(),
+        internal: internal.into(),
+    })
+}
+
+/// Does the input look like an output descriptor rather than an xpub or a wallet export
+pub fn is_descriptor(input: &str) -> bool {
+    let input = input.trim_start();
+    !input.starts_with('{') && input.contains('(')
+}
+
+fn parse_public(descriptor: &str) -> Result<ExtendedDescriptor, Error> {
+    let secp = Secp256k1::new();
+    let (descriptor, key_map) = ExtendedDescriptor::parse_descriptor(&secp, descriptor)
+        .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+    if !key_map.is_empty() {
+        return Err(Error::ContainsPrivateKeys);
+    }
+
+    descriptor
+        .sanity_check()
+        .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+    Ok(descriptor)
+}
+
+impl DescriptorDetails {
+    pub fn new(wallet: &bdk_wallet::Wallet) -> Self {
+        let external = wallet.public_descriptor(KeychainKind::External);
+        let internal = wallet.public_descriptor(KeychainKind::Internal);
+        let desc_type = external.desc_type();
+
+        let mut keys = vec![];
+        external.for_each_key(|key| {
+            keys.push(KeyOrigin::new(desc_type, key));
+            true
+        });
+
+        let is_standard = keys.iter().all(|key| key.is_standard);
+
+        Self {
+            external: external.to_string(),
+            internal: internal.to_string(),
+            keys,
+            is_standard,
+        }
+    }
+}
+
+impl KeyOrigin {
+    fn new(desc_type: DescriptorType, key: &DescriptorPublicKey) -> Self {
+        let origin = match key {
+            DescriptorPublicKey::Single(single) => single.origin.as_ref(),
+            DescriptorPublicKey::XPub(xpub) => xpub.origin.as_ref(),
+            DescriptorPublicKey::MultiXPub(xpub) => xpub.origin.as_ref(),
+        };
+
+        let Some((fingerprint, path)) = origin else {
+            return Self {
+                fingerprint: None,
+                derivation_path: None,
+                is_standard: false,
+            };
+        };
+
+        Self {
+            fingerprint: Some(fingerprint.to_string()),
+            derivation_path: Some(format!("m/{path}")),
+            is_standard: is_standard_path(desc_type, path),
+        }
+    }
+}
+
+/// BIP44/49/84/86 `purpose'/coin'/account'` for single sig, BIP48 `48'/coin'/account'/script'`
+/// for multisig
+fn is_standard_path(desc_type: DescriptorType, path: &DerivationPath) -> bool {
+    use DescriptorType as T;
+
+    let hardened = |index| ChildNumber::Hardened { index };
+    let is_coin_type =
+        |child: &ChildNumber| matches!(child, ChildNumber::Hardened { index: 0 | 1 });
+
+    let single_sig_purpose = match desc_type {
+        T::Pkh => Some(44),
+        T::ShWpkh => Some(49),
+        T::Wpkh => Some(84),
+        T::Tr => Some(86),
+        _ => None,
+    };
+
+    let multisig_script_type = match desc_type {
+        T::ShWsh | T::ShWshSortedMulti => Some(1),
+        T::Wsh | T::WshSortedMulti => Some(2),
+        _ => None,
+    };
+
+    let path: &[ChildNumber] = path.as_ref();
+    match (path, single_sig_purpose, multisig_script_type) {
+        ([purpose, coin, account], Some(expected), _) => {
+            *purpose == hardened(expected) && is_coin_type(coin) && account.is_hardened()
+        }
+
+        ([purpose, coin, account, script], _, Some(expected)) => {
+            *purpose == hardened(48)
+                && is_coin_type(coin)
+                && account.is_hardened()
+                && *script == hardened(expected)
+        }
+
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk_wallet::keys::bip39::Mnemonic;
+
+    use super::*;
+    use crate::{
+        keys::{Descriptor, DescriptorSecretKey},
+        network::Network,
+    };
+
+    /// BIP84 receive descriptor of the abandon mnemonic, and its private key version
+    fn bip84_descriptor() -> (String, String) {
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+
+        let network = Network::Bitcoin;
+        let secret_key = DescriptorSecretKey::new(network, mnemonic, None);
+        let descriptor = Descriptor::new_bip84(&secret_key, KeychainKind::External, network);
+
+        let public = descriptor.extended_descriptor.to_string();
+        let private = descriptor
+            .extended_descriptor
+            .to_string_with_secret(&descriptor.key_map);
+
+        (public, private)
+    }
+
+    fn without_checksum(descriptor: &str) -> &str {
+        descriptor.split('#').next().unwrap()
+    }
+
+    #[test]
+    fn test_parse_multipath_descriptor() {
+        let (public, _) = bip84_descriptor();
+        let multipath = without_checksum(&public).replace("/0/*", "/<0;1>/*");
+
+        let descriptors = parse_watch_only(&multipath).unwrap();
+        let external = descriptors.external.extended_descriptor.to_string();
+        let internal = descriptors.internal.extended_descriptor.to_string();
+
+        assert_eq!(without_checksum(&external), without_checksum(&public));
+        assert!(internal.contains("/1/*"));
+    }
+
+    #[test]
+    fn test_parse_two_descriptors_with_checksums() {
+        let (public, _) = bip84_descriptor();
+        let change = without_checksum(&public).replace("/0/*", "/1/*");
+        let input = format!("# exported descriptors\n{public}\n{change}\n");
+
+        assert!(parse_watch_only(&input).is_ok());
+
+        // a single character changed in the checksum
+        let mut bad_checksum = public.clone();
+        let last = bad_checksum.pop().unwrap();
+        bad_checksum.push(if last == 'q' { 'p' } else { 'q' });
+
+        let input = format!("{bad_checksum}\n{change}");
+        assert!(matches!(
+            parse_watch_only(&input),
+            Err(Error::InvalidDescriptor(_))
+        ));
+    }
+
+    #[test]
+    fn test_reject_private_and_single_descriptors() {
+        let (public, private) = bip84_descriptor();
+
+        assert_eq!(
+            parse_watch_only(&private).unwrap_err(),
+            Error::ContainsPrivateKeys
+        );
+        assert_eq!(
+            parse_watch_only(&public).unwrap_err(),
+            Error::WrongNumberOfDescriptors(1)
+        );
+    }
+
+    #[test]
+    fn test_standard_paths() {
+        let path = |path: &str| path.parse::<DerivationPath>().unwrap();
+
+        assert!(is_standard_path(DescriptorType::Wpkh, &path("m/84'/0'/0'")));
+        assert!(is_standard_path(DescriptorType::Tr, &path("m/86'/1'/3'")));
+        assert!(is_standard_path(
+            DescriptorType::WshSortedMulti,
+            &path("m/48'/0'/0'/2'")
+        ));
+
+        assert!(!is_standard_path(
+            DescriptorType::Wpkh,
+            &path("m/44'/0'/0'")
+        ));
+        assert!(!is_standard_path(
+            DescriptorType::Wpkh,
+            &path("m/84'/0'/0'/5")
+        ));
+        assert!(!is_standard_path(
+            DescriptorType::WshSortedMulti,
+            &path("m/48'/0'/0'/1'")
+        ));
+    }
+}
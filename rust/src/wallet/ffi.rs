@@ -4,6 +4,7 @@ use crate::hardware_export::HardwareExport;
 
 use super::{
     metadata::{DiscoveryState, FoundAddress},
+    multisig::{Cosigner, MultisigInfo},
     Address, Wallet, WalletAddressType, WalletError,
 };
 
@@ -19,6 +20,19 @@ impl Wallet {
         let export = Arc::unwrap_or_clone(export);
         Wallet::try_new_persisted_from_pubport(export.into_format())
     }
+
+    #[uniffi::constructor]
+    pub fn new_from_multisig(threshold: u8, cosigners: Vec<Cosigner>) -> Result<Self, WalletError> {
+        let info = MultisigInfo::try_new(threshold, cosigners)?;
+        Wallet::try_new_persisted_from_multisig(info)
+    }
+
+    /// Import a `wsh(sortedmulti(...))` descriptor
+    #[uniffi::constructor]
+    pub fn new_from_multisig_descriptor(descriptor: String) -> Result<Self, WalletError> {
+        let info = MultisigInfo::try_from_descriptor(&descriptor)?;
+        Wallet::try_new_persisted_from_multisig(info)
+    }
 }
 
 #[uniffi::export]
@@ -27,6 +41,7 @@ fn wallet_address_type_to_string(wallet_address_type: WalletAddressType) -> Stri
         WalletAddressType::NativeSegwit => "Native Segwit",
         WalletAddressType::WrappedSegwit => "Wrapped Segwit",
         WalletAddressType::Legacy => "Legacy",
+        WalletAddressType::Taproot => "Taproot",
     };
 
     str.to_string()
@@ -0,0 +1,327 @@
+//! User labels for transactions, addresses, outputs and xpubs, in the BIP-329 format
+//! https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+
+use std::{collections::HashMap, str::FromStr};
+
+use bitcoin::{OutPoint, Txid};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, uniffi::Record)]
+pub struct Label {
+    #[serde(rename = "type")]
+    pub label_type: LabelType,
+
+    /// The txid, address, outpoint, pubkey or xpub being labeled
+    #[serde(rename = "ref")]
+    pub reference: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Descriptor of the wallet the label came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+
+    /// Only for outputs, `false` when the output is frozen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spendable: Option<bool>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, uniffi::Enum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelType {
+    Tx,
+    Addr,
+    Pubkey,
+    Input,
+    Output,
+    Xpub,
+}
+
+/// All the labels of a wallet, one label per type and reference, saved as a list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<Label>", into = "Vec<Label>")]
+pub struct Labels(HashMap<(LabelType, String), Label>);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, uniffi::Error)]
+pub enum LabelError {
+    #[error("invalid label on line {line}: {error}")]
+    InvalidLine { line: u32, error: String },
+
+    #[error("unable to export labels: {0}")]
+    Export(String),
+}
+
+type Error = LabelError;
+
+/// Same as [`Label`] but with an unchecked type, so labels we don't store can be skipped
+#[derive(Debug, Deserialize)]
+struct RawLabel {
+    #[serde(rename = "type")]
+    label_type: String,
+    #[serde(rename = "ref")]
+    reference: String,
+    label: Option<String>,
+    origin: Option<String>,
+    spendable: Option<bool>,
+}
+
+impl Label {
+    pub fn new(label_type: LabelType, reference: impl Into<String>, label: String) -> Self {
+        Self {
+            label_type,
+            reference: reference.into(),
+            label: Some(label),
+            origin: None,
+            spendable: None,
+        }
+    }
+
+    pub fn tx(txid: Txid, label: String) -> Self {
+        Self::new(LabelType::Tx, txid.to_string(), label)
+    }
+
+    pub fn output(outpoint: OutPoint, label: String) -> Self {
+        Self::new(LabelType::Output, outpoint.to_string(), label)
+    }
+
+    /// A label without text or spendable state carries no information and is removed
+    pub fn is_empty(&self) -> bool {
+        let no_text = self.label.as_deref().unwrap_or_default().is_empty();
+        no_text && self.spendable.is_none()
+    }
+
+    /// The outpoint of an output label, `None` for other label types
+    pub fn outpoint(&self) -> Option<OutPoint> {
+        if self.label_type != LabelType::Output {
+            return None;
+        }
+
+        OutPoint::from_str(&self.reference).ok()
+    }
+}
+
+impl Labels {
+    pub fn new(labels: Vec<Label>) -> Self {
+        let mut me = Self::default();
+        me.extend(labels);
+        me
+    }
+
+    /// Parse BIP-329 JSONL, blank lines and label types we don't store are skipped
+    pub fn parse_jsonl(jsonl: &str) -> Result<Vec<Label>, Error> {
+        let mut labels = Vec::new();
+
+        for (index, line) in jsonl.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let raw =
+                serde_json::from_str::<RawLabel>(line).map_err(|error| Error::InvalidLine {
+                    line: index as u32 + 1,
+                    error: error.to_string(),
+                })?;
+
+            let Ok(label_type) = LabelType::from_str(&raw.label_type) else {
+                continue;
+            };
+
+            labels.push(Label {
+                label_type,
+                reference: raw.reference,
+                label: raw.label,
+                origin: raw.origin,
+                spendable: raw.spendable,
+            });
+        }
+
+        Ok(labels)
+    }
+
+    /// Labels are sorted by type and reference, so the same labels always export the same way
+    pub fn to_jsonl(&self) -> Result<String, Error> {
+        let mut jsonl = String::new();
+
+        for label in self.sorted() {
+            let json_line =
+                serde_json::to_string(label).map_err(|error| Error::Export(error.to_string()))?;
+
+            jsonl.push_str(&json_line);
+            jsonl.push('\n');
+        }
+
+        Ok(jsonl)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn sorted(&self) -> Vec<&Label> {
+        let mut labels = self.0.values().collect::<Vec<_>>();
+        labels.sort_by(|a, b| (a.label_type, &a.reference).cmp(&(b.label_type, &b.reference)));
+        labels
+    }
+
+    pub fn get(&self, label_type: LabelType, reference: &str) -> Option<&Label> {
+        self.0.get(&(label_type, reference.to_string()))
+    }
+
+    /// The label text for a type and reference, if the user set one
+    pub fn text(&self, label_type: LabelType, reference: &str) -> Option<String> {
+        self.get(label_type, reference)?.label.clone()
+    }
+
+    pub fn tx_label(&self, txid: &Txid) -> Option<String> {
+        self.text(LabelType::Tx, &txid.to_string())
+    }
+
+    /// The output's own label, falling back to the label of the transaction that created it
+    pub fn output_label(&self, outpoint: &OutPoint) -> Option<String> {
+        self.text(LabelType::Output, &outpoint.to_string())
+            .or_else(|| self.tx_label(&outpoint.txid))
+    }
+
+    /// Add or replace the label with the same type and reference, empty labels are removed
+    pub fn insert(&mut self, label: Label) {
+        let key = (label.label_type, label.reference.clone());
+
+        if label.is_empty() {
+            self.0.remove(&key);
+            return;
+        }
+
+        self.0.insert(key, label);
+    }
+
+    pub fn extend(&mut self, labels: impl IntoIterator<Item = Label>) {
+        for label in labels {
+            self.insert(label);
+        }
+    }
+}
+
+impl From<Vec<Label>> for Labels {
+    fn from(labels: Vec<Label>) -> Self {
+        Self::new(labels)
+    }
+}
+
+impl From<Labels> for Vec<Label> {
+    fn from(labels: Labels) -> Self {
+        labels.sorted().into_iter().cloned().collect()
+    }
+}
+
+impl FromStr for LabelType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tx" => Ok(Self::Tx),
+            "addr" => Ok(Self::Addr),
+            "pubkey" => Ok(Self::Pubkey),
+            "input" => Ok(Self::Input),
+            "output" => Ok(Self::Output),
+            "xpub" => Ok(Self::Xpub),
+            other => Err(format!("unknown label type: {other}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+
+    const BIP329_EXAMPLE: &str = r#"
+{ "type": "tx", "ref": "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd", "label": "Transaction", "origin": "wpkh([d34db33f/84'/0'/0'])" }
+{ "type": "addr", "ref": "bc1q34aq5drpuwy3wgl9lhup9892qp6svr8ldzyy7c", "label": "Address" }
+{ "type": "pubkey", "ref": "0283409659355b6d1cc3c32decd5d561abaac86c37a353b52895a5e6c196d6f448", "label": "Public Key" }
+{ "type": "input", "ref": "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd:0", "label": "Input" }
+{ "type": "output", "ref": "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd:1", "label": "Output", "spendable": false }
+{ "type": "xpub", "ref": "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8", "label": "Extended Public Key" }
+"#;
+
+    #[test]
+    fn test_parse_bip329_example() {
+        let labels = Labels::parse_jsonl(BIP329_EXAMPLE).unwrap();
+        assert_eq!(labels.len(), 6);
+
+        let labels = Labels::new(labels);
+        let txid = Txid::from_str(TXID).unwrap();
+
+        assert_eq!(labels.tx_label(&txid), Some("Transaction".to_string()));
+        assert_eq!(
+            labels.output_label(&OutPoint::new(txid, 1)),
+            Some("Output".to_string())
+        );
+
+        // outputs without their own label use the transaction label
+        assert_eq!(
+            labels.output_label(&OutPoint::new(txid, 5)),
+            Some("Transaction".to_string())
+        );
+
+        let output = labels.get(LabelType::Output, &OutPoint::new(txid, 1).to_string());
+        assert_eq!(output.and_then(|label| label.spendable), Some(false));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let labels = Labels::new(Labels::parse_jsonl(BIP329_EXAMPLE).unwrap());
+        let exported = labels.to_jsonl().unwrap();
+
+        assert_eq!(exported.lines().count(), 6);
+        assert_eq!(Labels::new(Labels::parse_jsonl(&exported).unwrap()), labels);
+    }
+
+    #[test]
+    fn test_skips_unknown_types_and_rejects_invalid_lines() {
+        let jsonl = r#"{ "type": "future", "ref": "something", "label": "skipped" }"#;
+        assert!(Labels::parse_jsonl(jsonl).unwrap().is_empty());
+
+        let jsonl = "\n{ not json }";
+        assert!(matches!(
+            Labels::parse_jsonl(jsonl),
+            Err(LabelError::InvalidLine { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_insert_replaces_and_removes() {
+        let txid = Txid::from_str(TXID).unwrap();
+
+        let mut labels = Labels::default();
+        labels.insert(Label::tx(txid, "first".to_string()));
+        labels.insert(Label::tx(txid, "second".to_string()));
+
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels.tx_label(&txid), Some("second".to_string()));
+
+        labels.insert(Label::tx(txid, String::new()));
+        assert!(labels.is_empty());
+    }
+
+    #[test]
+    fn test_saved_as_a_sorted_list() {
+        let labels = Labels::new(Labels::parse_jsonl(BIP329_EXAMPLE).unwrap());
+
+        let saved = serde_json::to_value(&labels).unwrap();
+        let saved_labels = saved.as_array().unwrap();
+        assert_eq!(saved_labels.len(), 6);
+        assert_eq!(saved_labels[0]["type"], "tx");
+        assert_eq!(saved_labels[5]["type"], "xpub");
+
+        assert_eq!(serde_json::from_value::<Labels>(saved).unwrap(), labels);
+    }
+}
@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{database::Database, network::Network};
 
-use super::{fingerprint::Fingerprint, AddressInfo, WalletAddressType};
+use super::{fingerprint::Fingerprint, multisig::MultisigInfo, AddressInfo, WalletAddressType};
+
+pub const DEFAULT_GAP_LIMIT: u32 = 25;
+pub const DEEP_RESCAN_GAP_LIMIT: u32 = 200;
 
 new_type!(WalletId, String);
 impl_default_for!(WalletId);
@@ -59,8 +62,20 @@ pub struct WalletMetadata {
     pub discovery_state: DiscoveryState,
     #[serde(default = "default_address_type")]
     pub address_type: WalletAddressType,
+    /// BIP44/49/84/86 account of a single sig wallet, the `account'` in m/84'/0'/account'
+    #[serde(default)]
+    pub account_index: u32,
     #[serde(default)]
     pub fiat_or_btc: FiatOrBtc,
+    /// Hot wallet created with a BIP39 passphrase, the user enters it again to sign
+    #[serde(default)]
+    pub has_passphrase: bool,
+    /// Threshold and cosigners, only set for multisig wallets
+    #[serde(default)]
+    pub multisig: Option<MultisigInfo>,
+    /// Gap limit and batch size used when scanning the wallet
+    #[serde(default)]
+    pub scan_settings: ScanSettings,
 
     // internal only metadata, don't use in the UI
     // note: maybe better to use a separate table for this
@@ -76,6 +91,19 @@ pub struct InternalOnlyMetadata {
     pub last_height_fetched: Option<BlockSizeLast>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq, uniffi::Record)]
+#[serde(default)]
+pub struct ScanSettings {
+    /// Unused addresses in a row a full scan looks at before stopping
+    pub gap_limit: u32,
+    /// Addresses requested from the node at once, `None` uses the default for the node type
+    pub batch_size: Option<u32>,
+    /// Block height the wallet was created at, full scans on nodes without an address index
+    /// (bitcoin core rpc and compact filters) start here, `None` starts where the wallet's
+    /// script type was first usable
+    pub birthday_height: Option<u32>,
+}
+
 #[derive(
     Debug, Clone, Copy, Default, Serialize, Deserialize, Hash, Eq, PartialEq, uniffi::Record,
 )]
@@ -169,10 +197,14 @@ impl WalletMetadata {
             sensitive_visible: true,
             details_expanded: false,
             address_type: WalletAddressType::default(),
+            account_index: 0,
             wallet_type: WalletType::Hot,
             wallet_mode,
             internal: InternalOnlyMetadata::default(),
             discovery_state: DiscoveryState::default(),
+            has_passphrase: false,
+            multisig: None,
+            scan_settings: ScanSettings::default(),
         }
     }
 
@@ -222,6 +254,7 @@ impl WalletMetadata {
             performed_full_scan: false,
             fiat_or_btc: FiatOrBtc::Btc,
             address_type: WalletAddressType::default(),
+            account_index: 0,
             selected_unit: Unit::default(),
             sensitive_visible: true,
             details_expanded: false,
@@ -229,6 +262,9 @@ impl WalletMetadata {
             wallet_mode: WalletMode::Main,
             internal: InternalOnlyMetadata::default(),
             discovery_state: DiscoveryState::default(),
+            has_passphrase: false,
+            multisig: None,
+            scan_settings: ScanSettings::default(),
         }
     }
 
@@ -241,6 +277,27 @@ impl WalletMetadata {
     }
 }
 
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            gap_limit: DEFAULT_GAP_LIMIT,
+            batch_size: None,
+            birthday_height: None,
+        }
+    }
+}
+
+impl ScanSettings {
+    /// Settings for a one off full scan that looks much further ahead,
+    /// for wallets restored from services that skip a lot of addresses
+    pub fn deep_rescan(&self) -> Self {
+        Self {
+            gap_limit: self.gap_limit.max(DEEP_RESCAN_GAP_LIMIT),
+            ..*self
+        }
+    }
+}
+
 impl InternalOnlyMetadata {
     pub fn last_seen_address_index(&self, addreses: &[AddressInfo]) -> Option<usize> {
         let address_index = self.address_index.as_ref()?;
@@ -0,0 +1,365 @@
+//! Multisig wallets, `wsh(sortedmulti(...))` descriptors built from cosigner xpubs
+
+use std::{str::FromStr as _, sync::Arc};
+
+use bdk_wallet::{
+    bitcoin::{
+        bip32::{ChildNumber, DerivationPath, Fingerprint as BdkFingerprint, Xpriv, Xpub},
+        secp256k1::Secp256k1,
+    },
+    descriptor::ExtendedDescriptor,
+    keys::DescriptorPublicKey,
+    miniscript::{
+        descriptor::{DescriptorXKey, Wildcard, WshInner},
+        Descriptor,
+    },
+    KeychainKind,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    database::Database, keychain::Keychain, keys::Descriptors, network::Network, psbt::BdkPsbt,
+    transaction::ffi::BitcoinTransaction,
+};
+
+use super::metadata::WalletType;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, uniffi::Record)]
+pub struct Cosigner {
+    pub name: String,
+    /// Master fingerprint of the cosigner's device, hex encoded
+    pub fingerprint: String,
+    /// Path from the master key to `xpub`, ex: `m/48'/0'/0'/2'`
+    pub derivation_path: String,
+    pub xpub: String,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, uniffi::Record)]
+pub struct MultisigInfo {
+    /// Number of signatures needed to spend
+    pub threshold: u8,
+    pub cosigners: Vec<Cosigner>,
+}
+
+/// How far along a multisig transaction is in collecting signatures
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, uniffi::Record)]
+pub struct SigningProgress {
+    pub signatures: u8,
+    pub required: u8,
+}
+
+/// Result of adding signatures to a multisig transaction
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MultisigSigningState {
+    pub progress: SigningProgress,
+    /// The finalized transaction, once enough cosigners have signed
+    pub transaction: Option<Arc<BitcoinTransaction>>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, thiserror::Error, uniffi::Error)]
+pub enum MultisigError {
+    #[error("invalid multisig descriptor: {0}")]
+    InvalidDescriptor(String),
+
+    #[error("only wsh(sortedmulti(...)) multisig descriptors are supported")]
+    UnsupportedDescriptor,
+
+    #[error("invalid threshold {threshold} for {cosigners} cosigners")]
+    InvalidThreshold { threshold: u8, cosigners: u8 },
+
+    #[error("invalid cosigner {name}: {error}")]
+    InvalidCosigner { name: String, error: String },
+
+    #[error("cosigner {0} was added more than once")]
+    DuplicateCosigner(String),
+
+    #[error("unable to sign with local cosigner: {0}")]
+    SignError(String),
+}
+
+type Error = MultisigError;
+
+impl Cosigner {
+    fn xkey(&self, keychain: KeychainKind) -> Result<DescriptorPublicKey, Error> {
+        let invalid = |error: String| Error::InvalidCosigner {
+            name: self.name.clone(),
+            error,
+        };
+
+        let fingerprint = BdkFingerprint::from_str(&self.fingerprint)
+            .map_err(|error| invalid(format!("fingerprint: {error}")))?;
+
+        let origin_path = DerivationPath::from_str(&self.derivation_path)
+            .map_err(|error| invalid(format!("derivation path: {error}")))?;
+
+        let xkey = Xpub::from_str(&self.xpub).map_err(|error| invalid(format!("xpub: {error}")))?;
+
+        let keychain_index = match keychain {
+            KeychainKind::External => 0,
+            KeychainKind::Internal => 1,
+        };
+
+        Ok(DescriptorPublicKey::XPub(DescriptorXKey {
+            origin: Some((fingerprint, origin_path)),
+            xkey,
+            derivation_path: vec![ChildNumber::Normal {
+                index: keychain_index,
+            }]
+            .into(),
+            wildcard: Wildcard::Unhardened,
+        }))
+    }
+
+    /// Cosigner from a descriptor key, only `/0/*`, `/1/*` and `/<0;1>/*` keys are supported
+    fn try_from_key(key: &DescriptorPublicKey, index: usize) -> Result<Self, Error> {
+        let name = format!("Cosigner {}", index + 1);
+        let unsupported = || Error::InvalidCosigner {
+            name: name.clone(),
+            error: format!("unsupported key: {key}"),
+        };
+
+        let (origin, xkey, wildcard, valid_paths) = match key {
+            DescriptorPublicKey::XPub(xkey) => {
+                let path = xkey.derivation_path.to_string();
+                let valid_path = path == "0" || path == "1";
+                (&xkey.origin, xkey.xkey, xkey.wildcard, valid_path)
+            }
+            DescriptorPublicKey::MultiXPub(xkey) => {
+                let valid_paths = xkey.derivation_paths.paths().len() == 2;
+                (&xkey.origin, xkey.xkey, xkey.wildcard, valid_paths)
+            }
+            DescriptorPublicKey::Single(_) => return Err(unsupported()),
+        };
+
+        if !valid_paths || wildcard != Wildcard::Unhardened {
+            return Err(unsupported());
+        }
+
+        let (fingerprint, derivation_path) = match origin {
+            Some((fingerprint, path)) => (*fingerprint, path.clone()),
+            None => (xkey.fingerprint(), DerivationPath::master()),
+        };
+
+        Ok(Self {
+            name,
+            fingerprint: fingerprint.to_string(),
+            derivation_path: derivation_path_string(&derivation_path),
+            xpub: xkey.to_string(),
+        })
+    }
+}
+
+impl MultisigInfo {
+    pub fn try_new(threshold: u8, cosigners: Vec<Cosigner>) -> Result<Self, Error> {
+        let me = Self {
+            threshold,
+            cosigners,
+        };
+
+        me.validate()?;
+        Ok(me)
+    }
+
+    /// Parse a `wsh(sortedmulti(...))` descriptor, with `/0/*` or `/<0;1>/*` keys
+    pub fn try_from_descriptor(descriptor: &str) -> Result<Self, Error> {
+        let descriptor = Descriptor::<DescriptorPublicKey>::from_str(descriptor.trim())
+            .map_err(|error| Error::InvalidDescriptor(error.to_string()))?;
+
+        let Descriptor::Wsh(wsh) = &descriptor else {
+            return Err(Error::UnsupportedDescriptor);
+        };
+
+        let WshInner::SortedMulti(sorted_multi) = wsh.as_inner() else {
+            return Err(Error::UnsupportedDescriptor);
+        };
+
+        let cosigners = sorted_multi
+            .pks()
+            .iter()
+            .enumerate()
+            .map(|(index, key)| Cosigner::try_from_key(key, index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::try_new(sorted_multi.k() as u8, cosigners)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let cosigners = self.cosigners.len() as u8;
+        if self.threshold == 0 || self.threshold > cosigners || cosigners < 2 {
+            return Err(Error::InvalidThreshold {
+                threshold: self.threshold,
+                cosigners,
+            });
+        }
+
+        for (index, cosigner) in self.cosigners.iter().enumerate() {
+            let duplicate = self.cosigners[..index]
+                .iter()
+                .any(|other| other.xpub == cosigner.xpub);
+
+            if duplicate {
+                return Err(Error::DuplicateCosigner(cosigner.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn descriptor(&self, keychain: KeychainKind) -> Result<ExtendedDescriptor, Error> {
+        let keys = self
+            .cosigners
+            .iter()
+            .map(|cosigner| cosigner.xkey(keychain))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Descriptor::new_wsh_sortedmulti(self.threshold as usize, keys)
+            .map_err(|error| Error::InvalidDescriptor(error.to_string()))
+    }
+
+    pub fn descriptors(&self) -> Result<Descriptors, Error> {
+        let external = self.descriptor(KeychainKind::External)?.into();
+        let internal = self.descriptor(KeychainKind::Internal)?.into();
+
+        Ok(Descriptors { external, internal })
+    }
+
+    /// Signatures collected so far, the input with the fewest signatures decides the progress
+    pub fn signing_progress(&self, psbt: &BdkPsbt) -> SigningProgress {
+        let signatures = psbt
+            .inputs
+            .iter()
+            .map(|input| {
+                let finalized =
+                    input.final_script_witness.is_some() || input.final_script_sig.is_some();
+
+                if finalized {
+                    self.threshold as usize
+                } else {
+                    input.partial_sigs.len()
+                }
+            })
+            .min()
+            .unwrap_or_default();
+
+        SigningProgress {
+            signatures: signatures.min(u8::MAX as usize) as u8,
+            required: self.threshold,
+        }
+    }
+
+    /// Sign with every cosigner that is also a hot wallet on this device, returns how many signed
+    pub fn sign_with_local_cosigners(
+        &self,
+        psbt: &mut BdkPsbt,
+        network: Network,
+    ) -> Result<usize, Error> {
+        let mode = Database::global().global_config.wallet_mode();
+        let hot_wallets = Database::global()
+            .wallets
+            .get_all(network, mode)
+            .map_err(|error| Error::SignError(error.to_string()))?
+            .into_iter()
+            // passphrase wallets can't sign without the user entering it
+            .filter(|wallet| wallet.wallet_type == WalletType::Hot && !wallet.has_passphrase);
+
+        let secp = Secp256k1::new();
+        let mut signed = 0;
+
+        for wallet in hot_wallets {
+            let Some(fingerprint) = wallet.master_fingerprint.as_ref() else {
+                continue;
+            };
+            let fingerprint = fingerprint.as_lowercase();
+
+            let is_cosigner = self
+                .cosigners
+                .iter()
+                .any(|cosigner| cosigner.fingerprint.to_ascii_lowercase() == fingerprint);
+
+            if !is_cosigner {
+                continue;
+            }
+
+            let Ok(Some(mnemonic)) = Keychain::global().get_wallet_key(&wallet.id) else {
+                warn!("cosigner {fingerprint} is a hot wallet, but its mnemonic is missing");
+                continue;
+            };
+
+            let seed = mnemonic.to_seed("");
+            let xpriv = Xpriv::new_master(bitcoin::Network::from(network), &seed)
+                .map_err(|error| Error::SignError(error.to_string()))?;
+
+            match psbt.sign(&xpriv, &secp) {
+                Ok(keys) => {
+                    debug!("signed {} inputs with cosigner {fingerprint}", keys.len());
+                    signed += 1;
+                }
+                Err((_, errors)) => {
+                    let error = format!("{errors:?}");
+                    return Err(Error::SignError(error));
+                }
+            }
+        }
+
+        Ok(signed)
+    }
+}
+
+/// `DerivationPath` displays without the leading `m`
+fn derivation_path_string(path: &DerivationPath) -> String {
+    if path.is_master() {
+        return "m".to_string();
+    }
+
+    format!("m/{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-48 testnet keys of the abandon mnemonic, accounts 0, 1 and 2
+    const DESCRIPTOR: &str = "wsh(sortedmulti(2,[73c5da0a/48'/1'/0'/2']tpubDFH9dgzveyD8zTbPUFuLrGmCydNvxehyNdUXKJAQN8x4aZ4j6UZqGfnqFrD4NqyaTVGKbvEW54tsvPTK2UoSbCC1PJY8iCNiwTL3RWZEheQ/<0;1>/*,[73c5da0a/48'/1'/1'/2']tpubDEYM1BmQ5rp2PWKvCgvQxNeUrEv8gu5819xRdmu6S23fYpS8x2icwAeoVaBTLyN3fGWJQcWoaiKMduTXWKtG9bXNpVrZPRF7XVxrANtAEcR/<0;1>/*,[73c5da0a/48'/1'/2'/2']tpubDEPGZXGPF2v3uNGTWHteRSQnZdEFfMvh6Dcp3DykkBE48hTA6pe3Es6nWH5dAThPyvqmZGmZqQhTw5kxqgR5uJW6Ei2VWyAAN92yLcYBvzV/<0;1>/*))";
+
+    #[test]
+    fn test_parse_multipath_descriptor() {
+        let info = MultisigInfo::try_from_descriptor(DESCRIPTOR).unwrap();
+
+        assert_eq!(info.threshold, 2);
+        assert_eq!(info.cosigners.len(), 3);
+        assert_eq!(info.cosigners[0].fingerprint, "73c5da0a");
+        assert_eq!(info.cosigners[0].derivation_path, "m/48'/1'/0'/2'");
+    }
+
+    #[test]
+    fn test_descriptors_round_trip() {
+        let info = MultisigInfo::try_from_descriptor(DESCRIPTOR).unwrap();
+
+        let external = info.descriptor(KeychainKind::External).unwrap();
+        let internal = info.descriptor(KeychainKind::Internal).unwrap();
+        assert_ne!(external, internal);
+
+        let reparsed = MultisigInfo::try_from_descriptor(&external.to_string()).unwrap();
+        assert_eq!(reparsed, info);
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let info = MultisigInfo::try_from_descriptor(DESCRIPTOR).unwrap();
+
+        let error = MultisigInfo::try_new(4, info.cosigners.clone()).unwrap_err();
+        assert!(matches!(error, Error::InvalidThreshold { .. }));
+
+        let error = MultisigInfo::try_new(1, info.cosigners[..1].to_vec()).unwrap_err();
+        assert!(matches!(error, Error::InvalidThreshold { .. }));
+    }
+
+    #[test]
+    fn test_rejects_single_sig_descriptor() {
+        let descriptor = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)";
+        let error = MultisigInfo::try_from_descriptor(descriptor).unwrap_err();
+
+        assert_eq!(error, Error::UnsupportedDescriptor);
+    }
+}
@@ -0,0 +1,271 @@
+//! Checks run on a signed transaction right before it is broadcast
+
+use std::sync::Arc;
+
+use bitcoin::{params::Params, Amount as BdkAmount, Transaction};
+
+use crate::{network::Network, transaction::Amount};
+
+use super::{confirm::ConfirmDetails, Address};
+
+/// Fees under this amount are never considered absurd, small sends can have a high fee percentage
+const ABSURD_FEE_MIN: BdkAmount = BdkAmount::from_sat(20_000);
+
+/// Fee is considered absurd when it is more than this percentage of the amount being sent
+const ABSURD_FEE_PERCENT: u64 = 25;
+
+type Error = SafetyCheckError;
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, thiserror::Error, uniffi::Error)]
+pub enum SafetyCheckError {
+    #[error("fee of {} is too high for sending {}", fee.0, sending_amount.0)]
+    AbsurdFee {
+        fee: Arc<Amount>,
+        sending_amount: Arc<Amount>,
+    },
+
+    #[error("output of {} to {address} is below the dust limit", amount.0)]
+    DustOutput {
+        address: Arc<Address>,
+        amount: Arc<Amount>,
+    },
+
+    /// A dust output paying a script that has no address form, ex: a bare multisig
+    #[error("output of {} to script {script} is below the dust limit", amount.0)]
+    DustScriptOutput { script: String, amount: Arc<Amount> },
+
+    #[error("signed transaction does not match the approved transaction, {0}")]
+    TransactionMismatch(String),
+}
+
+/// Run all safety checks for a transaction the user approved with `details`
+///
+/// `fee` is the fee of the signed transaction, calculated from the wallet's own view of the inputs
+pub fn check_transaction(
+    transaction: &Transaction,
+    details: &ConfirmDetails,
+    fee: BdkAmount,
+    network: Network,
+) -> Result<(), Error> {
+    check_matches_details(transaction, details, fee)?;
+    check_dust_outputs(transaction, network)?;
+    check_fee(fee, details.sending_amount.0)?;
+
+    Ok(())
+}
+
+/// The signed transaction must spend the same inputs, to the same outputs as the one the user saw
+fn check_matches_details(
+    transaction: &Transaction,
+    details: &ConfirmDetails,
+    fee: BdkAmount,
+) -> Result<(), Error> {
+    let approved = &details.psbt.0.unsigned_tx;
+
+    // the ntxid ignores the script sigs, so it stays the same after signing legacy inputs
+    if transaction.compute_ntxid() != approved.compute_ntxid() {
+        return Err(Error::TransactionMismatch(
+            "inputs or outputs have changed".to_string(),
+        ));
+    }
+
+    if fee != details.fee_total.0 {
+        return Err(Error::TransactionMismatch(format!(
+            "fee changed from {} to {fee}",
+            details.fee_total.0
+        )));
+    }
+
+    for recipient in details.recipients() {
+        let script_pubkey = recipient.address.script_pubkey();
+        let sends_approved_amount = transaction.output.iter().any(|output| {
+            output.script_pubkey == script_pubkey && output.value == recipient.amount.0
+        });
+
+        if !sends_approved_amount {
+            return Err(Error::TransactionMismatch(format!(
+                "no output sending {} to {}",
+                recipient.amount.0, recipient.address
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_dust_outputs(transaction: &Transaction, network: Network) -> Result<(), Error> {
+    let dust_output = transaction.output.iter().find(|output| {
+        !output.script_pubkey.is_op_return()
+            && output.value < output.script_pubkey.minimal_non_dust()
+    });
+
+    let Some(output) = dust_output else { return Ok(()) };
+
+    let amount = Arc::new(output.value.into());
+    let Ok(address) = bitcoin::Address::from_script(&output.script_pubkey, Params::from(network))
+    else {
+        let script = output.script_pubkey.to_hex_string();
+        return Err(Error::DustScriptOutput { script, amount });
+    };
+
+    Err(Error::DustOutput {
+        address: Arc::new(address.into()),
+        amount,
+    })
+}
+
+fn check_fee(fee: BdkAmount, sending_amount: BdkAmount) -> Result<(), Error> {
+    if fee <= ABSURD_FEE_MIN {
+        return Ok(());
+    }
+
+    let max_fee = sending_amount.to_sat().saturating_mul(ABSURD_FEE_PERCENT) / 100;
+    if fee.to_sat() <= max_fee {
+        return Ok(());
+    }
+
+    Err(Error::AbsurdFee {
+        fee: Arc::new(fee.into()),
+        sending_amount: Arc::new(sending_amount.into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        absolute, hashes::Hash as _, transaction, OutPoint, ScriptBuf, TxIn, TxOut, Txid,
+        WPubkeyHash,
+    };
+
+    use super::*;
+    use crate::psbt::BdkPsbt;
+
+    const FEE: BdkAmount = BdkAmount::from_sat(1_000);
+
+    fn p2wpkh(byte: u8) -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([byte; 20]))
+    }
+
+    fn unsigned_tx(output: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 0),
+                ..Default::default()
+            }],
+            output,
+        }
+    }
+
+    /// Details the user approved, sending the first output of `tx`
+    fn approved_details(tx: &Transaction) -> ConfirmDetails {
+        let psbt = BdkPsbt::from_unsigned_tx(tx.clone()).unwrap();
+        ConfirmDetails::new_for_test(psbt, FEE)
+    }
+
+    fn outputs() -> Vec<TxOut> {
+        vec![
+            TxOut {
+                value: BdkAmount::from_sat(50_000),
+                script_pubkey: p2wpkh(1),
+            },
+            TxOut {
+                value: BdkAmount::from_sat(20_000),
+                script_pubkey: p2wpkh(2),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_matches_details_after_filling_script_sig() {
+        let approved = unsigned_tx(outputs());
+        let details = approved_details(&approved);
+
+        // signing a legacy or wrapped segwit input changes the txid, but not the ntxid
+        let mut signed = approved.clone();
+        signed.input[0].script_sig = ScriptBuf::from_bytes(vec![0x16, 0x00, 0x14]);
+        assert_ne!(signed.compute_txid(), approved.compute_txid());
+
+        assert_eq!(check_matches_details(&signed, &details, FEE), Ok(()));
+    }
+
+    #[test]
+    fn test_changed_transaction_does_not_match() {
+        let approved = unsigned_tx(outputs());
+        let details = approved_details(&approved);
+
+        let mut changed_output = approved.clone();
+        changed_output.output[0].script_pubkey = p2wpkh(3);
+        assert!(matches!(
+            check_matches_details(&changed_output, &details, FEE),
+            Err(Error::TransactionMismatch(_))
+        ));
+
+        let higher_fee = FEE + BdkAmount::from_sat(1);
+        assert!(matches!(
+            check_matches_details(&approved, &details, higher_fee),
+            Err(Error::TransactionMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_dust_outputs() {
+        let tx = unsigned_tx(outputs());
+        assert_eq!(check_dust_outputs(&tx, Network::Bitcoin), Ok(()));
+
+        // op returns carry no value, they are never dust
+        let mut op_return = outputs();
+        op_return.push(TxOut {
+            value: BdkAmount::ZERO,
+            script_pubkey: ScriptBuf::new_op_return([1, 2, 3]),
+        });
+        assert_eq!(
+            check_dust_outputs(&unsigned_tx(op_return), Network::Bitcoin),
+            Ok(())
+        );
+
+        let mut dust = outputs();
+        dust[1].value = BdkAmount::from_sat(100);
+        assert!(matches!(
+            check_dust_outputs(&unsigned_tx(dust), Network::Bitcoin),
+            Err(Error::DustOutput { .. })
+        ));
+
+        // scripts without an address are reported with the raw script
+        let mut bare = outputs();
+        bare[1] = TxOut {
+            value: BdkAmount::from_sat(100),
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+        };
+        assert_eq!(
+            check_dust_outputs(&unsigned_tx(bare), Network::Bitcoin),
+            Err(Error::DustScriptOutput {
+                script: "51".to_string(),
+                amount: Arc::new(BdkAmount::from_sat(100).into()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_small_fee_is_never_absurd() {
+        let fee = BdkAmount::from_sat(15_000);
+        let sending_amount = BdkAmount::from_sat(10_000);
+
+        assert!(check_fee(fee, sending_amount).is_ok());
+    }
+
+    #[test]
+    fn test_fee_relative_to_amount() {
+        let sending_amount = BdkAmount::from_sat(1_000_000);
+
+        assert!(check_fee(BdkAmount::from_sat(250_000), sending_amount).is_ok());
+        assert_eq!(
+            check_fee(BdkAmount::from_sat(250_001), sending_amount),
+            Err(Error::AbsurdFee {
+                fee: Arc::new(BdkAmount::from_sat(250_001).into()),
+                sending_amount: Arc::new(sending_amount.into()),
+            })
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Unspent outputs owned by the wallet, and the user's choice of which ones to spend
+
+use std::sync::Arc;
+
+use bdk_chain::{ChainPosition, ConfirmationBlockTime};
+use bdk_wallet::{
+    bitcoin::{params::Params, Network, OutPoint as BdkOutPoint},
+    tx_builder::{AddUtxoError, TxBuilder},
+    KeychainKind, LocalOutput,
+};
+
+use crate::transaction::{Amount, OutPoint};
+
+use super::Address;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct Utxo {
+    pub outpoint: Arc<OutPoint>,
+    pub amount: Arc<Amount>,
+    pub address: Arc<Address>,
+    pub status: UtxoStatus,
+    /// Output was sent to one of our change addresses
+    pub is_change: bool,
+    /// Frozen by the user, never spent by new transactions
+    pub is_frozen: bool,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum UtxoStatus {
+    Confirmed {
+        block_height: u32,
+        confirmed_at: u64,
+    },
+    Unconfirmed {
+        last_seen: u64,
+    },
+}
+
+/// Which of the wallet's UTXOs a new transaction is allowed to spend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct CoinControl {
+    /// UTXOs that must be spent by the transaction
+    pub include: Vec<Arc<OutPoint>>,
+    /// UTXOs that must never be spent by the transaction
+    pub exclude: Vec<Arc<OutPoint>>,
+    /// Only spend the included UTXOs, instead of adding more as needed
+    pub only_include: bool,
+}
+
+impl Utxo {
+    pub fn try_new(output: LocalOutput, network: Network, is_frozen: bool) -> eyre::Result<Self> {
+        let address =
+            bitcoin::Address::from_script(&output.txout.script_pubkey, Params::from(network))?;
+
+        let status = match output.chain_position {
+            ChainPosition::Confirmed {
+                anchor:
+                    ConfirmationBlockTime {
+                        block_id,
+                        confirmation_time,
+                    },
+                ..
+            } => UtxoStatus::Confirmed {
+                block_height: block_id.height,
+                confirmed_at: confirmation_time,
+            },
+            ChainPosition::Unconfirmed { last_seen } => UtxoStatus::Unconfirmed {
+                last_seen: last_seen.unwrap_or_default(),
+            },
+        };
+
+        Ok(Self {
+            outpoint: Arc::new(output.outpoint.into()),
+            amount: Arc::new(output.txout.value.into()),
+            address: Arc::new(address.into()),
+            status,
+            is_change: output.keychain == KeychainKind::Internal,
+            is_frozen,
+            label: None,
+        })
+    }
+}
+
+impl CoinControl {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn included(&self) -> Vec<BdkOutPoint> {
+        self.include
+            .iter()
+            .map(|outpoint| outpoint.as_ref().into())
+            .collect()
+    }
+
+    pub fn excluded(&self) -> Vec<BdkOutPoint> {
+        self.exclude
+            .iter()
+            .map(|outpoint| outpoint.as_ref().into())
+            .collect()
+    }
+
+    /// Restrict the coins the builder can select, excluded coins win over included ones
+    pub fn apply<Cs>(&self, tx_builder: &mut TxBuilder<'_, Cs>) -> Result<(), AddUtxoError> {
+        let excluded = self.excluded();
+        let included = self
+            .included()
+            .into_iter()
+            .filter(|outpoint| !excluded.contains(outpoint))
+            .collect::<Vec<_>>();
+
+        if !included.is_empty() {
+            tx_builder.add_utxos(&included)?;
+        }
+
+        if self.only_include {
+            tx_builder.manually_selected_only();
+        }
+
+        for outpoint in excluded {
+            tx_builder.add_unspendable(outpoint);
+        }
+
+        Ok(())
+    }
+}
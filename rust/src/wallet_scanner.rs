@@ -11,6 +11,11 @@ use tracing::{debug, error, info, warn};
 
 /// Default number of addresses to scan
 const DEFAULT_SCAN_LIMIT: u32 = 125;
+/// Addresses checked at the start of each account when looking for used accounts
+const ACCOUNT_PROBE_LIMIT: u32 = 20;
+
+/// Gap limit used while discovering which address types an imported wallet has used
+const DISCOVERY_GAP_LIMIT: u32 = 50;
 
 use crate::{
     database::{
@@ -22,11 +27,14 @@ use crate::{
     mnemonic::MnemonicExt,
     node::{
         client::{NodeClient, NodeClientOptions},
+        pool::NodePool,
         Node,
     },
     task::spawn_actor,
     wallet::{
-        metadata::{DiscoveryState, FoundAddress, FoundJson, WalletId, WalletMetadata},
+        metadata::{
+            DiscoveryState, FoundAddress, FoundJson, ScanSettings, WalletId, WalletMetadata,
+        },
         WalletAddressType, WalletError,
     },
 };
@@ -34,7 +42,7 @@ use crate::{
 #[derive(
     Debug, Default, derive_more::From, derive_more::Into, derive_more::Deref, derive_more::DerefMut,
 )]
-pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 2]);
+pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 3]);
 
 #[derive(
     Debug,
@@ -45,7 +53,7 @@ pub struct Wallets([Option<(WalletAddressType, BdkWallet)>; 2]);
     derive_more::Deref,
     derive_more::DerefMut,
 )]
-pub struct Workers([Option<WorkerHandle>; 2]);
+pub struct Workers([Option<WorkerHandle>; 3]);
 
 #[derive(Debug, Clone)]
 pub struct WorkerHandle {
@@ -76,6 +84,9 @@ pub enum WalletScannerError {
 
     #[error("No mnemonic available for id {0}")]
     NoMnemonicAvailable(WalletId),
+
+    #[error("Unable to check addresses for history: {0}")]
+    NodeError(String),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, uniffi::Enum)]
@@ -84,6 +95,14 @@ pub enum ScannerResponse {
     NoneFound,
 }
 
+/// Account of a seed with history, ex: 1 for m/84'/0'/1'
+#[derive(Debug, Clone, Eq, PartialEq, uniffi::Record)]
+pub struct FoundAccount {
+    pub account: u32,
+    pub address_type: WalletAddressType,
+    pub first_address: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct NodeClientBuilder {
     pub node: Node,
@@ -172,10 +191,13 @@ impl WalletScanner {
             return Err(WalletScannerError::NoAddressTypes);
         }
 
+        // look further ahead than a regular scan, unless the wallet is set to go even further
         let node = db.global_config().selected_node();
+        let settings = metadata.scan_settings;
         let options = NodeClientOptions {
-            batch_size: 1,
-            stop_gap: 50,
+            batch_size: settings.batch_size.unwrap_or(1) as usize,
+            stop_gap: settings.gap_limit.max(DISCOVERY_GAP_LIMIT) as usize,
+            start_height: settings.birthday_height.unwrap_or(0),
         };
 
         let client_builder = NodeClientBuilder { node, options };
@@ -229,6 +251,58 @@ impl WalletScanner {
         }
     }
 
+    /// Probe accounts `0..number_of_accounts` of the seed for history, only the first
+    /// addresses of each account are checked, each check fails over to the next node in the pool
+    pub async fn find_used_accounts(
+        mnemonic: &Mnemonic,
+        passphrase: Option<String>,
+        address_type: WalletAddressType,
+        number_of_accounts: u32,
+        node_pool: &mut NodePool,
+        settings: ScanSettings,
+    ) -> Result<Vec<FoundAccount>, WalletScannerError> {
+        let network = Database::global().global_config.selected_network();
+        let mut found_accounts = vec![];
+
+        for account in 0..number_of_accounts {
+            let wallet = mnemonic
+                .clone()
+                .into_descriptors_for_account(passphrase.clone(), network, address_type, account)
+                .into_create_params()
+                .network(network.into())
+                .create_wallet_no_persist()
+                .map_err(|error| WalletError::BdkError(error.to_string()))?;
+
+            for index in 0..ACCOUNT_PROBE_LIMIT {
+                let address = wallet.peek_address(KeychainKind::External, index).address;
+                let check_address = move |client: NodeClient| {
+                    let address = address.clone();
+                    async move { client.check_address_for_txn(address).await }
+                };
+
+                let has_txns = node_pool
+                    .call(settings, check_address)
+                    .await
+                    .map_err(|error| WalletScannerError::NodeError(error.to_string()))?;
+
+                if has_txns {
+                    let first_address = wallet.peek_address(KeychainKind::External, 0).address;
+                    found_accounts.push(FoundAccount {
+                        account,
+                        address_type,
+                        first_address: first_address.to_string(),
+                    });
+
+                    break;
+                }
+            }
+
+            debug!("checked account {account} for {address_type}");
+        }
+
+        Ok(found_accounts)
+    }
+
     async fn start_workers(&mut self) -> ActorResult<()> {
         let parent = self.addr.clone();
 
@@ -540,6 +614,7 @@ fn index(type_: WalletAddressType) -> usize {
     match type_ {
         WalletAddressType::WrappedSegwit => 0,
         WalletAddressType::Legacy => 1,
+        WalletAddressType::Taproot => 2,
         WalletAddressType::NativeSegwit => panic!("Not scanning the default one NativeSegwit"),
     }
 }
@@ -551,6 +626,7 @@ impl Wallets {
         for (json, type_) in [
             (&json.bip49, WalletAddressType::WrappedSegwit),
             (&json.bip44, WalletAddressType::Legacy),
+            (&json.bip86, WalletAddressType::Taproot),
         ] {
             if let Some(json) = json {
                 let params = BdkWallet::create(json.external.clone(), json.internal.clone())
@@ -569,7 +645,13 @@ impl Wallets {
     pub fn try_from_mnemonic(mnemonic: &Mnemonic, network: Network) -> Result<Self, WalletError> {
         let mut wallets = Wallets::default();
 
-        for type_ in [WalletAddressType::WrappedSegwit, WalletAddressType::Legacy] {
+        let types = [
+            WalletAddressType::WrappedSegwit,
+            WalletAddressType::Legacy,
+            WalletAddressType::Taproot,
+        ];
+
+        for type_ in types {
             let descriptor = mnemonic.clone().into_descriptors(None, network, type_);
             let wallet = BdkWallet::create(
                 descriptor.external.into_tuple(),